@@ -0,0 +1,36 @@
+//! 消息生成基准：10,000 架飞机时单线程与 rayon 线程池的耗时对比
+//!
+//! 运行：`cargo bench --bench messages`
+
+use adsb_lib::adsb::AdsbSimulator;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const FLEET_SIZE: usize = 10_000;
+
+fn fleet() -> AdsbSimulator {
+    let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 1);
+    sim.generate_mock_aircrafts(FLEET_SIZE).unwrap();
+    sim
+}
+
+fn generate_all_messages(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generate_all_messages/10000");
+    group.sample_size(20);
+
+    // 单线程线程池即为串行生成
+    let serial = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+    let mut sim = fleet();
+    group.bench_function("serial", |b| {
+        b.iter(|| serial.install(|| sim.generate_all_messages()))
+    });
+
+    let mut sim = fleet();
+    group.bench_function("parallel", |b| b.iter(|| sim.generate_all_messages()));
+    group.finish();
+}
+
+criterion_group!(benches, generate_all_messages);
+criterion_main!(benches);
@@ -1,8 +1,85 @@
+use crate::geo;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::f64::consts::PI;
+use std::fs;
+
+/// 每架飞机默认保留的航迹点数（1 点/秒，约 1 小时）
+pub const DEFAULT_HISTORY_POINTS: usize = 3600;
+/// 全体飞机航迹点总数的缺省上限（每点约 32 字节，约 16 MB）
+pub const DEFAULT_HISTORY_BUDGET_POINTS: usize = 500_000;
+/// 速度消息可编码的最大垂直速率 (ft/min)：9 位字段，(511 - 1) * 64
+pub const MAX_VERTICAL_RATE_FPM: f64 = 32640.0;
+
+/// 距航路点小于该距离 (nm) 视为到达
+const WAYPOINT_ARRIVAL_NM: f64 = 1.0;
+/// 沿航路飞行时的最大转弯率 (度/秒，标准转弯率)
+const MAX_TURN_RATE_DEG: f64 = 3.0;
+/// 沿航路飞行时的爬升/下降率 (ft/s，约 2000 ft/min)
+const ROUTE_CLIMB_RATE_FPS: f64 = 2000.0 / 60.0;
+/// 生成航班时大圆航线的采样间隔 (nm)
+const FLIGHT_WAYPOINT_SPACING_NM: f64 = 20.0;
+/// 生成航班时起降端的高度 (ft)
+const FLIGHT_TERMINAL_ALTITUDE: f64 = 1000.0;
+/// 生成航班时的爬升/下降梯度 (ft/nm，约 3°)
+const FLIGHT_CLIMB_GRADIENT: f64 = 318.0;
+/// 等待航线的空速 (节)
+const HOLDING_SPEED_KTS: f64 = 230.0;
+/// 着陆引导时在跑道延长线上的前视距离 (nm)
+const APPROACH_LEAD_NM: f64 = 2.0;
+/// 从跑道另一侧进入时先飞往的最后进近定位点距入口的距离 (nm)
+const FINAL_APPROACH_FIX_NM: f64 = 8.0;
+/// 判定已建立五边时允许的最大侧向偏差 (nm)
+const ESTABLISHED_CROSS_TRACK_NM: f64 = 0.5;
+/// 判定已建立五边时允许的最大航向偏差 (度)
+const ESTABLISHED_HEADING_DEG: f64 = 15.0;
+/// 越过入口后仍允许继续下降接地的接地区长度 (nm)
+const TOUCHDOWN_ZONE_NM: f64 = 0.5;
+/// 离场飞机沿跑道方向爬升的航段长度 (nm)
+const DEPARTURE_LEG_NM: f64 = 30.0;
+/// 离场飞机的初始爬升高度 (ft)
+const DEPARTURE_ALTITUDE: f64 = 10000.0;
+/// 离场飞机的抬轮速度 (kts)
+const DEPARTURE_SPEED_KTS: f64 = 160.0;
+/// 离场飞机出现时的离地高度 (ft)：抬轮后越过跑道末端的起飞越障高度
+const DEPARTURE_SCREEN_HEIGHT_FT: f64 = 35.0;
+/// 平行跑道允许的最大方向差 (度)
+const PARALLEL_RUNWAY_TOLERANCE_DEG: f64 = 5.0;
+/// CPR 纬度分区数
+const CPR_NZ: f64 = 15.0;
+/// CPR 编码位数对应的分辨率 (2^17)
+const CPR_SCALE: f64 = 131072.0;
+/// 全局 CPR 解码要求奇偶帧的最大时间间隔 (s)
+const CPR_PAIR_WINDOW_S: u64 = 10;
+/// 新加入飞机默认的 GNSS 捕获时长 (tick)
+pub const DEFAULT_ACQUISITION_TICKS: u32 = 5;
+/// 飞行阶段驱动的爬升/下降率 (ft/s，约 1500 ft/min)
+const PHASE_VERTICAL_RATE_FPS: f64 = 1500.0 / 60.0;
+/// 地面飞机所在的机场标高 (ft)
+const FIELD_ELEVATION_FT: f64 = 0.0;
+/// 应答机编码缺省值 (八进制 1200，目视飞行)
+const DEFAULT_SQUAWK: u16 = 0o1200;
+/// Comm-B 识别应答的 BDS 寄存器号 (BDS 2,0)
+const BDS_IDENTIFICATION: u64 = 0x20;
+/// 识别消息的类型码：TC 4 为 A 类（动力航空器）
+const IDENTIFICATION_TYPE_CODE: u64 = 4;
+
+/// 可分配的 ICAO 地址范围，排除全 0 与全 1
+const ICAO_ADDRESS_MIN: u32 = 0x000001;
+const ICAO_ADDRESS_MAX: u32 = 0xFFFFFE;
+
+/// 飞机状态消息的类型码
+const STATUS_TYPE_CODE: u64 = 28;
+/// 运行状态消息的类型码
+const OPERATIONAL_STATUS_TYPE_CODE: u64 = 31;
+
+/// 触发 TCAS 决断咨询的接近门限：水平距离 (NM) 与高度差 (ft)，不按高度层分级
+const TCAS_RA_RANGE_NM: f64 = 1.0;
+const TCAS_RA_ALTITUDE_FT: f64 = 600.0;
 
 /// 飞机数据结构
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Aircraft {
     pub id: String,           // ICAO 地址
     pub callsign: String,     // 航班号
@@ -10,8 +87,694 @@ pub struct Aircraft {
     pub lng: f64,             // 经度
     pub altitude: f64,        // 高度 (ft)
     pub speed: f64,           // 速度 (kts)
-    pub heading: f64,         // 航向 (度)
+    pub heading: f64,         // 航向 (度)，机头指向
+    #[serde(default)]
+    pub track: f64, // 航迹角 (度)，地速矢量方向，由航向、空速和风计算
+    #[serde(default)]
+    pub ground_speed: f64, // 地速 (kts)，由航向、空速和风计算
     pub nic: u8,              // GNSS 质量 (0-11)
+    #[serde(default)]
+    pub vertical_rate: f64, // 垂直速率 (ft/min，上升为正)
+    #[serde(default)]
+    pub capability: CapabilityProfile, // 机载设备能力
+    #[serde(default)]
+    pub source: AdsbSource, // 监视数据来源
+    #[serde(default)]
+    pub phase: Option<FlightPhase>, // 飞行阶段，None 时不启用阶段状态机
+    #[serde(default)]
+    pub target_altitude: Option<f64>, // 爬升/下降阶段的目标高度 (ft)
+    #[serde(default)]
+    pub on_ground: bool, // 是否在地面（发送地面位置消息）
+    #[serde(default = "default_version")]
+    pub version: u8, // ADS-B 版本号 (0: DO-260, 1: DO-260A, 2: DO-260B)
+    #[serde(default = "default_nacp")]
+    pub nacp: u8, // 位置精度类别 NACp (0-11)
+    #[serde(default = "default_nacv")]
+    pub nacv: u8, // 速度精度类别 NACv (0-4)，编码在速度消息中
+    #[serde(default = "default_sil")]
+    pub sil: u8, // 源完整性等级 SIL (0-3)
+    #[serde(default)]
+    pub spi: bool, // IDENT 按钮按下 (SPI)，运行状态消息中报告 IDENT 激活
+    #[serde(default)]
+    pub capability_class: CapabilityClass, // 运行状态消息中声明的机载能力
+    #[serde(default = "default_squawk", with = "squawk_code")]
+    pub squawk: u16, // 应答机编码 (Mode A)，以八进制存储四位数字，如 0o7700；JSON 中为 "7700"
+    #[serde(default)]
+    pub emergency: Option<EmergencyKind>, // 紧急状态，None 时由应答机编码推断
+    #[serde(default)]
+    pub category: AircraftCategory, // 发射机类别（尾流等级），编码在识别消息中
+}
+
+fn default_squawk() -> u16 {
+    DEFAULT_SQUAWK
+}
+
+/// 应答机编码在 JSON 中按四位八进制字符串 (如 "7700") 读写，与解码器和 SBS 输出一致
+mod squawk_code {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(squawk: &u16, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{:04o}", squawk))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u16, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        if code.len() != 4 || !code.bytes().all(|b| (b'0'..=b'7').contains(&b)) {
+            return Err(de::Error::custom(format!(
+                "squawk {:?} is not a 4-digit octal code",
+                code
+            )));
+        }
+        u16::from_str_radix(&code, 8).map_err(de::Error::custom)
+    }
+}
+
+fn default_version() -> u8 {
+    2
+}
+
+fn default_nacp() -> u8 {
+    9
+}
+
+fn default_nacv() -> u8 {
+    2
+}
+
+fn default_sil() -> u8 {
+    3
+}
+
+/// 与 NIC 相对应的典型 NACp：GNSS 定位的精度类别通常比完整性类别高 2 级
+fn nacp_for_nic(nic: u8) -> u8 {
+    (nic + 2).min(11)
+}
+
+/// 飞行阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlightPhase {
+    /// 地面滑行
+    Taxi,
+    /// 爬升至目标高度
+    Climb,
+    /// 巡航，保持高度
+    Cruise,
+    /// 下降至目标高度
+    Descent,
+    /// 进近
+    Approach,
+}
+
+/// 各飞行阶段的速度范围 (kts)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PhaseSpeedProfile {
+    pub speed_limit_altitude: f64, // 限速高度 (ft)，其下速度不超过 speed_limit
+    pub speed_limit: f64,          // 限速高度以下的速度上限
+    pub cruise_min: f64,
+    pub cruise_max: f64,
+    pub approach_min: f64,
+    pub approach_max: f64,
+    pub taxi_max: f64,
+    pub acceleration: f64, // 每秒最大速度变化 (kts/s)
+}
+
+impl Default for PhaseSpeedProfile {
+    fn default() -> Self {
+        PhaseSpeedProfile {
+            speed_limit_altitude: 10000.0,
+            speed_limit: 250.0,
+            cruise_min: 450.0,
+            cruise_max: 500.0,
+            approach_min: 140.0,
+            approach_max: 180.0,
+            taxi_max: 30.0,
+            acceleration: 2.0,
+        }
+    }
+}
+
+impl PhaseSpeedProfile {
+    /// 指定阶段和高度下的速度范围 (最小, 最大)
+    pub fn speed_range(&self, phase: FlightPhase, altitude: f64) -> (f64, f64) {
+        match phase {
+            FlightPhase::Taxi => (0.0, self.taxi_max),
+            FlightPhase::Approach => (self.approach_min, self.approach_max),
+            _ if altitude < self.speed_limit_altitude => (self.approach_max, self.speed_limit),
+            FlightPhase::Cruise => (self.cruise_min, self.cruise_max),
+            FlightPhase::Climb | FlightPhase::Descent => (self.speed_limit, self.cruise_max),
+        }
+    }
+}
+
+/// 监视数据来源，决定消息使用的下行格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdsbSource {
+    /// 机载应答机直接发送 (DF17)
+    #[default]
+    Adsb,
+    /// 地面站根据雷达数据广播的 TIS-B (DF18 CF=2)
+    TisB,
+    /// 地面站转发另一频段 ADS-B 的 ADS-R (DF18 CF=6)
+    Adsr,
+}
+
+impl AdsbSource {
+    /// 返回 (DF, CA/CF)
+    pub fn downlink_header(&self) -> (u8, u8) {
+        match self {
+            AdsbSource::Adsb => (17, 5),
+            AdsbSource::TisB => (18, 2),
+            AdsbSource::Adsr => (18, 6),
+        }
+    }
+
+    /// 目标是否装有 Mode S 应答机（TIS-B 与 ADS-R 目标由地面站代为广播，没有应答机）
+    pub fn has_transponder(&self) -> bool {
+        *self == AdsbSource::Adsb
+    }
+}
+
+/// ADS-B 消息类型，序列化为小写下划线字符串 (如 `"position"`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageType {
+    /// 识别与类别 (TC 1-4)
+    Identification,
+    /// 地面位置 (TC 5-8)
+    SurfacePosition,
+    /// 空中位置 (TC 9-18, 20-22)
+    Position,
+    /// 空中速度 (TC 19)
+    Velocity,
+    /// 飞机状态 (TC 28)
+    Status,
+    /// 目标状态 (TC 29)
+    TargetState,
+    /// 运行状态 (TC 31)
+    OperationalStatus,
+    /// 全呼应答短断续振荡 (DF11)
+    AllCall,
+    /// 其他或保留类型码
+    Other,
+}
+
+impl MessageType {
+    /// 由扩展电文类型码判断消息类型
+    pub fn from_type_code(type_code: u8) -> Self {
+        match type_code {
+            1..=4 => MessageType::Identification,
+            5..=8 => MessageType::SurfacePosition,
+            9..=18 | 20..=22 => MessageType::Position,
+            19 => MessageType::Velocity,
+            28 => MessageType::Status,
+            29 => MessageType::TargetState,
+            31 => MessageType::OperationalStatus,
+            _ => MessageType::Other,
+        }
+    }
+
+    /// 由下行格式与类型码判断消息类型：类型码只对 DF17/18 扩展电文有意义
+    pub fn from_downlink_format(df: u8, type_code: u8) -> Self {
+        match df {
+            11 => MessageType::AllCall,
+            17 | 18 => Self::from_type_code(type_code),
+            _ => MessageType::Other,
+        }
+    }
+}
+
+/// 机载 ADS-B 设备能力档位，决定该飞机实际发送哪些消息类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CapabilityProfile {
+    /// 仅发送位置消息（简易设备）
+    PositionOnly,
+    /// DO-260 (版本 0)：位置、速度、识别，不发送状态类消息
+    Do260,
+    /// DO-260A (版本 1)
+    Do260A,
+    /// DO-260B (版本 2)：发送全部消息类型
+    #[default]
+    Do260B,
+}
+
+impl CapabilityProfile {
+    /// 判断该档位是否发送指定类型的消息
+    pub fn emits(&self, message_type: MessageType) -> bool {
+        match self {
+            CapabilityProfile::PositionOnly => message_type == MessageType::Position,
+            CapabilityProfile::Do260 => matches!(
+                message_type,
+                MessageType::Position
+                    | MessageType::Velocity
+                    | MessageType::Identification
+                    | MessageType::AllCall
+            ),
+            CapabilityProfile::Do260A | CapabilityProfile::Do260B => true,
+        }
+    }
+}
+
+/// 运行状态消息 (TC 31) 中的能力类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CapabilityClass {
+    pub tcas: bool,   // 装有 TCAS 且处于工作状态
+    pub es_in: bool,  // 具备 1090ES 接收能力
+    pub uat_in: bool, // 具备 978 MHz UAT 接收能力
+}
+
+impl Default for CapabilityClass {
+    fn default() -> Self {
+        CapabilityClass {
+            tcas: true,
+            es_in: true,
+            uat_in: false,
+        }
+    }
+}
+
+impl CapabilityClass {
+    /// 16 位能力类别编码 (ME 9-24)，空中与地面子类型的位布局不同
+    pub fn code(&self, on_ground: bool) -> u16 {
+        if on_ground {
+            // ME 12: 1090ES IN，ME 16: UAT IN；地面子类型不报告 TCAS
+            (self.es_in as u16) << 12 | (self.uat_in as u16) << 8
+        } else {
+            // ME 11: TCAS 工作，ME 12: 1090ES IN，ME 19: UAT IN
+            (self.tcas as u16) << 13 | (self.es_in as u16) << 12 | (self.uat_in as u16) << 5
+        }
+    }
+}
+
+/// 飞机状态消息 (TC 28) 中的紧急状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmergencyKind {
+    /// 一般紧急情况 (7700)
+    General,
+    /// 无线电通信失效 (7600)
+    RadioFailure,
+    /// 非法干扰/劫机 (7500)
+    Hijack,
+}
+
+impl EmergencyKind {
+    const ALL: [EmergencyKind; 3] = [
+        EmergencyKind::General,
+        EmergencyKind::RadioFailure,
+        EmergencyKind::Hijack,
+    ];
+
+    /// 对应的应答机编码
+    pub fn squawk(&self) -> u16 {
+        match self {
+            EmergencyKind::General => 0o7700,
+            EmergencyKind::RadioFailure => 0o7600,
+            EmergencyKind::Hijack => 0o7500,
+        }
+    }
+
+    /// 紧急/优先状态子字段编码 (ME 9-11)
+    pub fn code(&self) -> u8 {
+        match self {
+            EmergencyKind::General => 1,
+            EmergencyKind::RadioFailure => 4,
+            EmergencyKind::Hijack => 5,
+        }
+    }
+
+    pub fn from_code(code: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.code() == code)
+    }
+
+    /// 由紧急应答机编码推断紧急状态
+    pub fn from_squawk(squawk: u16) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.squawk() == squawk)
+    }
+}
+
+/// 识别消息 (TC 4，A 类) 中的发射机类别，按最大起飞重量区分尾流等级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AircraftCategory {
+    /// A1 轻型 (< 15 500 lb)
+    Light,
+    /// A2 小型 (15 500-75 000 lb)
+    Small,
+    /// A3 大型 (75 000-300 000 lb)
+    #[default]
+    Large,
+    /// A4 高涡流大型（如 B757）
+    HighVortexLarge,
+    /// A5 重型 (> 300 000 lb)
+    Heavy,
+    /// A6 高性能 (> 5 g 且 > 400 kts)
+    HighPerformance,
+    /// A7 旋翼机
+    Rotorcraft,
+}
+
+impl AircraftCategory {
+    const ALL: [AircraftCategory; 7] = [
+        AircraftCategory::Light,
+        AircraftCategory::Small,
+        AircraftCategory::Large,
+        AircraftCategory::HighVortexLarge,
+        AircraftCategory::Heavy,
+        AircraftCategory::HighPerformance,
+        AircraftCategory::Rotorcraft,
+    ];
+
+    /// 识别消息的类别子字段编码 (ME 6-8)
+    pub fn code(&self) -> u8 {
+        match self {
+            AircraftCategory::Light => 1,
+            AircraftCategory::Small => 2,
+            AircraftCategory::Large => 3,
+            AircraftCategory::HighVortexLarge => 4,
+            AircraftCategory::Heavy => 5,
+            AircraftCategory::HighPerformance => 6,
+            AircraftCategory::Rotorcraft => 7,
+        }
+    }
+
+    pub fn from_code(code: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.code() == code)
+    }
+}
+
+impl Aircraft {
+    /// 当前的紧急状态：显式设置优先，否则由应答机编码 7500/7600/7700 推断
+    pub fn emergency_state(&self) -> Option<EmergencyKind> {
+        self.emergency
+            .or_else(|| EmergencyKind::from_squawk(self.squawk))
+    }
+}
+
+/// 生成机队时的初始高度 (ft) 与速度 (kts) 范围
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MockRanges {
+    pub min_altitude: f64,
+    pub max_altitude: f64,
+    pub min_speed: f64,
+    pub max_speed: f64,
+}
+
+impl Default for MockRanges {
+    fn default() -> Self {
+        MockRanges {
+            min_altitude: 5000.0,
+            max_altitude: 15000.0,
+            min_speed: 400.0,
+            max_speed: 650.0,
+        }
+    }
+}
+
+/// 速度-高度耦合参数：目标速度随高度线性增加（高空快、低空慢）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpeedAltitudeCoupling {
+    pub base_speed: f64,       // 海平面基准速度 (kts)
+    pub knots_per_1000ft: f64, // 每升高 1000 ft 增加的速度 (kts)
+    pub spread: f64,           // 生成时的随机浮动范围 (±kts)
+    pub max_speed: f64,        // 速度上限 (kts)
+}
+
+impl Default for SpeedAltitudeCoupling {
+    fn default() -> Self {
+        SpeedAltitudeCoupling {
+            base_speed: 200.0,
+            knots_per_1000ft: 8.0,
+            spread: 20.0,
+            max_speed: 520.0,
+        }
+    }
+}
+
+impl SpeedAltitudeCoupling {
+    /// 指定高度下的目标速度 (kts)
+    pub fn target_speed(&self, altitude: f64) -> f64 {
+        (self.base_speed + altitude.max(0.0) / 1000.0 * self.knots_per_1000ft)
+            .min(self.max_speed)
+    }
+}
+
+/// 各类型消息的发送周期（每 N 个 tick 发送一次）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MessageCadence {
+    pub position_every: u32,
+    pub velocity_every: u32,
+    pub identification_every: u32,
+    pub all_call_every: u32,
+    pub operational_status_every: u32,
+    pub status_every: u32,
+}
+
+impl Default for MessageCadence {
+    fn default() -> Self {
+        MessageCadence {
+            position_every: 1,
+            velocity_every: 1,
+            identification_every: 5,     // 实际设备约每 5 s 广播一次识别消息
+            all_call_every: 1,           // 应答机约每秒发送一次 DF11 捕获断续振荡
+            operational_status_every: 3, // 空中约每 2.5 s 广播一次运行状态
+            status_every: 5,             // 无紧急情况时约每 5 s 广播一次飞机状态
+        }
+    }
+}
+
+impl MessageCadence {
+    /// 指定类型消息的发送周期 (tick)
+    pub fn every(&self, message_type: MessageType) -> u32 {
+        let every = match message_type {
+            MessageType::Position => self.position_every,
+            MessageType::Velocity => self.velocity_every,
+            MessageType::Identification => self.identification_every,
+            MessageType::AllCall => self.all_call_every,
+            MessageType::OperationalStatus => self.operational_status_every,
+            MessageType::Status => self.status_every,
+            _ => 1,
+        };
+        every.max(1)
+    }
+}
+
+/// 单个消息类型的发送计划
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleEntry {
+    pub message_type: MessageType,
+    pub interval_ms: u64, // 发送周期 (ms)
+    pub next_ms: u64,     // 距下次发送的时间 (ms)
+}
+
+/// 信道损伤参数：模拟接收端丢帧与比特错误
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChannelImpairment {
+    pub dropout_rate: f64,   // 整帧丢失概率 (0-1)
+    pub bit_error_rate: f64, // 每比特翻转概率 (0-1)
+}
+
+impl ChannelImpairment {
+    /// 单帧可用的概率：未丢失，且比特错误不超过 1 位（可由 CRC 纠正）
+    pub fn delivery_probability(&self) -> f64 {
+        let p = self.bit_error_rate.clamp(0.0, 1.0);
+        let bits = 112.0;
+        let correctable = (1.0 - p).powf(bits) + bits * p * (1.0 - p).powf(bits - 1.0);
+        (1.0 - self.dropout_rate.clamp(0.0, 1.0)) * correctable
+    }
+}
+
+/// 接收概率降至 50% 的缺省距离 (nm)
+pub const DEFAULT_RECEPTION_HALF_RANGE_NM: f64 = 150.0;
+/// 斜距 1 nm 处的信号强度 (dBFS)
+const RSSI_AT_1NM_DBFS: f64 = -3.0;
+/// 信号强度下限 (dBFS)，接近接收机灵敏度
+const MIN_RSSI_DBFS: f64 = -50.0;
+
+fn default_reception_half_range() -> f64 {
+    DEFAULT_RECEPTION_HALF_RANGE_NM
+}
+
+/// 距离衰减接收模型：单个接收机按飞机的距离与高度随机漏收消息，并估算信号强度
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ReceptionModel {
+    pub receiver_position: (f64, f64), // 接收机 (纬度, 经度)
+    #[serde(default = "default_reception_half_range")]
+    pub half_range_nm: f64, // 接收概率降至 50% 的距离 (nm)
+}
+
+impl ReceptionModel {
+    /// 单条消息被接收的概率：无线电视距以外为 0，视距内按 `1 / (1 + (d / d½)⁴)` 随距离衰减
+    pub fn reception_probability(&self, distance_nm: f64, altitude_ft: f64) -> f64 {
+        if distance_nm > geo::radar_horizon_nm(altitude_ft, 0.0) {
+            return 0.0;
+        }
+        1.0 / (1.0 + (distance_nm / self.half_range_nm).powi(4))
+    }
+
+    /// 按斜距的自由空间损耗估算信号强度 (dBFS)，距离每增加一倍衰减 6 dB
+    pub fn rssi_dbfs(&self, distance_nm: f64, altitude_ft: f64) -> f64 {
+        let slant_nm = distance_nm
+            .hypot(altitude_ft.max(0.0) / geo::FEET_PER_NM)
+            .max(1.0);
+        (RSSI_AT_1NM_DBFS - 20.0 * slant_nm.log10()).max(MIN_RSSI_DBFS)
+    }
+}
+
+/// 风层：自 `floor_ft` 起至上一层底高之间的风
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindLayer {
+    pub floor_ft: f64,      // 该层底高 (ft)
+    pub direction_deg: f64, // 风向 (度，风的来向)
+    pub speed_kts: f64,     // 风速 (kts)
+}
+
+/// 在按底高排序的风层表中查找指定高度所在的风层
+fn wind_at(layers: &[WindLayer], altitude: f64) -> Option<&WindLayer> {
+    layers.iter().rev().find(|layer| layer.floor_ft <= altitude)
+}
+
+/// 地速矢量 (地速 kts, 航迹角 度)：沿航向的空速矢量叠加所在高度层的风
+fn ground_vector(aircraft: &Aircraft, wind_layers: &[WindLayer]) -> (f64, f64) {
+    let heading = aircraft.heading.to_radians();
+    let mut east = aircraft.speed * heading.sin();
+    let mut north = aircraft.speed * heading.cos();
+    if let Some(wind) = wind_on(aircraft, wind_layers) {
+        // 风向为来向，风矢量指向其反方向
+        let toward = (wind.direction_deg + 180.0).to_radians();
+        east += wind.speed_kts * toward.sin();
+        north += wind.speed_kts * toward.cos();
+    }
+    if east == 0.0 && north == 0.0 {
+        return (0.0, aircraft.heading);
+    }
+    (
+        east.hypot(north),
+        (east.atan2(north).to_degrees() + 360.0) % 360.0,
+    )
+}
+
+/// 飞机所受的风：地面飞机不受风影响
+fn wind_on<'a>(aircraft: &Aircraft, layers: &'a [WindLayer]) -> Option<&'a WindLayer> {
+    wind_at(layers, aircraft.altitude).filter(|_| !aircraft.on_ground)
+}
+
+/// 按当前速度、航向和所在高度层的风，计算 `seconds` 秒后的 (纬度, 经度)
+fn advance_position(aircraft: &Aircraft, wind_layers: &[WindLayer], seconds: f64) -> (f64, f64) {
+    // 速度单位为 kts，沿当前航向在球面上前进对应的海里数（经度变化随纬度按 cos 缩放）
+    let distance = aircraft.speed / 3600.0 * seconds;
+    let (mut lat, mut lng) =
+        geo::destination_point(aircraft.lat, aircraft.lng, aircraft.heading, distance);
+
+    // 叠加所在高度层的风，使地速矢量偏离航向（风向为来向，飘移方向相反）
+    if let Some(wind) = wind_on(aircraft, wind_layers) {
+        let drift = wind.speed_kts / 3600.0 * seconds;
+        (lat, lng) = geo::destination_point(lat, lng, wind.direction_deg + 180.0, drift);
+    }
+    (lat, lng)
+}
+
+/// 机场参考点信标：固定位置的地面发射源，定期发送地面位置消息供场面监视客户端参考
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AirportBeacon {
+    pub icao: String, // 信标 ICAO 地址
+    pub lat: f64,     // 机场参考点纬度
+    pub lng: f64,     // 机场参考点经度
+}
+
+/// 跑道入口，着陆引导的目标
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Runway {
+    pub lat: f64,     // 入口纬度
+    pub lng: f64,     // 入口经度
+    pub heading: f64, // 跑道方向 (度)
+}
+
+/// 航路点
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Waypoint {
+    pub lat: f64,
+    pub lng: f64,
+    #[serde(default)]
+    pub altitude: Option<f64>, // 目标高度 (ft)，None 表示保持当前高度
+}
+
+/// 航路飞完后的行为
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteCompletion {
+    /// 在最后一个航路点附近盘旋等待
+    #[default]
+    Hold,
+    /// 回到第一个航路点循环飞行
+    Loop,
+    /// 在最后一个航路点下降至地面，落地后移出机队
+    Land,
+}
+
+/// 飞行计划：依次飞向各航路点
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlightPlan {
+    pub waypoints: Vec<Waypoint>,
+    #[serde(default)]
+    pub on_complete: RouteCompletion,
+}
+
+/// 正在执行的飞行计划
+#[derive(Debug, Clone)]
+struct ActiveRoute {
+    plan: FlightPlan,
+    next: usize,   // 下一个航路点索引
+    flown_nm: f64, // 已飞行距离 (nm)
+    arrived: bool, // 是否已到达最后一个航路点
+}
+
+/// 航班完成事件：飞机到达目的地并落地
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlightCompletion {
+    pub aircraft_id: String,
+    pub callsign: String,
+    pub distance_nm: f64, // 全程飞行距离 (nm)
+}
+
+/// 新出现飞机的 GNSS 捕获状态
+#[derive(Debug, Clone, Copy)]
+struct Acquisition {
+    remaining: u32, // 距完成捕获的 tick 数
+    nic: u8,        // 捕获完成后恢复的 NIC
+}
+
+/// 等待放行的飞机（离场飞机或启动时分批出现的飞机）
+#[derive(Debug, Clone)]
+struct PendingDeparture {
+    release_tick: u64, // 放行时刻 (tick)
+    aircraft: Aircraft,
+    plan: Option<FlightPlan>, // 放行后执行的飞行计划，None 时保持原航向飞行
+}
+
+/// 地址欺骗发射源：冒用目标飞机的 ICAO 地址，在偏移位置上发送消息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Spoofer {
+    pub target_id: String, // 被冒用的 ICAO 地址
+    pub lat_offset: f64,   // 相对真实位置的纬度偏移 (度)
+    pub lng_offset: f64,   // 相对真实位置的经度偏移 (度)
+}
+
+/// 航迹历史点
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TrackPoint {
+    pub time: f64,     // 模拟时间 (s)
+    pub lat: f64,      // 纬度
+    pub lng: f64,      // 经度
+    pub altitude: f64, // 高度 (ft)
 }
 
 /// ADS-B 消息事件
@@ -19,33 +782,261 @@ pub struct Aircraft {
 pub struct AdsbEvent {
     pub hex_message: String,
     pub aircraft_id: String,
-    pub message_type: String, // "position" or "velocity"
+    pub message_type: MessageType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rssi: Option<f64>, // 模拟接收信号强度 (dBFS)，未启用接收模型时为 None
+    #[serde(skip)]
+    pub spoofed: bool, // 由地址欺骗源发出（ICAO 与被冒用的飞机相同），仅用于模拟接收
+}
+
+/// 场景快照：导出的机队，附带模拟器中心坐标作为元数据
+///
+/// 只保存飞机自身状态；航路、进近、GNSS 捕获等模拟器侧状态不在快照中，
+/// 重新加载后这些飞机按自身航向与速度飞行。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioFile {
+    pub center_lat: f64,
+    pub center_lng: f64,
+    pub aircrafts: Vec<Aircraft>,
 }
 
+/// 每个 tick 调用的扩展回调，参数为可修改的机队与当前 tick 数
+///
+/// 在运动更新之后、航迹记录和消息生成之前调用，可用于自定义机动或注入飞机。
+/// 回调返回后，非法或 ICAO 重复的飞机被移除，被移除飞机的附属状态一并清理。
+/// 模拟器运行在后台线程中，回调必须为 `Send`。
+pub type TickHook = Box<dyn FnMut(&mut Vec<Aircraft>, u64) + Send>;
+
 /// ADS-B 信号模拟器
 pub struct AdsbSimulator {
     aircrafts: Vec<Aircraft>,
     center_lat: f64,
     center_lng: f64,
+    seed: u64,
+    rng: SimRng,
+    sim_time: f64,      // 已模拟时间 (s)
+    start_time_ms: u64, // 模拟开始的墙钟时间 (Unix ms)
+    history: BTreeMap<String, VecDeque<TrackPoint>>, // 航迹历史，按 ICAO 索引
+    history_limit: usize, // 每架飞机保留的最大航迹点数
+    history_budget: usize, // 全体飞机保留的航迹点总数上限
+    speed_coupling: Option<SpeedAltitudeCoupling>, // 速度-高度耦合，None 时速度与高度独立
+    spoofers: Vec<Spoofer>, // 地址欺骗发射源
+    tick: u64,              // 已执行的模拟步数
+    tick_interval_ms: u64,  // 每步对应的墙钟时间 (ms)
+    cadence: MessageCadence, // 各类型消息的发送周期
+    routes: BTreeMap<String, ActiveRoute>, // 飞行计划，按 ICAO 索引
+    phase_speeds: PhaseSpeedProfile, // 各飞行阶段的速度范围
+    acquisition_ticks: u32, // 新加入飞机的 GNSS 捕获时长 (tick)，0 表示立即可用
+    acquiring: BTreeMap<String, Acquisition>, // 捕获中的飞机，按 ICAO 索引
+    impairment: ChannelImpairment, // 信道损伤
+    reception: Option<ReceptionModel>, // 距离衰减接收模型，None 时不按距离丢弃消息
+    beacon: Option<AirportBeacon>, // 机场参考点信标
+    completed: Vec<FlightCompletion>, // 尚未取走的航班完成事件
+    wind_layers: Vec<WindLayer>, // 分层风，按底高升序
+    landings: BTreeMap<String, Runway>, // 正在着陆的飞机及目标跑道，按 ICAO 索引
+    departures: Vec<PendingDeparture>, // 等待放行的飞机
+    semicircular_levels: bool, // 生成机队时是否按半圆规则分配高度层
+    mock_ranges: MockRanges, // 生成机队时的高度与速度范围
+    max_radius_deg: Option<f64>, // 飞出中心该半径 (度) 的飞机由边缘新飞入的飞机替换
+    cpr_odd: BTreeMap<(String, bool), bool>, // 下一条位置是否为奇帧，按 (ICAO, 欺骗源) 索引
+    tick_hooks: Vec<TickHook>, // 按注册顺序调用的扩展回调
 }
 
 impl AdsbSimulator {
     pub fn new(center_lat: f64, center_lng: f64) -> Self {
+        Self::with_seed(center_lat, center_lng, time_seed())
+    }
+
+    /// 使用指定种子创建模拟器，相同种子产生完全相同的模拟过程
+    pub fn with_seed(center_lat: f64, center_lng: f64, seed: u64) -> Self {
         AdsbSimulator {
             aircrafts: Vec::new(),
             center_lat,
             center_lng,
+            seed,
+            rng: SimRng::new(seed),
+            sim_time: 0.0,
+            start_time_ms: unix_time_ms(),
+            history: BTreeMap::new(),
+            history_limit: DEFAULT_HISTORY_POINTS,
+            history_budget: DEFAULT_HISTORY_BUDGET_POINTS,
+            speed_coupling: None,
+            spoofers: Vec::new(),
+            tick: 0,
+            tick_interval_ms: 1000,
+            cadence: MessageCadence::default(),
+            routes: BTreeMap::new(),
+            phase_speeds: PhaseSpeedProfile::default(),
+            acquisition_ticks: DEFAULT_ACQUISITION_TICKS,
+            acquiring: BTreeMap::new(),
+            impairment: ChannelImpairment::default(),
+            reception: None,
+            beacon: None,
+            completed: Vec::new(),
+            wind_layers: Vec::new(),
+            landings: BTreeMap::new(),
+            departures: Vec::new(),
+            semicircular_levels: false,
+            mock_ranges: MockRanges::default(),
+            max_radius_deg: None,
+            cpr_odd: BTreeMap::new(),
+            tick_hooks: Vec::new(),
+        }
+    }
+
+    /// 获取当前随机数种子
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// 重新设置种子并重置随机数生成器
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = SimRng::new(seed);
+    }
+
+    /// 设置速度-高度耦合参数
+    pub fn set_speed_coupling(&mut self, coupling: Option<SpeedAltitudeCoupling>) {
+        self.speed_coupling = coupling;
+    }
+
+    /// 设置全体飞机航迹点总数上限，超出时立即淘汰最旧的点
+    pub fn set_history_budget(&mut self, points: usize) {
+        self.history_budget = points;
+        self.enforce_history_budget();
+    }
+
+    /// 设置每步对应的墙钟时间 (ms)，用于换算发送计划
+    pub fn set_tick_interval_ms(&mut self, interval_ms: u64) {
+        self.tick_interval_ms = interval_ms.max(1);
+    }
+
+    /// 设置分层风表，空表表示无风
+    pub fn set_wind_layers(&mut self, mut layers: Vec<WindLayer>) -> Result<(), String> {
+        if let Some(layer) = layers.iter().find(|l| !(0.0..=200.0).contains(&l.speed_kts)) {
+            return Err(format!("Wind speed {} kts out of range", layer.speed_kts));
+        }
+        layers.sort_by(|a, b| a.floor_ft.total_cmp(&b.floor_ft));
+        self.wind_layers = layers;
+        Ok(())
+    }
+
+    /// 设置生成机队时的高度与速度范围，最小值须小于最大值
+    pub fn set_mock_ranges(&mut self, ranges: MockRanges) -> Result<(), String> {
+        if ranges.min_altitude >= ranges.max_altitude {
+            return Err(format!(
+                "Altitude range {}-{} ft is empty",
+                ranges.min_altitude, ranges.max_altitude
+            ));
+        }
+        if ranges.min_speed >= ranges.max_speed || ranges.min_speed < 0.0 {
+            return Err(format!(
+                "Speed range {}-{} kts is invalid",
+                ranges.min_speed, ranges.max_speed
+            ));
+        }
+        self.mock_ranges = ranges;
+        Ok(())
+    }
+
+    /// 设置全高度统一的风，替换分层风表；`direction_deg` 为风的来向
+    pub fn set_wind(&mut self, speed_kts: f64, direction_deg: f64) -> Result<(), String> {
+        self.set_wind_layers(vec![WindLayer {
+            floor_ft: -1000.0,
+            direction_deg,
+            speed_kts,
+        }])
+    }
+
+    /// 设置各飞行阶段的速度范围
+    pub fn set_phase_speeds(&mut self, profile: PhaseSpeedProfile) {
+        self.phase_speeds = profile;
+    }
+
+    /// 设置活动半径 (度)，None 时飞机可任意飞远
+    pub fn set_max_radius_deg(&mut self, radius: Option<f64>) -> Result<(), String> {
+        if let Some(radius) = radius.filter(|&r| r <= 0.0 || r.is_nan()) {
+            return Err(format!("Max radius {} deg must be positive", radius));
+        }
+        self.max_radius_deg = radius;
+        Ok(())
+    }
+
+    /// 设置生成机队时是否按半圆规则分配巡航高度层
+    pub fn set_semicircular_levels(&mut self, enabled: bool) {
+        self.semicircular_levels = enabled;
+    }
+
+    /// 设置模拟器生成的新飞机的 GNSS 捕获时长 (tick)
+    pub fn set_acquisition_ticks(&mut self, ticks: u32) {
+        self.acquisition_ticks = ticks;
+    }
+
+    /// 设置信道损伤参数
+    pub fn set_impairment(&mut self, impairment: ChannelImpairment) {
+        self.impairment = impairment;
+    }
+
+    /// 设置距离衰减接收模型，None 时所有消息都被接收
+    pub fn set_reception(&mut self, reception: Option<ReceptionModel>) -> Result<(), String> {
+        if let Some(model) = &reception {
+            let (lat, lng) = model.receiver_position;
+            if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lng) {
+                return Err(format!("Receiver position ({}, {}) out of range", lat, lng));
+            }
+            if !(model.half_range_nm > 0.0 && model.half_range_nm.is_finite()) {
+                return Err(format!(
+                    "Invalid reception half range: {} nm",
+                    model.half_range_nm
+                ));
+            }
+        }
+        self.reception = reception;
+        Ok(())
+    }
+
+    /// 设置机场参考点信标，None 表示不启用
+    pub fn set_airport_beacon(&mut self, beacon: Option<AirportBeacon>) -> Result<(), String> {
+        if let Some(beacon) = &beacon {
+            let id = &beacon.icao;
+            if id.len() != 6 || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(format!("Invalid beacon ICAO address '{}': expected 6 hex digits", id));
+            }
+            if !(-90.0..=90.0).contains(&beacon.lat) || !(-180.0..=180.0).contains(&beacon.lng) {
+                return Err(format!("Beacon {}: position out of range", id));
+            }
         }
+        self.beacon = beacon;
+        Ok(())
+    }
+
+    /// 设置各类型消息的发送周期
+    pub fn set_cadence(&mut self, cadence: MessageCadence) {
+        self.cadence = cadence;
     }
 
     /// 生成模拟飞机
-    pub fn generate_mock_aircrafts(&mut self, count: usize) {
+    pub fn generate_mock_aircrafts(&mut self, count: usize) -> Result<(), String> {
+        let available = ICAO_ADDRESS_MAX - ICAO_ADDRESS_MIN + 1;
+        if count > available as usize {
+            return Err(format!(
+                "Cannot generate {} aircraft: only {} ICAO addresses available",
+                count, available
+            ));
+        }
         self.aircrafts.clear();
+        self.history.clear();
+        self.routes.clear();
+        self.acquiring.clear();
+        self.landings.clear();
+        self.departures.clear();
+        self.cpr_odd.clear();
         
         // 航空公司前缀
         let airlines = ["CZ", "CA", "MU", "BZ", "FM", "ZH", "HU", "SC", "3U", "GS"];
         
         // 使用伪随机种子生成飞机位置（基于索引的确定性随机）
+        let mut used = self.icao_in_use();
         for i in 0..count {
             // 使用黄金分割角度确保均匀分布，避免螺旋
             let golden_angle = PI * (3.0 - (5.0_f64).sqrt()); // ≈ 137.5°
@@ -63,163 +1054,2004 @@ impl AdsbSimulator {
             let flight_num = 1000 + (i * 111) % 9000;
             let callsign = format!("{}{}", airline, flight_num);
             
-            // 随机 ICAO 地址
-            let icao = format!("{:06X}", 0x780000 + i * 0x1111);
+            let icao = self.allocate_icao(&mut used);
             
             // 航向基于位置指向或离开中心，更真实
             let seed2 = (i * 6997 + 99991) % 360;
             let heading = seed2 as f64; // 伪随机航向
             
+            // 伪随机高度与速度，按比例落在配置的范围内
+            let ranges = self.mock_ranges;
+            let altitude_span = ranges.max_altitude - ranges.min_altitude;
+            let mut altitude =
+                ranges.min_altitude + ((i * 2749) % 10000) as f64 / 10000.0 * altitude_span;
+            if self.semicircular_levels {
+                altitude = semicircular_level(altitude, heading);
+            }
+            // 约四分之一爬升、四分之一下降到相差 2000-5000 ft 的整千英尺目标高度
+            // (不超出高度范围)，其余巡航；按高度层飞行时全部巡航
+            let delta = 2000.0 + ((i * 1237) % 3000) as f64;
+            let level = |altitude: f64| (altitude / 1000.0).round() * 1000.0;
+            let (phase, target_altitude) = match i % 4 {
+                0 if !self.semicircular_levels => (
+                    FlightPhase::Climb,
+                    level(altitude + delta).min(ranges.max_altitude),
+                ),
+                1 if !self.semicircular_levels => (
+                    FlightPhase::Descent,
+                    level(altitude - delta).max(ranges.min_altitude),
+                ),
+                _ => (FlightPhase::Cruise, altitude),
+            };
+            // 范围过窄、目标高度与当前高度相同时直接巡航
+            let (phase, target_altitude) = if target_altitude == altitude {
+                (FlightPhase::Cruise, None)
+            } else {
+                (phase, Some(target_altitude))
+            };
+            let vertical_rate = match phase {
+                FlightPhase::Climb => PHASE_VERTICAL_RATE_FPS * 60.0,
+                FlightPhase::Descent => -PHASE_VERTICAL_RATE_FPS * 60.0,
+                _ => 0.0,
+            };
+            let speed = match self.speed_coupling {
+                // 启用耦合时速度由高度决定，叠加小幅随机浮动
+                Some(coupling) => {
+                    let jitter = (self.rng.next_f64() * 2.0 - 1.0) * coupling.spread;
+                    (coupling.target_speed(altitude) + jitter).max(0.0)
+                }
+                None => {
+                    let speed_span = ranges.max_speed - ranges.min_speed;
+                    ranges.min_speed + ((i * 3571) % 250) as f64 / 250.0 * speed_span
+                }
+            };
+            
+            // 航空公司航班以大型、重型客机为主，少量高涡流大型与小型支线飞机
+            let category = match (i * 4513) % 100 {
+                0..=64 => AircraftCategory::Large,
+                65..=89 => AircraftCategory::Heavy,
+                90..=96 => AircraftCategory::HighVortexLarge,
+                _ => AircraftCategory::Small,
+            };
+
+            // 完整性与精度类别相关联：NIC 越高，NACp、NACv 也越高
+            let nic = (5 + i % 7) as u8; // NIC 5-11
             let aircraft = Aircraft {
                 id: icao,
                 callsign,
                 lat,
                 lng,
-                altitude: 5000.0 + ((i * 2749) % 10000) as f64, // 伪随机高度
-                speed: 400.0 + ((i * 3571) % 250) as f64,       // 伪随机速度
+                altitude,
+                speed,
                 heading,
-                nic: (5 + i % 7) as u8, // NIC 5-11
+                nic,
+                vertical_rate,
+                capability: CapabilityProfile::default(),
+                source: AdsbSource::default(),
+                phase: Some(phase),
+                target_altitude,
+                on_ground: false,
+                version: default_version(),
+                nacp: nacp_for_nic(nic),
+                nacv: if nic >= 8 { 2 } else { 1 },
+                sil: default_sil(),
+                spi: false,
+                capability_class: CapabilityClass::default(),
+                category,
+                squawk: DEFAULT_SQUAWK,
+                track: 0.0,
+                ground_speed: 0.0,
+                emergency: None,
             };
             
             self.aircrafts.push(aircraft);
         }
+        for aircraft in &mut self.aircrafts {
+            (aircraft.ground_speed, aircraft.track) = ground_vector(aircraft, &self.wind_layers);
+        }
+
+        self.record_history();
+        Ok(())
     }
 
     /// 更新飞机位置
     pub fn update_positions(&mut self) {
+        let rng = &mut self.rng;
+        let routes = &mut self.routes;
+        let acquiring = &mut self.acquiring;
+        let phase_speeds = self.phase_speeds;
+        let wind_layers = &self.wind_layers;
+        let landings = &self.landings;
         for aircraft in &mut self.aircrafts {
-            // 根据速度和航向更新位置
-            // 速度单位：km/h，转换为度/秒（简化计算）
-            // 1度纬度 ≈ 111km，所以 speed(km/h) / 3600 / 111 ≈ degree/s
-            let speed_deg_per_sec = aircraft.speed / 3600.0 / 111.0;
-            
-            // 航向角转数学角度：航向0度=正北=数学90度
-            // 数学角度 = 90 - 航向角
-            let math_rad = (90.0 - aircraft.heading) * PI / 180.0;
-            
-            // 使用正确的三角函数：
-            // lat (南北) 使用 sin，lng (东西) 使用 cos
-            aircraft.lat += speed_deg_per_sec * math_rad.sin();
-            aircraft.lng += speed_deg_per_sec * math_rad.cos();
+            let prev_altitude = aircraft.altitude;
+            // 着陆引导或飞行计划接管航向和高度
+            let (route_heading, route_altitude) = if let Some(runway) = landings.get(&aircraft.id) {
+                fly_approach(aircraft, runway);
+                (true, true)
+            } else {
+                match routes.get_mut(&aircraft.id) {
+                    Some(route) => steer_along_route(aircraft, route),
+                    None => (false, false),
+                }
+            };
+
+            // 根据速度、航向和风更新位置
+            let (prev_lat, prev_lng) = (aircraft.lat, aircraft.lng);
+            (aircraft.lat, aircraft.lng) = advance_position(aircraft, wind_layers, 1.0);
+            if let Some(route) = routes.get_mut(&aircraft.id) {
+                route.flown_nm += geo::distance_nm(prev_lat, prev_lng, aircraft.lat, aircraft.lng);
+            }
             
             // 随机微调 NIC (GNSS 质量波动)
-            if rand_simple() > 0.9 {
-                let nic_change = rand_range(-1, 1) as i8;
+            if rng.next_f64() > 0.9 {
+                let nic_change = rng.range_i32(-1, 1) as i8;
                 let new_nic = (aircraft.nic as i8 + nic_change).clamp(0, 11);
                 aircraft.nic = new_nic as u8;
             }
+
+            // GNSS 捕获期间 NIC 为 0，捕获完成后恢复
+            if let Some(acquisition) = acquiring.get_mut(&aircraft.id) {
+                acquisition.remaining = acquisition.remaining.saturating_sub(1);
+                if acquisition.remaining == 0 {
+                    aircraft.nic = acquisition.nic;
+                    acquiring.remove(&aircraft.id);
+                } else {
+                    aircraft.nic = 0;
+                }
+            }
+            
+            // 启用阶段状态机时由阶段驱动高度；否则按垂直速率爬升/下降，
+            // 到达高度范围边界后改平；平飞时只有小幅波动
+            let phase_altitude = !route_altitude && advance_phase(aircraft);
+            if aircraft.on_ground {
+                // 地面飞机停留在机场标高
+                aircraft.altitude = FIELD_ELEVATION_FT;
+                aircraft.vertical_rate = 0.0;
+            } else if route_altitude || phase_altitude {
+                aircraft.vertical_rate = (aircraft.altitude - prev_altitude) * 60.0;
+            } else if aircraft.vertical_rate != 0.0 {
+                aircraft.altitude += aircraft.vertical_rate / 60.0;
+                if !(3000.0..=12000.0).contains(&aircraft.altitude) {
+                    aircraft.altitude = aircraft.altitude.clamp(3000.0, 12000.0);
+                    aircraft.vertical_rate = 0.0;
+                }
+            } else {
+                aircraft.altitude += rng.range_i32(-20, 20) as f64;
+                aircraft.altitude = aircraft.altitude.clamp(3000.0, 12000.0);
+            }
             
-            // 保持高度稳定，只有小幅波动
-            aircraft.altitude += rand_range(-20, 20) as f64;
-            aircraft.altitude = aircraft.altitude.clamp(3000.0, 12000.0);
+            // 速度逐渐向当前阶段（或高度）对应的目标速度靠拢：着陆或按飞行计划飞行的
+            // 航班总是按阶段速度；随机生成的飞机在启用速度-高度耦合时按高度
+            let managed = landings.contains_key(&aircraft.id) || routes.contains_key(&aircraft.id);
+            let phase = aircraft
+                .phase
+                .filter(|_| managed || self.speed_coupling.is_none());
+            if let Some(phase) = phase {
+                let (min, max) = phase_speeds.speed_range(phase, aircraft.altitude);
+                let target = aircraft.speed.clamp(min, max);
+                aircraft.speed += (target - aircraft.speed)
+                    .clamp(-phase_speeds.acceleration, phase_speeds.acceleration);
+            } else if let Some(coupling) = self.speed_coupling {
+                let target = coupling.target_speed(aircraft.altitude);
+                aircraft.speed += (target - aircraft.speed) * 0.05;
+            }
+            if aircraft.on_ground {
+                aircraft.speed = aircraft.speed.min(phase_speeds.taxi_max);
+            }
             
             // 航向小幅微调（模拟轻微转弯）
-            aircraft.heading += rand_range(-1, 1) as f64;
-            aircraft.heading = (aircraft.heading + 360.0) % 360.0;
+            if !route_heading {
+                aircraft.heading += rng.range_i32(-1, 1) as f64;
+                aircraft.heading = (aircraft.heading + 360.0) % 360.0;
+            }
+            (aircraft.ground_speed, aircraft.track) = ground_vector(aircraft, wind_layers);
+        }
+
+        self.complete_landed_flights();
+        self.replace_departed_aircraft();
+        self.sim_time += 1.0;
+        self.tick += 1;
+        self.release_departures();
+        for hook in &mut self.tick_hooks {
+            hook(&mut self.aircrafts, self.tick);
         }
+        if !self.tick_hooks.is_empty() {
+            self.sanitize_hooked_fleet();
+        }
+        self.record_history();
     }
 
-    /// 获取所有飞机数据
-    pub fn get_aircrafts(&self) -> &Vec<Aircraft> {
-        &self.aircrafts
+    /// 回调可以任意修改机队：移除非法或 ICAO 重复的飞机，并清理已不在机队中的
+    /// 飞机的航路、进近、捕获状态与航迹
+    fn sanitize_hooked_fleet(&mut self) {
+        let mut seen = HashSet::new();
+        self.retain_aircrafts(|aircraft| {
+            if let Err(e) = validate_aircraft(aircraft) {
+                eprintln!("[Rust] Dropping aircraft from tick hook: {}", e);
+                return false;
+            }
+            let id = &aircraft.id;
+            if !seen.insert(id.to_ascii_uppercase()) {
+                eprintln!("[Rust] Dropping duplicate aircraft {} from tick hook", id);
+                return false;
+            }
+            true
+        });
+        let aircrafts = &self.aircrafts;
+        self.history
+            .retain(|id, _| aircrafts.iter().any(|a| &a.id == id));
     }
 
-    /// 生成位置消息 (DF17 Type 11)
-    pub fn generate_position_message(aircraft: &Aircraft) -> String {
-        let df: u8 = 17;
-        let ca: u8 = 5;
-        let icao_int = u32::from_str_radix(&aircraft.id, 16).unwrap_or(0);
-        
-        let type_code: u64 = 11;
-        let nic_encoded = (aircraft.nic & 0xF) as u64;
-        let alt_encoded = (((aircraft.altitude + 1000.0) / 25.0) as u64) & 0xFFF;
-        let lat_encoded = (((aircraft.lat + 90.0) / 180.0) * 131071.0) as u64 & 0x1FFFF;
-        let lng_encoded = (((aircraft.lng + 180.0) / 360.0) * 131071.0) as u64 & 0x1FFFF;
-        
+    /// 注册每个 tick 调用的扩展回调，多个回调按注册顺序执行
+    pub fn add_tick_hook(&mut self, hook: impl FnMut(&mut Vec<Aircraft>, u64) + Send + 'static) {
+        self.tick_hooks.push(Box::new(hook));
+    }
+
+    /// 移除全部扩展回调
+    pub fn clear_tick_hooks(&mut self) {
+        self.tick_hooks.clear();
+    }
+
+    /// 放行已到时刻的飞机
+    fn release_departures(&mut self) {
+        let tick = self.tick;
+        let (due, pending) = std::mem::take(&mut self.departures)
+            .into_iter()
+            .partition(|d| d.release_tick <= tick);
+        self.departures = pending;
+        for departure in due {
+            let id = departure.aircraft.id.clone();
+            // ICAO 在生成时已预留，放行前若被手动添加的飞机占用则跳过
+            if self.spawn_aircraft(departure.aircraft).is_ok() {
+                if let Some(plan) = departure.plan {
+                    let _ = self.assign_route(&id, plan);
+                }
+            }
+        }
+    }
+
+    /// 移除已按 Land 计划落地的飞机，并记录航班完成事件
+    fn complete_landed_flights(&mut self) {
+        let aircrafts = &self.aircrafts;
+        let landed: Vec<FlightCompletion> = self
+            .routes
+            .iter()
+            .filter(|(_, route)| route.arrived)
+            .filter_map(|(id, route)| {
+                aircrafts
+                    .iter()
+                    .find(|a| &a.id == id && a.altitude <= 0.0)
+                    .map(|aircraft| FlightCompletion {
+                        aircraft_id: aircraft.id.clone(),
+                        callsign: aircraft.callsign.clone(),
+                        distance_nm: route.flown_nm,
+                    })
+            })
+            .collect();
+        if landed.is_empty() {
+            return;
+        }
+
+        self.retain_aircrafts(|a| !landed.iter().any(|done| done.aircraft_id == a.id));
+        self.completed.extend(landed);
+    }
+
+    /// 将飞出活动半径的飞机替换为从半径边缘向内飞行的新飞机，保持机队规模不变
+    ///
+    /// 执行飞行计划或着陆引导的飞机不受限制。新飞机沿用原飞机的高度、速度与飞行阶段，
+    /// ICAO 地址、航班号和进入方位由种子随机数决定。
+    fn replace_departed_aircraft(&mut self) {
+        let Some(radius) = self.max_radius_deg else {
+            return;
+        };
+        let (center_lat, center_lng) = (self.center_lat, self.center_lng);
+        let departed: Vec<Aircraft> = self
+            .aircrafts
+            .iter()
+            .filter(|a| !self.routes.contains_key(&a.id) && !self.landings.contains_key(&a.id))
+            .filter(|a| (a.lat - center_lat).hypot(a.lng - center_lng) > radius)
+            .cloned()
+            .collect();
+        if departed.is_empty() {
+            return;
+        }
+        self.retain_aircrafts(|a| !departed.iter().any(|d| d.id == a.id));
+
+        // 刚移出的地址也不立即复用，避免下游把新飞机接到旧航迹上
+        let mut used = self.icao_in_use();
+        let departed_ids = departed.iter().map(|a| u32::from_str_radix(&a.id, 16));
+        used.extend(departed_ids.filter_map(Result::ok));
+        for old in departed {
+            let id = self.allocate_icao(&mut used);
+            // 在半径边缘随机方位进入，航向指向中心附近 (±30°)
+            let bearing = self.rng.next_f64() * 360.0;
+            let edge = radius * 0.95;
+            let heading = (bearing + 180.0 + self.rng.range_i32(-30, 30) as f64).rem_euclid(360.0);
+            let airline: String = old.callsign.chars().take(2).collect();
+            let replacement = Aircraft {
+                id,
+                callsign: format!("{}{}", airline, self.rng.range_i32(1000, 9999)),
+                lat: center_lat + edge * bearing.to_radians().cos(),
+                lng: center_lng + edge * bearing.to_radians().sin(),
+                heading,
+                spi: false,
+                squawk: DEFAULT_SQUAWK,
+                emergency: None,
+                ..old
+            };
+            if let Err(e) = self.spawn_aircraft(replacement) {
+                eprintln!("[Rust] Failed to spawn replacement aircraft: {}", e);
+            }
+        }
+    }
+
+    /// 取走自上次调用以来完成的航班
+    pub fn take_completed_flights(&mut self) -> Vec<FlightCompletion> {
+        std::mem::take(&mut self.completed)
+    }
+
+    /// 每架飞机的发送相位偏移，由 ICAO 地址决定，避免所有飞机同一 tick 集中发送
+    fn phase_offset(aircraft: &Aircraft, every: u32) -> u64 {
+        u32::from_str_radix(&aircraft.id, 16).unwrap_or(0) as u64 % every as u64
+    }
+
+    /// 判断飞机在当前 tick 是否发送指定类型的消息
+    fn is_due(&self, aircraft: &Aircraft, message_type: MessageType) -> bool {
+        Self::due_at(self.tick, &self.cadence, aircraft, message_type)
+    }
+
+    /// 判断飞机在指定 tick 是否发送指定类型的消息，不借用模拟器，可在并行编码中调用
+    fn due_at(
+        tick: u64,
+        cadence: &MessageCadence,
+        aircraft: &Aircraft,
+        message_type: MessageType,
+    ) -> bool {
+        if !aircraft.capability.emits(message_type) {
+            return false;
+        }
+        let every = cadence.every(message_type);
+        (tick + Self::phase_offset(aircraft, every)).is_multiple_of(every as u64)
+    }
+
+    /// 获取指定飞机各类型消息的发送计划
+    pub fn get_schedule(&self, icao: &str) -> Option<Vec<ScheduleEntry>> {
+        let aircraft = self
+            .aircrafts
+            .iter()
+            .find(|a| a.id.eq_ignore_ascii_case(icao))?;
+
+        let schedule = [
+            MessageType::Position,
+            MessageType::Velocity,
+            MessageType::Identification,
+            MessageType::AllCall,
+            MessageType::OperationalStatus,
+            MessageType::Status,
+        ]
+        .into_iter()
+        .filter(|&message_type| aircraft.capability.emits(message_type))
+        .filter(|&message_type| {
+            message_type != MessageType::AllCall || aircraft.source.has_transponder()
+        })
+        .map(|message_type| {
+            let every = self.cadence.every(message_type) as u64;
+            let phase = Self::phase_offset(aircraft, every as u32);
+            // 下一次满足 (tick + k + phase) % every == 0 的 k (k >= 1)
+            let ticks_until = every - (self.tick + phase) % every;
+            ScheduleEntry {
+                message_type,
+                interval_ms: every * self.tick_interval_ms,
+                next_ms: ticks_until * self.tick_interval_ms,
+            }
+        })
+        .collect();
+        Some(schedule)
+    }
+
+    /// 记录当前时刻所有飞机的航迹点
+    fn record_history(&mut self) {
+        for aircraft in &self.aircrafts {
+            let track = self.history.entry(aircraft.id.clone()).or_default();
+            track.push_back(TrackPoint {
+                time: self.sim_time,
+                lat: aircraft.lat,
+                lng: aircraft.lng,
+                altitude: aircraft.altitude,
+            });
+            while track.len() > self.history_limit {
+                track.pop_front();
+            }
+        }
+        self.enforce_history_budget();
+    }
+
+    /// 航迹点总数超出预算时，按时间先后淘汰全体飞机（含已移除飞机）中最旧的点
+    ///
+    /// 航迹、GPX/CZML 导出与物理检查共用这份历史，预算对所有功能同时生效。
+    fn enforce_history_budget(&mut self) {
+        let mut total: usize = self.history.values().map(VecDeque::len).sum();
+        while total > self.history_budget {
+            let Some(oldest) = self
+                .history
+                .values()
+                .filter_map(|track| track.front().map(|point| point.time))
+                .min_by(f64::total_cmp)
+            else {
+                break;
+            };
+            // 逐架淘汰该时刻的点，直到回到预算内
+            for track in self.history.values_mut() {
+                if total <= self.history_budget {
+                    break;
+                }
+                if track.front().is_some_and(|point| point.time <= oldest) {
+                    track.pop_front();
+                    total -= 1;
+                }
+            }
+            self.history.retain(|_, track| !track.is_empty());
+        }
+    }
+
+    /// 获取所有飞机的航迹历史
+    pub fn get_history(&self) -> &BTreeMap<String, VecDeque<TrackPoint>> {
+        &self.history
+    }
+
+    /// 模拟开始的墙钟时间 (Unix ms)
+    pub fn start_time_ms(&self) -> u64 {
+        self.start_time_ms
+    }
+
+    /// 获取所有飞机数据
+    pub fn get_aircrafts(&self) -> &Vec<Aircraft> {
+        &self.aircrafts
+    }
+
+    /// 按 ICAO 地址 (不区分大小写) 查找单架飞机
+    pub fn get_aircraft(&self, icao: &str) -> Option<&Aircraft> {
+        self.aircrafts
+            .iter()
+            .find(|a| a.id.eq_ignore_ascii_case(icao))
+    }
+
+    /// 添加一架飞机，校验字段并拒绝重复的 ICAO 地址，返回当前飞机总数
+    ///
+    /// 用户提供的飞机按给定状态立即可用，不经历 GNSS 捕获。
+    pub fn add_aircraft(&mut self, aircraft: Aircraft) -> Result<usize, String> {
+        self.insert_aircraft(aircraft, false)
+    }
+
+    /// 添加一架模拟器生成的飞机 (放行的离场、补充的飞机等)，先经历 GNSS 捕获
+    fn spawn_aircraft(&mut self, aircraft: Aircraft) -> Result<usize, String> {
+        self.insert_aircraft(aircraft, true)
+    }
+
+    fn insert_aircraft(&mut self, mut aircraft: Aircraft, acquire: bool) -> Result<usize, String> {
+        validate_aircraft(&aircraft)?;
+        if self
+            .aircrafts
+            .iter()
+            .any(|a| a.id.eq_ignore_ascii_case(&aircraft.id))
+        {
+            return Err(format!("Aircraft {} already exists", aircraft.id));
+        }
+
+        (aircraft.ground_speed, aircraft.track) = ground_vector(&aircraft, &self.wind_layers);
+
+        // 新出现的目标先经历 GNSS 捕获：只发送速度等消息，位置消息暂缺
+        if acquire && self.acquisition_ticks > 0 {
+            self.acquiring.insert(
+                aircraft.id.clone(),
+                Acquisition {
+                    remaining: self.acquisition_ticks,
+                    nic: aircraft.nic,
+                },
+            );
+            aircraft.nic = 0;
+        }
+        self.aircrafts.push(aircraft);
+        Ok(self.aircrafts.len())
+    }
+
+    /// 从 JSON 文件加载固定机队替换当前机队，返回加载的飞机数
+    ///
+    /// 任一飞机校验失败时拒绝整个文件，当前机队保持不变。
+    pub fn load_aircrafts_from_json(&mut self, path: &str) -> Result<usize, String> {
+        let fleet = read_aircrafts_json(path)?;
+        self.retain_aircrafts(|_| false);
+        for aircraft in fleet {
+            self.add_aircraft(aircraft)?;
+        }
+        Ok(self.aircrafts.len())
+    }
+
+    /// 将当前机队导出为格式化的 JSON 场景快照，可由 `load_aircrafts_from_json` 重新加载
+    ///
+    /// 捕获中的飞机导出其配置的 NIC，而不是捕获期间广播的 0。只保存飞机状态，
+    /// 航路与进近不随快照导出 (见 [`ScenarioFile`])。序列化失败时返回错误信息。
+    pub fn export_aircrafts_to_json(&self) -> Result<String, String> {
+        let aircrafts = self
+            .aircrafts
+            .iter()
+            .cloned()
+            .map(|mut aircraft| {
+                if let Some(acquisition) = self.acquiring.get(&aircraft.id) {
+                    aircraft.nic = acquisition.nic;
+                }
+                aircraft
+            })
+            .collect();
+        let scenario = ScenarioFile {
+            center_lat: self.center_lat,
+            center_lng: self.center_lng,
+            aircrafts,
+        };
+        serde_json::to_string_pretty(&scenario).map_err(|e| e.to_string())
+    }
+
+    /// 让当前机队在接下来的 `ramp_ticks` 个 tick 内逐步出现，而不是同时出现
+    ///
+    /// 第 i 架飞机 (共 n 架) 在 i * ramp_ticks / n 个 tick 后放行，第一架立即可见。
+    /// 等待中的飞机保持原位置，放行后按新加入的目标经历 GNSS 捕获。
+    pub fn stagger_fleet(&mut self, ramp_ticks: u64) {
+        let count = self.aircrafts.len() as u64;
+        if ramp_ticks == 0 || count < 2 {
+            return;
+        }
+        let delayed: Vec<(u64, Aircraft)> = self
+            .aircrafts
+            .iter()
+            .enumerate()
+            .map(|(i, aircraft)| (i as u64 * ramp_ticks / count, aircraft.clone()))
+            .filter(|&(delay, _)| delay > 0)
+            .collect();
+        let delayed_ids: BTreeSet<String> = delayed.iter().map(|(_, a)| a.id.clone()).collect();
+        self.retain_aircrafts(|a| !delayed_ids.contains(&a.id));
+        for (delay, aircraft) in delayed {
+            self.history.remove(&aircraft.id);
+            self.departures.push(PendingDeparture {
+                release_tick: self.tick + delay,
+                aircraft,
+                plan: None,
+            });
+        }
+    }
+
+    /// 按当前速度、航向、垂直速率和风外推 `steps` 个 tick 后各飞机的位置，按 ICAO 索引
+    ///
+    /// 不考虑航路、阶段与随机扰动，速度恒定时与实际推进结果一致。
+    pub fn predict_positions(&self, steps: u32) -> BTreeMap<String, TrackPoint> {
+        let seconds = steps as f64;
+        self.aircrafts
+            .iter()
+            .map(|aircraft| {
+                let (lat, lng) = advance_position(aircraft, &self.wind_layers, seconds);
+                let point = TrackPoint {
+                    time: self.sim_time + seconds,
+                    lat,
+                    lng,
+                    altitude: aircraft.altitude + aircraft.vertical_rate / 60.0 * seconds,
+                };
+                (aircraft.id.clone(), point)
+            })
+            .collect()
+    }
+
+    /// 按 ICAO 地址移除一架飞机（不区分大小写），不存在时返回 false
+    pub fn remove_aircraft(&mut self, icao: &str) -> bool {
+        let before = self.aircrafts.len();
+        self.retain_aircrafts(|a| !a.id.eq_ignore_ascii_case(icao));
+        self.aircrafts.len() < before
+    }
+
+    /// 仅保留满足条件的飞机
+    pub fn retain_aircrafts(&mut self, keep: impl FnMut(&Aircraft) -> bool) {
+        self.aircrafts.retain(keep);
+        let aircrafts = &self.aircrafts;
+        self.routes
+            .retain(|id, _| aircrafts.iter().any(|a| &a.id == id));
+        self.acquiring
+            .retain(|id, _| aircrafts.iter().any(|a| &a.id == id));
+        self.landings
+            .retain(|id, _| aircrafts.iter().any(|a| &a.id == id));
+        self.cpr_odd
+            .retain(|(id, _), _| aircrafts.iter().any(|a| &a.id == id));
+    }
+
+    /// 设置飞机的紧急状态并切换到对应的应答机编码，`None` 时恢复为缺省编码 1200
+    pub fn set_emergency(&mut self, icao: &str, kind: Option<EmergencyKind>) -> Result<(), String> {
+        let aircraft = self
+            .aircrafts
+            .iter_mut()
+            .find(|a| a.id.eq_ignore_ascii_case(icao))
+            .ok_or_else(|| format!("Aircraft {} not found", icao))?;
+        aircraft.emergency = kind;
+        aircraft.squawk = kind.map_or(DEFAULT_SQUAWK, |kind| kind.squawk());
+        Ok(())
+    }
+
+    /// 为指定飞机分配飞行计划
+    pub fn assign_route(&mut self, icao: &str, plan: FlightPlan) -> Result<(), String> {
+        if plan.waypoints.is_empty() {
+            return Err("Flight plan has no waypoints".to_string());
+        }
+        let id = self
+            .aircrafts
+            .iter()
+            .find(|a| a.id.eq_ignore_ascii_case(icao))
+            .map(|a| a.id.clone())
+            .ok_or_else(|| format!("Aircraft {} not found", icao))?;
+        self.landings.remove(&id);
+        self.routes.insert(
+            id,
+            ActiveRoute {
+                plan,
+                next: 0,
+                flown_nm: 0.0,
+                arrived: false,
+            },
+        );
+        Ok(())
+    }
+
+    /// 获取指定飞机的飞行计划
+    pub fn get_flight_plan(&self, icao: &str) -> Option<&FlightPlan> {
+        self.routes
+            .iter()
+            .find(|(id, _)| id.eq_ignore_ascii_case(icao))
+            .map(|(_, route)| &route.plan)
+    }
+
+    /// 已占用的 ICAO 地址：当前机队与等待放行的飞机
+    fn icao_in_use(&self) -> HashSet<u32> {
+        self.aircrafts
+            .iter()
+            .chain(self.departures.iter().map(|d| &d.aircraft))
+            .filter_map(|a| u32::from_str_radix(&a.id, 16).ok())
+            .collect()
+    }
+
+    /// 由随机数抽取一个不在 `used` 中的 ICAO 地址并登记到 `used`
+    ///
+    /// `used` 取自 `icao_in_use`，同一批连续分配时共用，保证批内也不重复。
+    fn allocate_icao(&mut self, used: &mut HashSet<u32>) -> String {
+        let available = (ICAO_ADDRESS_MAX - ICAO_ADDRESS_MIN + 1) as u64;
+        loop {
+            let address = ICAO_ADDRESS_MIN + (self.rng.next_u64() % available) as u32;
+            if used.insert(address) {
+                return format!("{:06X}", address);
+            }
+        }
+    }
+
+    /// 创建一个沿大圆航线从起点飞往终点的航班
+    ///
+    /// 航线按固定间隔采样为航路点，起点端按约 3° 梯度爬升至巡航高度，
+    /// 终点端同样按 3° 梯度下降，到达终点后落地并移出机队。
+    pub fn create_flight(
+        &mut self,
+        callsign: &str,
+        from: (f64, f64),
+        to: (f64, f64),
+        cruise_altitude: f64,
+        speed: f64,
+    ) -> Result<Aircraft, String> {
+        if callsign.trim().is_empty() {
+            return Err("Callsign must not be empty".to_string());
+        }
+
+        let total = geo::distance_nm(from.0, from.1, to.0, to.1);
+        let segments = (total / FLIGHT_WAYPOINT_SPACING_NM).ceil().max(1.0) as usize;
+        let waypoints: Vec<Waypoint> = (0..=segments)
+            .map(|i| {
+                let fraction = i as f64 / segments as f64;
+                let (lat, lng) = geo::intermediate_point(from.0, from.1, to.0, to.1, fraction);
+                let flown = total * fraction;
+                let remaining = total - flown;
+                let altitude = (FLIGHT_TERMINAL_ALTITUDE + FLIGHT_CLIMB_GRADIENT * flown)
+                    .min(FLIGHT_TERMINAL_ALTITUDE + FLIGHT_CLIMB_GRADIENT * remaining)
+                    .min(cruise_altitude);
+                Waypoint {
+                    lat,
+                    lng,
+                    altitude: Some(altitude),
+                }
+            })
+            .collect();
+
+        let mut used = self.icao_in_use();
+        let aircraft = Aircraft {
+            id: self.allocate_icao(&mut used),
+            callsign: callsign.trim().to_uppercase(),
+            lat: from.0,
+            lng: from.1,
+            altitude: FLIGHT_TERMINAL_ALTITUDE.min(cruise_altitude),
+            speed,
+            heading: geo::initial_bearing(from.0, from.1, to.0, to.1),
+            nic: 8,
+            vertical_rate: 0.0,
+            capability: CapabilityProfile::default(),
+            source: AdsbSource::default(),
+            phase: None,
+            target_altitude: None,
+            on_ground: false,
+            version: default_version(),
+            nacp: default_nacp(),
+            nacv: default_nacv(),
+            sil: default_sil(),
+            spi: false,
+            capability_class: CapabilityClass::default(),
+            category: AircraftCategory::default(),
+            squawk: DEFAULT_SQUAWK,
+            track: 0.0,
+            ground_speed: 0.0,
+            emergency: None,
+        };
+        validate_aircraft(&aircraft)?;
+        if !(FLIGHT_TERMINAL_ALTITUDE..=60000.0).contains(&cruise_altitude) {
+            return Err(format!("Cruise altitude {} out of range", cruise_altitude));
+        }
+
+        self.spawn_aircraft(aircraft.clone())?;
+        self.assign_route(
+            &aircraft.id,
+            FlightPlan {
+                waypoints,
+                on_complete: RouteCompletion::Land,
+            },
+        )?;
+        Ok(aircraft)
+    }
+
+    /// 在同一定位点生成垂直堆叠的等待航线机群
+    ///
+    /// 第 i 架飞机高度为 `base_altitude + i * separation_ft`，各自以定位点为唯一航路点
+    /// 并在到达后持续盘旋等待。
+    pub fn create_holding_stack(
+        &mut self,
+        fix: (f64, f64),
+        count: usize,
+        base_altitude: f64,
+        separation_ft: f64,
+    ) -> Result<Vec<Aircraft>, String> {
+        if count == 0 {
+            return Err("Holding stack count must be positive".to_string());
+        }
+        if !(separation_ft.is_finite() && separation_ft > 0.0) {
+            return Err(format!("Invalid stack separation: {}", separation_ft));
+        }
+        let top = base_altitude + separation_ft * (count - 1) as f64;
+        if !(0.0..=60000.0).contains(&base_altitude) || top > 60000.0 {
+            return Err(format!(
+                "Holding stack altitudes {}-{} out of range",
+                base_altitude, top
+            ));
+        }
+
+        // 先生成并校验整个机群，全部通过后再加入机队，避免失败时留下半个机群
+        let mut stack: Vec<Aircraft> = Vec::with_capacity(count);
+        let mut used = self.icao_in_use();
+        let mut number = 0;
+        for i in 0..count {
+            let altitude = base_altitude + separation_ft * i as f64;
+            let id = self.allocate_icao(&mut used);
+            // 呼号编号跳过机队中已使用的 HOLDnn
+            let callsign = loop {
+                number += 1;
+                let callsign = format!("HOLD{:02}", number);
+                let taken = |a: &Aircraft| a.callsign.eq_ignore_ascii_case(&callsign);
+                if !self.aircrafts.iter().any(taken) {
+                    break callsign;
+                }
+            };
+            let aircraft = Aircraft {
+                id,
+                callsign,
+                lat: fix.0,
+                lng: fix.1,
+                altitude,
+                speed: HOLDING_SPEED_KTS,
+                heading: 360.0 * i as f64 / count as f64,
+                nic: 8,
+                vertical_rate: 0.0,
+                capability: CapabilityProfile::default(),
+                source: AdsbSource::default(),
+                phase: None,
+                target_altitude: None,
+                on_ground: false,
+                version: default_version(),
+                nacp: default_nacp(),
+                nacv: default_nacv(),
+                sil: default_sil(),
+                spi: false,
+                capability_class: CapabilityClass::default(),
+                category: AircraftCategory::default(),
+                squawk: DEFAULT_SQUAWK,
+                track: 0.0,
+                ground_speed: 0.0,
+                emergency: None,
+            };
+            validate_aircraft(&aircraft)?;
+            stack.push(aircraft);
+        }
+
+        for aircraft in &stack {
+            self.spawn_aircraft(aircraft.clone())?;
+            self.assign_route(
+                &aircraft.id,
+                FlightPlan {
+                    waypoints: vec![Waypoint {
+                        lat: fix.0,
+                        lng: fix.1,
+                        altitude: Some(aircraft.altitude),
+                    }],
+                    on_complete: RouteCompletion::Hold,
+                },
+            )?;
+        }
+        Ok(stack)
+    }
+
+    /// 引导飞机沿 3° 下滑道在指定跑道着陆
+    ///
+    /// 飞机切入并对准跑道延长线，进近阶段减速，接地后转为地面状态沿跑道滑跑，
+    /// 此后发送地面位置消息。取消该飞机已有的飞行计划。
+    pub fn land_aircraft(&mut self, icao: &str, runway: Runway) -> Result<(), String> {
+        if !(-90.0..=90.0).contains(&runway.lat) || !(-180.0..=180.0).contains(&runway.lng) {
+            return Err(format!("Invalid runway position ({}, {})", runway.lat, runway.lng));
+        }
+        if !(0.0..360.0).contains(&runway.heading) {
+            return Err(format!("Runway heading {} out of range", runway.heading));
+        }
+        let aircraft = self
+            .find_aircraft_mut(icao)
+            .ok_or_else(|| format!("Aircraft {} not found", icao))?;
+        if aircraft.on_ground {
+            return Err(format!("Aircraft {} is already on ground", aircraft.id));
+        }
+        aircraft.phase = Some(FlightPhase::Approach);
+        aircraft.target_altitude = None;
+        let id = aircraft.id.clone();
+        self.routes.remove(&id);
+        self.landings.insert(id, runway);
+        Ok(())
+    }
+
+    /// 生成两条平行跑道的同时离场序列
+    ///
+    /// 每条跑道各 `count` 架，两条跑道同时放行，相邻批次间隔 `interval_s` 秒；
+    /// 首批立即加入机队，其余在到达放行时刻时加入。飞机在抬轮离地后的越障高度出现，
+    /// 以空中状态沿跑道方向爬升。
+    /// 返回全部离场飞机（按放行顺序）。
+    pub fn create_parallel_departures(
+        &mut self,
+        runway_a: Runway,
+        runway_b: Runway,
+        count: usize,
+        interval_s: u32,
+    ) -> Result<Vec<Aircraft>, String> {
+        if count == 0 {
+            return Err("Departure count must be positive".to_string());
+        }
+        if count > 1 && interval_s == 0 {
+            return Err("Departure interval must be positive".to_string());
+        }
+        for runway in [&runway_a, &runway_b] {
+            if !(0.0..360.0).contains(&runway.heading) {
+                return Err(format!("Runway heading {} out of range", runway.heading));
+            }
+        }
+        if geo::heading_difference(runway_a.heading, runway_b.heading).abs()
+            > PARALLEL_RUNWAY_TOLERANCE_DEG
+        {
+            return Err(format!(
+                "Runways {} and {} are not parallel",
+                runway_a.heading, runway_b.heading
+            ));
+        }
+
+        let mut created = Vec::with_capacity(count * 2);
+        let mut used = self.icao_in_use();
+        for i in 0..count {
+            for (label, runway) in [('A', &runway_a), ('B', &runway_b)] {
+                let aircraft = Aircraft {
+                    id: self.allocate_icao(&mut used),
+                    callsign: format!("DEP{}{:02}", label, i + 1),
+                    lat: runway.lat,
+                    lng: runway.lng,
+                    altitude: FIELD_ELEVATION_FT + DEPARTURE_SCREEN_HEIGHT_FT,
+                    speed: DEPARTURE_SPEED_KTS,
+                    heading: runway.heading,
+                    nic: 8,
+                    vertical_rate: PHASE_VERTICAL_RATE_FPS * 60.0,
+                    capability: CapabilityProfile::default(),
+                    source: AdsbSource::default(),
+                    phase: Some(FlightPhase::Climb),
+                    target_altitude: Some(DEPARTURE_ALTITUDE),
+                    on_ground: false,
+                    version: default_version(),
+                    nacp: default_nacp(),
+                    nacv: default_nacv(),
+                    sil: default_sil(),
+                    spi: false,
+                    capability_class: CapabilityClass::default(),
+                    category: AircraftCategory::default(),
+                    squawk: DEFAULT_SQUAWK,
+                    track: 0.0,
+                    ground_speed: 0.0,
+                    emergency: None,
+                };
+                validate_aircraft(&aircraft)?;
+                let (lat, lng) =
+                    geo::destination_point(runway.lat, runway.lng, runway.heading, DEPARTURE_LEG_NM);
+                self.departures.push(PendingDeparture {
+                    release_tick: self.tick + (i as u64) * interval_s as u64,
+                    aircraft: aircraft.clone(),
+                    plan: Some(FlightPlan {
+                        waypoints: vec![Waypoint {
+                            lat,
+                            lng,
+                            altitude: Some(DEPARTURE_ALTITUDE),
+                        }],
+                        on_complete: RouteCompletion::Hold,
+                    }),
+                });
+                created.push(aircraft);
+            }
+        }
+        self.release_departures();
+        Ok(created)
+    }
+
+    /// 添加地址欺骗发射源（安全测试用），目标飞机必须存在
+    pub fn add_spoofer(&mut self, target_id: &str, offset: (f64, f64)) -> Result<(), String> {
+        let target = self
+            .aircrafts
+            .iter()
+            .find(|a| a.id.eq_ignore_ascii_case(target_id))
+            .ok_or_else(|| format!("Aircraft {} not found", target_id))?;
+        self.spoofers.push(Spoofer {
+            target_id: target.id.clone(),
+            lat_offset: offset.0,
+            lng_offset: offset.1,
+        });
+        Ok(())
+    }
+
+    /// 移除所有地址欺骗发射源
+    pub fn clear_spoofers(&mut self) {
+        self.spoofers.clear();
+    }
+
+    /// 欺骗源当前伪造的飞机状态（与目标同 ICAO，位置带偏移）
+    pub fn get_spoofed_aircrafts(&self) -> Vec<Aircraft> {
+        self.spoofers
+            .iter()
+            .filter_map(|spoofer| {
+                let target = self.aircrafts.iter().find(|a| a.id == spoofer.target_id)?;
+                let mut ghost = target.clone();
+                ghost.lat = (ghost.lat + spoofer.lat_offset).clamp(-90.0, 90.0);
+                // 经度越过 ±180° 时回绕到 [-180, 180)
+                ghost.lng = (ghost.lng + spoofer.lng_offset + 540.0).rem_euclid(360.0) - 180.0;
+                Some(ghost)
+            })
+            .collect()
+    }
+
+    fn find_aircraft_mut(&mut self, icao: &str) -> Option<&mut Aircraft> {
+        self.aircrafts
+            .iter_mut()
+            .find(|a| a.id.eq_ignore_ascii_case(icao))
+    }
+
+    /// 设置指定飞机的设备能力档位，找不到该飞机时返回 false
+    pub fn set_capability_profile(&mut self, icao: &str, profile: CapabilityProfile) -> bool {
+        match self.find_aircraft_mut(icao) {
+            Some(aircraft) => {
+                aircraft.capability = profile;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 设置指定飞机的监视数据来源，找不到该飞机时返回 false
+    pub fn set_source(&mut self, icao: &str, source: AdsbSource) -> bool {
+        match self.find_aircraft_mut(icao) {
+            Some(aircraft) => {
+                aircraft.source = source;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 模拟地面询问机的询问，返回该飞机应答机的应答报文（AP 为 CRC 与地址异或）
+    ///
+    /// 支持 UF4 (高度)、UF5 (识别码)、UF20/21 (Comm-B，MB 字段为 BDS 2,0 航班号)。
+    /// TIS-B 与 ADS-R 目标由地面站代为广播，没有 Mode S 应答机，返回 None。
+    pub fn interrogate(&self, icao: &str, uf: u8) -> Result<Option<String>, String> {
+        let aircraft = self
+            .aircrafts
+            .iter()
+            .find(|a| a.id.eq_ignore_ascii_case(icao))
+            .ok_or_else(|| format!("Aircraft {} not found", icao))?;
+        if !aircraft.source.has_transponder() {
+            return Ok(None);
+        }
+        let address = u32::from_str_radix(&aircraft.id, 16).map_err(|e| e.to_string())?;
+
+        // FS(3) DR(5) UM(6)：FS 0 为空中、1 为地面，无下行请求与告警
+        let fs = aircraft.on_ground as u128;
+        let code13 = match uf {
+            4 | 20 => encode_ac13(aircraft.altitude),
+            5 | 21 => encode_id13(aircraft.squawk),
+            _ => return Err(format!("Unsupported uplink format UF{}", uf)),
+        } as u128;
+        let mut payload = (fs << 24) | code13;
+        if uf >= 20 {
+            let mb = (BDS_IDENTIFICATION << 48) | encode_callsign(&aircraft.callsign);
+            payload = (payload << 56) | mb as u128;
+        }
+        assemble_raw(uf, payload, Some(address)).map(Some)
+    }
+
+    /// 生成空中位置消息 (DF17 Type 9-18)，`odd` 选择 CPR 奇帧或偶帧
+    pub fn generate_position_message(aircraft: &Aircraft, odd: bool) -> String {
+        let (df, ca) = aircraft.source.downlink_header();
+        let icao_int = u32::from_str_radix(&aircraft.id, 16).unwrap_or(0);
+        let (lat_cpr, lng_cpr) = cpr_encode(aircraft.lat, aircraft.lng, odd);
+
+        // 空中位置类型码由 NIC 决定
+        let type_code: u64 = match aircraft.nic {
+            11 => 9,
+            10 => 10,
+            8 | 9 => 11,
+            7 => 12,
+            6 => 13,
+            5 => 14,
+            4 => 15,
+            2 | 3 => 16,
+            1 => 17,
+            _ => 18,
+        };
+        let alt_encoded = encode_altitude_ft(aircraft.altitude) as u64;
+
         let mut payload: u64 = 0;
         payload |= type_code << 51;
-        payload |= nic_encoded << 47;
-        payload |= alt_encoded << 35;
-        payload |= lat_encoded << 16;
-        payload |= lng_encoded;
-        
+        payload |= alt_encoded << 36;
+        payload |= (odd as u64) << 34;
+        payload |= (lat_cpr as u64) << 17;
+        payload |= lng_cpr as u64;
+
+        assemble_message(df, ca, icao_int, payload)
+    }
+
+    /// 生成机场信标的地面位置消息 (DF18 CF=0, Type 5)
+    ///
+    /// 信标为非应答机设备，位置经过测量，使用最高精度的 TC=5，运动状态为静止。
+    pub fn generate_beacon_message(beacon: &AirportBeacon, odd: bool) -> String {
+        let icao_int = u32::from_str_radix(&beacon.icao, 16).unwrap_or(0);
+        let (lat_cpr, lng_cpr) = cpr_encode_surface(beacon.lat, beacon.lng, odd);
+
+        let type_code: u64 = 5;
+        let movement: u64 = 1; // 静止
+        let mut payload: u64 = 0;
+        payload |= type_code << 51;
+        payload |= movement << 44;
+        payload |= (odd as u64) << 34;
+        payload |= (lat_cpr as u64) << 17;
+        payload |= lng_cpr as u64;
+
+        assemble_message(18, 0, icao_int, payload)
+    }
+
+    /// 生成地面位置消息 (DF17 Type 5-8)：不含高度，编码地面移动速度与地面航迹
+    pub fn generate_surface_position_message(aircraft: &Aircraft, odd: bool) -> String {
+        let (df, ca) = aircraft.source.downlink_header();
+        let icao_int = u32::from_str_radix(&aircraft.id, 16).unwrap_or(0);
+        let (lat_cpr, lng_cpr) = cpr_encode_surface(aircraft.lat, aircraft.lng, odd);
+
+        // 地面位置类型码由 NIC 决定
+        let type_code: u64 = match aircraft.nic {
+            11 => 5,
+            10 => 6,
+            8 | 9 => 7,
+            _ => 8,
+        };
+        let track = quantize_angle(aircraft.track, 7);
+        let mut payload: u64 = 0;
+        payload |= type_code << 51;
+        payload |= encode_ground_movement(aircraft.ground_speed) << 44;
+        payload |= 1 << 43; // 地面航迹有效
+        payload |= track << 36;
+        payload |= (odd as u64) << 34;
+        payload |= (lat_cpr as u64) << 17;
+        payload |= lng_cpr as u64;
+
+        assemble_message(df, ca, icao_int, payload)
+    }
+
+    /// 生成识别消息 (DF17 Type 1-4)：航班号按 6 位字符集编码，类别子字段取自 `category`
+    pub fn generate_identification_message(aircraft: &Aircraft) -> String {
+        let (df, ca) = aircraft.source.downlink_header();
+        let icao_int = u32::from_str_radix(&aircraft.id, 16).unwrap_or(0);
+
+        let mut payload: u64 = 0;
+        payload |= IDENTIFICATION_TYPE_CODE << 51;
+        payload |= (aircraft.category.code() as u64) << 48;
+        payload |= encode_callsign(&aircraft.callsign);
+
+        assemble_message(df, ca, icao_int, payload)
+    }
+
+    /// 生成飞机状态消息 (DF17 Type 28 子类型 1)：紧急/优先状态和应答机编码
+    pub fn generate_status_message(aircraft: &Aircraft) -> String {
+        let (df, ca) = aircraft.source.downlink_header();
+        let icao_int = u32::from_str_radix(&aircraft.id, 16).unwrap_or(0);
+        let emergency = aircraft.emergency_state().map_or(0, |kind| kind.code());
+
+        let mut payload: u64 = 0;
+        payload |= STATUS_TYPE_CODE << 51;
+        payload |= 1 << 48;
+        payload |= (emergency as u64) << 45;
+        payload |= (encode_id13(aircraft.squawk) as u64) << 32;
+
+        assemble_message(df, ca, icao_int, payload)
+    }
+
+    /// 生成运行状态消息 (DF17 Type 31)：子类型 0 为空中、1 为地面
+    ///
+    /// 运行模式中 IDENT 位取自 SPI，TCAS RA 位由调用方根据冲突判断给出 (仅装有 TCAS 时置位)。
+    pub fn generate_operational_status_message(aircraft: &Aircraft, tcas_ra: bool) -> String {
+        let (df, ca) = aircraft.source.downlink_header();
+        let icao_int = u32::from_str_radix(&aircraft.id, 16).unwrap_or(0);
+
+        // 运行模式 (ME 25-40)：ME 27 为 TCAS RA 激活，ME 28 为 IDENT 激活
+        let ra_active = tcas_ra && aircraft.capability_class.tcas;
+        let operational_mode = (ra_active as u64) << 13 | (aircraft.spi as u64) << 12;
+
+        let mut payload: u64 = 0;
+        payload |= OPERATIONAL_STATUS_TYPE_CODE << 51;
+        payload |= (aircraft.on_ground as u64) << 48;
+        payload |= (aircraft.capability_class.code(aircraft.on_ground) as u64) << 32;
+        payload |= operational_mode << 16;
+        payload |= (aircraft.version.min(7) as u64) << 13;
+        payload |= (aircraft.nacp.min(15) as u64) << 8;
+        payload |= (aircraft.sil.min(3) as u64) << 4;
+
         assemble_message(df, ca, icao_int, payload)
     }
 
-    /// 生成速度消息 (DF17 Type 19)
+    /// 当前处于 TCAS 决断咨询状态的飞机：装有 TCAS 的空中飞机与另一架空中飞机
+    /// 的水平距离小于 TCAS_RA_RANGE_NM 且高度差小于 TCAS_RA_ALTITUDE_FT
+    pub fn tcas_ra_aircraft(&self) -> BTreeSet<String> {
+        let airborne: Vec<&Aircraft> = self.aircrafts.iter().filter(|a| !a.on_ground).collect();
+        let mut active = BTreeSet::new();
+        for (i, a) in airborne.iter().enumerate() {
+            for b in &airborne[i + 1..] {
+                if (a.altitude - b.altitude).abs() >= TCAS_RA_ALTITUDE_FT
+                    || geo::distance_nm(a.lat, a.lng, b.lat, b.lng) >= TCAS_RA_RANGE_NM
+                {
+                    continue;
+                }
+                for aircraft in [a, b] {
+                    if aircraft.capability_class.tcas {
+                        active.insert(aircraft.id.clone());
+                    }
+                }
+            }
+        }
+        active
+    }
+
+    /// 生成全呼应答 (DF11)：CA(3) + AA(24) + PI(24)
+    ///
+    /// 断续振荡的询问机识别码 II 为 0，PI 即为前 32 位的 CRC。
+    /// CA 为 4 (地面) 或 5 (空中)，表示 2 级以上应答机。
+    pub fn generate_all_call_reply(aircraft: &Aircraft) -> String {
+        let icao_int = u32::from_str_radix(&aircraft.id, 16).unwrap_or(0) & 0xFF_FFFF;
+        let ca: u128 = if aircraft.on_ground { 4 } else { 5 };
+        // 地址已截断为 24 位，载荷必然容纳于 27 位
+        assemble_raw(11, (ca << 24) | icao_int as u128, None).unwrap_or_default()
+    }
+
+    /// 立即生成所有装有应答机的飞机的一组 DF11 全呼应答，不受发送周期限制
+    pub fn all_call_burst(&self) -> Vec<AdsbEvent> {
+        self.aircrafts
+            .iter()
+            .filter(|aircraft| aircraft.source.has_transponder())
+            .map(|aircraft| AdsbEvent {
+                hex_message: Self::generate_all_call_reply(aircraft),
+                aircraft_id: aircraft.id.clone(),
+                message_type: MessageType::AllCall,
+                rssi: None,
+                spoofed: false,
+            })
+            .collect()
+    }
+
+    /// 生成速度消息 (DF17 Type 19, 子类型 1)：地速按东西、南北分量编码，附带 NACv 与垂直速率
     pub fn generate_velocity_message(aircraft: &Aircraft) -> String {
-        let df: u8 = 17;
-        let ca: u8 = 5;
+        let (df, ca) = aircraft.source.downlink_header();
         let icao_int = u32::from_str_radix(&aircraft.id, 16).unwrap_or(0);
         
         let type_code: u64 = 19;
         let sub_type: u64 = 1;
-        let speed_encoded = (aircraft.speed as u64) & 0x3FF;
-        let heading_encoded = ((aircraft.heading / 360.0) * 127.0) as u64 & 0x7F;
+        // 子类型 1 为地速：按地速矢量（航迹角）而非机头航向编码
+        let track = aircraft.track.to_radians();
+        // 方向位 1 表示向西/向南，速度值为 |v| + 1 (0 表示无效)
+        let component = |v: f64| -> u64 {
+            let magnitude = quantize(v.abs(), 1.0, 0x3FE) + 1;
+            ((v < 0.0) as u64) << 10 | magnitude
+        };
         
         let mut payload: u64 = 0;
         payload |= type_code << 51;
         payload |= sub_type << 48;
-        payload |= speed_encoded << 30;
-        payload |= heading_encoded << 20;
+        payload |= (aircraft.nacv.min(7) as u64) << 43;
+        payload |= component(aircraft.ground_speed * track.sin()) << 32;
+        payload |= component(aircraft.ground_speed * track.cos()) << 21;
+        // 垂直速率：气压来源，符号位 1 表示下降，64 ft/min 分辨率，值为 |vr| / 64 + 1
+        let vertical_rate = quantize(aircraft.vertical_rate.abs(), 64.0, 0x1FE) + 1;
+        payload |= 1 << 20;
+        payload |= ((aircraft.vertical_rate < 0.0) as u64) << 19;
+        payload |= vertical_rate << 10;
         
         assemble_message(df, ca, icao_int, payload)
     }
 
     /// 生成所有飞机的 ADS-B 消息
-    pub fn generate_all_messages(&self) -> Vec<AdsbEvent> {
+    ///
+    /// 每架飞机的位置消息按发送顺序在 CPR 偶帧、奇帧之间交替。
+    /// 奇偶帧标志先串行分配，各飞机的报文再用 rayon 并行编码；编码过程不使用随机数，
+    /// 输出顺序与串行生成一致（按飞机顺序，欺骗源在后，机场信标最后）。
+    pub fn generate_all_messages(&mut self) -> Vec<AdsbEvent> {
+        let spoofed = self.get_spoofed_aircrafts();
+        // 冲突判断需两两比较，仅在有运行状态消息待发时计算
+        let tcas_ra = if self
+            .aircrafts
+            .iter()
+            .any(|a| self.is_due(a, MessageType::OperationalStatus))
+        {
+            self.tcas_ra_aircraft()
+        } else {
+            BTreeSet::new()
+        };
+
+        // 位置消息（GNSS 捕获完成前不发送）：取出本次的奇偶帧标志，并翻转供下一次使用
+        let senders: Vec<&Aircraft> = self.aircrafts.iter().chain(spoofed.iter()).collect();
+        let (tick, cadence) = (self.tick, self.cadence);
+        let real = self.aircrafts.len();
+        // 欺骗源与被冒用的飞机 ICAO 相同，各自独立交替奇偶帧
+        let cpr_flags: Vec<Option<bool>> = senders
+            .iter()
+            .enumerate()
+            .map(|(index, aircraft)| {
+                let due = Self::due_at(tick, &cadence, aircraft, MessageType::Position);
+                if !due || self.acquiring.contains_key(&aircraft.id) {
+                    return None;
+                }
+                let key = (aircraft.id.clone(), index >= real);
+                let next = self.cpr_odd.entry(key).or_insert(false);
+                let odd = *next;
+                *next = !odd;
+                Some(odd)
+            })
+            .collect();
+
+        let mut events: Vec<AdsbEvent> = senders
+            .par_iter()
+            .zip(cpr_flags.par_iter())
+            .enumerate()
+            .flat_map_iter(|(index, (aircraft, &cpr_odd))| {
+                let tcas_ra = tcas_ra.contains(&aircraft.id);
+                let spoofed = index >= real;
+                Self::aircraft_messages(aircraft, tick, &cadence, cpr_odd, tcas_ra, spoofed)
+            })
+            .collect();
+
+        // 机场信标，奇偶帧逐 tick 交替
+        if let Some(beacon) = &self.beacon {
+            events.push(AdsbEvent {
+                hex_message: Self::generate_beacon_message(beacon, self.tick % 2 == 1),
+                aircraft_id: beacon.icao.clone(),
+                message_type: MessageType::SurfacePosition,
+                rssi: None,
+                spoofed: false,
+            });
+        }
+        events
+    }
+
+    /// 编码单架飞机在指定 tick 应发送的全部消息
+    ///
+    /// `cpr_odd` 为 None 时不发送位置消息，否则为本次位置消息的奇偶帧标志。
+    fn aircraft_messages(
+        aircraft: &Aircraft,
+        tick: u64,
+        cadence: &MessageCadence,
+        cpr_odd: Option<bool>,
+        tcas_ra: bool,
+        spoofed: bool,
+    ) -> Vec<AdsbEvent> {
         let mut events = Vec::new();
-        
-        for aircraft in &self.aircrafts {
-            // 位置消息
+        let is_due = |message_type| Self::due_at(tick, cadence, aircraft, message_type);
+
+        // 位置消息，地面飞机发送地面位置消息
+        if let Some(odd) = cpr_odd {
+            events.push(if aircraft.on_ground {
+                AdsbEvent {
+                    hex_message: Self::generate_surface_position_message(aircraft, odd),
+                    aircraft_id: aircraft.id.clone(),
+                    message_type: MessageType::SurfacePosition,
+                    rssi: None,
+                    spoofed,
+                }
+            } else {
+                AdsbEvent {
+                    hex_message: Self::generate_position_message(aircraft, odd),
+                    aircraft_id: aircraft.id.clone(),
+                    message_type: MessageType::Position,
+                    rssi: None,
+                    spoofed,
+                }
+            });
+        }
+
+        // 速度消息（地面飞机的运动信息包含在地面位置消息中）
+        if is_due(MessageType::Velocity) && !aircraft.on_ground {
             events.push(AdsbEvent {
-                hex_message: Self::generate_position_message(aircraft),
+                hex_message: Self::generate_velocity_message(aircraft),
                 aircraft_id: aircraft.id.clone(),
-                message_type: "position".to_string(),
+                message_type: MessageType::Velocity,
+                rssi: None,
+                spoofed,
             });
-            
-            // 速度消息
+        }
+
+        // 识别消息（按 identification_every 周期发送）
+        if is_due(MessageType::Identification) {
             events.push(AdsbEvent {
-                hex_message: Self::generate_velocity_message(aircraft),
+                hex_message: Self::generate_identification_message(aircraft),
                 aircraft_id: aircraft.id.clone(),
-                message_type: "velocity".to_string(),
+                message_type: MessageType::Identification,
+                rssi: None,
+                spoofed,
+            });
+        }
+
+        // 运行状态消息：能力类别与运行模式 (IDENT、TCAS RA)
+        if is_due(MessageType::OperationalStatus) {
+            events.push(AdsbEvent {
+                hex_message: Self::generate_operational_status_message(aircraft, tcas_ra),
+                aircraft_id: aircraft.id.clone(),
+                message_type: MessageType::OperationalStatus,
+                rssi: None,
+                spoofed,
+            });
+        }
+
+        // 飞机状态消息：紧急状态期间每次都发送
+        let in_emergency =
+            aircraft.emergency_state().is_some() && aircraft.capability.emits(MessageType::Status);
+        if in_emergency || is_due(MessageType::Status) {
+            events.push(AdsbEvent {
+                hex_message: Self::generate_status_message(aircraft),
+                aircraft_id: aircraft.id.clone(),
+                message_type: MessageType::Status,
+                rssi: None,
+                spoofed,
+            });
+        }
+
+        // 全呼应答（仅真实应答机发送）
+        if is_due(MessageType::AllCall) && aircraft.source.has_transponder() {
+            events.push(AdsbEvent {
+                hex_message: Self::generate_all_call_reply(aircraft),
+                aircraft_id: aircraft.id.clone(),
+                message_type: MessageType::AllCall,
+                rssi: None,
+                spoofed,
             });
         }
-        
         events
     }
-}
 
-/// 组装 ADS-B 消息
-fn assemble_message(df: u8, ca: u8, icao: u32, payload: u64) -> String {
-    // 112 bits total: DF(5) + CA(3) + ICAO(24) + Payload(56) + PI(24)
-    let mut msg: u128 = 0;
-    msg |= (df as u128) << 107;
-    msg |= (ca as u128) << 104;
-    msg |= (icao as u128) << 80;
-    msg |= (payload as u128) << 24;
-    msg |= 0xA5A5A5; // 简化的校验码
-    
-    format!("{:028X}", msg)
-}
+    /// 按接收模型随机丢弃远处飞机的消息，并为收到的消息附加信号强度
+    ///
+    /// 未启用接收模型时原样返回；找不到发送者位置的消息（如机场信标）总是保留。
+    pub fn apply_reception(&mut self, events: Vec<AdsbEvent>) -> Vec<AdsbEvent> {
+        let Some(model) = self.reception else {
+            return events;
+        };
+        let (lat, lng) = model.receiver_position;
+        // 欺骗源与被冒用的飞机 ICAO 相同，按 (ICAO, 是否欺骗源) 分别取发送者位置
+        let spoofed = self.get_spoofed_aircrafts();
+        let real = self.aircrafts.iter().map(|a| (a, false));
+        let geometry: HashMap<(&str, bool), (f64, f64)> = real
+            .chain(spoofed.iter().map(|a| (a, true)))
+            .map(|(a, spoofed)| {
+                let distance = geo::distance_nm(lat, lng, a.lat, a.lng);
+                ((a.id.as_str(), spoofed), (distance, a.altitude))
+            })
+            .collect();
 
-/// 简单随机数生成（不依赖外部库）
-fn rand_simple() -> f64 {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let nanos = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .subsec_nanos();
-    (nanos % 1000) as f64 / 1000.0
-}
+        events
+            .into_iter()
+            .filter_map(|mut event| {
+                let source = (event.aircraft_id.as_str(), event.spoofed);
+                let Some(&(distance, altitude)) = geometry.get(&source) else {
+                    return Some(event);
+                };
+                if self.rng.next_f64() >= model.reception_probability(distance, altitude) {
+                    return None;
+                }
+                event.rssi = Some(model.rssi_dbfs(distance, altitude));
+                Some(event)
+            })
+            .collect()
+    }
 
-fn rand_range(min: i32, max: i32) -> i32 {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let nanos = SystemTime::now()
+    /// 按信道损伤参数丢弃消息并随机翻转比特
+    pub fn apply_impairment(&mut self, events: Vec<AdsbEvent>) -> Vec<AdsbEvent> {
+        let impairment = self.impairment;
+        if impairment == ChannelImpairment::default() {
+            return events;
+        }
+
+        let mut delivered = Vec::with_capacity(events.len());
+        for mut event in events {
+            if self.rng.next_f64() < impairment.dropout_rate {
+                continue;
+            }
+            if impairment.bit_error_rate > 0.0 {
+                if let Ok(mut frame) = u128::from_str_radix(&event.hex_message, 16) {
+                    let bits = event.hex_message.len() * 4;
+                    for bit in 0..bits {
+                        if self.rng.next_f64() < impairment.bit_error_rate {
+                            frame ^= 1u128 << bit;
+                        }
+                    }
+                    event.hex_message = format!("{:0width$X}", frame, width = bits / 4);
+                }
+            }
+            delivered.push(event);
+        }
+        delivered
+    }
+
+    /// 估算在当前信道损伤下能完成全局 CPR 解码的飞机比例
+    ///
+    /// 对每架飞机模拟 `samples` 帧奇偶交替的位置消息，只要某对奇偶帧都被正确接收
+    /// 且间隔不超过 10 s，即可解出全局位置。使用独立的随机序列，不影响模拟过程。
+    pub fn analyze_cpr_robustness(&self, samples: usize) -> f64 {
+        if self.aircrafts.is_empty() {
+            return 0.0;
+        }
+
+        let delivery = self.impairment.delivery_probability();
+        let every = self.cadence.every(MessageType::Position) as u64;
+        let mut rng = SimRng::new(self.seed ^ self.tick);
+        let recoverable = self
+            .aircrafts
+            .iter()
+            .filter(|_| {
+                // 最近一次收到的偶帧/奇帧时间 (s)
+                let mut last: [Option<u64>; 2] = [None, None];
+                (0..samples as u64).any(|k| {
+                    if rng.next_f64() >= delivery {
+                        return false;
+                    }
+                    let time = k * every;
+                    let parity = (k % 2) as usize;
+                    last[parity] = Some(time);
+                    last[1 - parity].is_some_and(|other| time - other <= CPR_PAIR_WINDOW_S)
+                })
+            })
+            .count();
+        recoverable as f64 / self.aircrafts.len() as f64
+    }
+}
+
+/// 沿飞行计划调整航向和高度，返回 (是否接管航向, 是否接管高度)
+fn steer_along_route(aircraft: &mut Aircraft, route: &mut ActiveRoute) -> (bool, bool) {
+    let count = route.plan.waypoints.len();
+    if count == 0 {
+        return (false, false);
+    }
+
+    // 到达当前航路点后切换到下一个
+    let current = route.plan.waypoints[route.next];
+    if geo::distance_nm(aircraft.lat, aircraft.lng, current.lat, current.lng) < WAYPOINT_ARRIVAL_NM {
+        if route.next + 1 < count {
+            route.next += 1;
+        } else {
+            match route.plan.on_complete {
+                RouteCompletion::Loop => route.next = 0,
+                RouteCompletion::Land => route.arrived = true,
+                // Hold：保持最后一个航路点为目标，转弯率限制使飞机绕其盘旋
+                RouteCompletion::Hold => {}
+            }
+        }
+    }
+
+    let mut target = route.plan.waypoints[route.next];
+    if route.arrived {
+        // 到达终点后在其上空盘旋下降至地面
+        target.altitude = Some(0.0);
+    }
+    let bearing = geo::initial_bearing(aircraft.lat, aircraft.lng, target.lat, target.lng);
+    let turn = geo::heading_difference(aircraft.heading, bearing)
+        .clamp(-MAX_TURN_RATE_DEG, MAX_TURN_RATE_DEG);
+    aircraft.heading = (aircraft.heading + turn + 360.0) % 360.0;
+
+    match target.altitude {
+        Some(altitude) => {
+            aircraft.altitude += (altitude - aircraft.altitude)
+                .clamp(-ROUTE_CLIMB_RATE_FPS, ROUTE_CLIMB_RATE_FPS);
+            (true, true)
+        }
+        None => (true, false),
+    }
+}
+
+/// 沿 3° 下滑道引导飞机飞向跑道入口，接地后沿跑道方向滑跑
+fn fly_approach(aircraft: &mut Aircraft, runway: &Runway) {
+    if aircraft.on_ground {
+        aircraft.altitude = 0.0;
+        aircraft.heading = runway.heading;
+        return;
+    }
+
+    // 沿跑道延长线到入口的距离，负值表示已越过入口
+    let reciprocal = (runway.heading + 180.0) % 360.0;
+    let distance = geo::distance_nm(runway.lat, runway.lng, aircraft.lat, aircraft.lng);
+    let bearing = geo::initial_bearing(runway.lat, runway.lng, aircraft.lat, aircraft.lng);
+    let offset = geo::heading_difference(reciprocal, bearing).to_radians();
+    let along = distance * offset.cos();
+    let cross = distance * offset.sin();
+
+    // 建立五边 (位于入口外侧或接地区内且已对准跑道) 之前保持高度，
+    // 之后低于下滑道时保持高度等待切入，高于下滑道时按最大下降率下降
+    let aligned = geo::heading_difference(aircraft.heading, runway.heading).abs();
+    let established = along > -TOUCHDOWN_ZONE_NM
+        && cross.abs() <= ESTABLISHED_CROSS_TRACK_NM
+        && aligned <= ESTABLISHED_HEADING_DEG;
+    let glideslope = along.max(0.0) * FLIGHT_CLIMB_GRADIENT;
+    let target = if established {
+        aircraft.altitude.min(glideslope)
+    } else {
+        aircraft.altitude
+    };
+    aircraft.altitude += (target - aircraft.altitude).max(-ROUTE_CLIMB_RATE_FPS);
+    if aircraft.altitude <= 0.0 {
+        aircraft.altitude = 0.0;
+        aircraft.on_ground = true;
+        aircraft.phase = Some(FlightPhase::Taxi);
+        aircraft.heading = runway.heading;
+        return;
+    }
+
+    // 瞄准延长线上前方一点逐步对准跑道；从跑道另一侧进入时先飞往最后进近定位点，
+    // 背台飞越入口后继续飞到最后进近定位点外再转向五边
+    let outbound = aligned > 90.0;
+    let aim_along = if along < 0.0 || (outbound && along < FINAL_APPROACH_FIX_NM) {
+        FINAL_APPROACH_FIX_NM
+    } else {
+        along - APPROACH_LEAD_NM
+    };
+    let (aim_lat, aim_lng) = geo::destination_point(runway.lat, runway.lng, reciprocal, aim_along);
+    let bearing = geo::initial_bearing(aircraft.lat, aircraft.lng, aim_lat, aim_lng);
+    let turn = geo::heading_difference(aircraft.heading, bearing)
+        .clamp(-MAX_TURN_RATE_DEG, MAX_TURN_RATE_DEG);
+    aircraft.heading = (aircraft.heading + turn + 360.0) % 360.0;
+}
+
+/// 地面移动速度编码 (7 bit，非线性量化)
+fn encode_ground_movement(speed_kts: f64) -> u64 {
+    // (区间下限, 步长, 起始编码)
+    const BANDS: [(f64, f64, u64); 7] = [
+        (0.125, 0.146, 2),
+        (1.0, 0.25, 9),
+        (2.0, 0.5, 13),
+        (15.0, 1.0, 39),
+        (70.0, 2.0, 94),
+        (100.0, 5.0, 109),
+        (175.0, 0.0, 124),
+    ];
+    if speed_kts < BANDS[0].0 {
+        return 1; // 静止
+    }
+    let &(low, step, base) = BANDS
+        .iter()
+        .rev()
+        .find(|(low, _, _)| speed_kts >= *low)
+        .unwrap();
+    if step == 0.0 {
+        return base;
+    }
+    // 取最接近的量化值，区间上端取整后恰为下一区间的起始编码
+    (base + quantize(speed_kts - low, step, 124)).min(124)
+}
+
+/// 按半圆规则取最接近的巡航高度层 (ft)
+///
+/// 航向 0-179° (向东) 使用奇数千英尺，180-359° (向西) 使用偶数千英尺。
+fn semicircular_level(altitude: f64, heading: f64) -> f64 {
+    let eastbound = heading < 180.0;
+    let nearest = (altitude / 1000.0).round() as i64;
+    let level = if (nearest % 2 != 0) == eastbound {
+        nearest
+    } else if altitude >= nearest as f64 * 1000.0 {
+        nearest + 1
+    } else {
+        nearest - 1
+    };
+    level.max(if eastbound { 1 } else { 2 }) as f64 * 1000.0
+}
+
+/// 按飞行阶段调整高度并在到达目标高度时切换阶段，返回是否接管高度
+fn advance_phase(aircraft: &mut Aircraft) -> bool {
+    let Some(phase) = aircraft.phase else {
+        return false;
+    };
+    // 巡航、进近、滑行阶段保持高度
+    if let (FlightPhase::Climb | FlightPhase::Descent, Some(target)) =
+        (phase, aircraft.target_altitude)
+    {
+        aircraft.altitude += (target - aircraft.altitude)
+            .clamp(-PHASE_VERTICAL_RATE_FPS, PHASE_VERTICAL_RATE_FPS);
+        if aircraft.altitude == target {
+            aircraft.phase = Some(FlightPhase::Cruise);
+            aircraft.target_altitude = None;
+        }
+    }
+    true
+}
+
+/// 读取 JSON 文件中的机队，逐架校验并拒绝重复的 ICAO 地址
+///
+/// 文件可以是飞机数组 (`Vec<Aircraft>`)，也可以是导出的 [`ScenarioFile`]，
+/// 后者的中心坐标只作为元数据，不影响加载。
+pub fn read_aircrafts_json(path: &str) -> Result<Vec<Aircraft>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let value: serde_json::Value =
+        serde_json::from_str(&text).map_err(|e| format!("Invalid scenario {}: {}", path, e))?;
+    let fleet = if value.is_array() {
+        serde_json::from_value(value)
+    } else {
+        serde_json::from_value(value).map(|scenario: ScenarioFile| scenario.aircrafts)
+    }
+    .map_err(|e| format!("Invalid scenario {}: {}", path, e))?;
+    let mut seen = HashSet::new();
+    for (index, aircraft) in fleet.iter().enumerate() {
+        validate_aircraft(aircraft).map_err(|e| format!("{} entry {}: {}", path, index, e))?;
+        if !seen.insert(aircraft.id.to_ascii_uppercase()) {
+            let id = &aircraft.id;
+            return Err(format!("{} entry {}: duplicate ICAO {}", path, index, id));
+        }
+    }
+    Ok(fleet)
+}
+
+/// 校验飞机数据是否合法
+pub fn validate_aircraft(aircraft: &Aircraft) -> Result<(), String> {
+    let id = &aircraft.id;
+    if id.len() != 6 || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid ICAO address '{}': expected 6 hex digits", id));
+    }
+    if !(-90.0..=90.0).contains(&aircraft.lat) {
+        return Err(format!("Aircraft {}: latitude {} out of range", id, aircraft.lat));
+    }
+    if !(-180.0..=180.0).contains(&aircraft.lng) {
+        return Err(format!("Aircraft {}: longitude {} out of range", id, aircraft.lng));
+    }
+    if !(0.0..360.0).contains(&aircraft.heading) {
+        return Err(format!("Aircraft {}: heading {} out of range", id, aircraft.heading));
+    }
+    if !(-1000.0..=60000.0).contains(&aircraft.altitude) {
+        return Err(format!("Aircraft {}: altitude {} out of range", id, aircraft.altitude));
+    }
+    if !(0.0..=1000.0).contains(&aircraft.speed) {
+        return Err(format!("Aircraft {}: speed {} out of range", id, aircraft.speed));
+    }
+    if aircraft.nic > 11 {
+        return Err(format!("Aircraft {}: NIC {} out of range", id, aircraft.nic));
+    }
+    if aircraft.squawk > 0o7777 {
+        return Err(format!("Aircraft {}: squawk {:o} is not a 4-digit octal code", id, aircraft.squawk));
+    }
+    if !(-MAX_VERTICAL_RATE_FPM..=MAX_VERTICAL_RATE_FPM).contains(&aircraft.vertical_rate) {
+        return Err(format!(
+            "Aircraft {}: vertical rate {} out of range",
+            id, aircraft.vertical_rate
+        ));
+    }
+    if aircraft.version > 2 {
+        return Err(format!("Aircraft {}: ADS-B version {} out of range", id, aircraft.version));
+    }
+    if aircraft.nacp > 11 {
+        return Err(format!("Aircraft {}: NACp {} out of range", id, aircraft.nacp));
+    }
+    if aircraft.nacv > 4 {
+        return Err(format!("Aircraft {}: NACv {} out of range", id, aircraft.nacv));
+    }
+    if aircraft.sil > 3 {
+        return Err(format!("Aircraft {}: SIL {} out of range", id, aircraft.sil));
+    }
+    Ok(())
+}
+
+/// CPR 经度分区数 NL(lat)
+pub(crate) fn cpr_nl(lat: f64) -> f64 {
+    let lat = lat.abs();
+    if lat < 1e-9 {
+        return 59.0;
+    }
+    if lat >= 87.0 {
+        return if lat > 87.0 { 1.0 } else { 2.0 };
+    }
+    let a = 1.0 - (PI / (2.0 * CPR_NZ)).cos();
+    let b = (PI / 180.0 * lat).cos().powi(2);
+    (2.0 * PI / (1.0 - a / b).acos()).floor()
+}
+
+/// 按指定区间跨度做 CPR 编码（空中 360°，地面 90°），返回 17 位 (YZ, XZ)
+fn cpr_encode_span(lat: f64, lng: f64, odd: bool, span: f64) -> (u32, u32) {
+    let i = odd as u8 as f64;
+    let modulo = |x: f64, y: f64| x - y * (x / y).floor();
+
+    let d_lat = span / (4.0 * CPR_NZ - i);
+    let yz = (CPR_SCALE * modulo(lat, d_lat) / d_lat + 0.5).floor();
+    let r_lat = d_lat * (yz / CPR_SCALE + (lat / d_lat).floor());
+
+    let d_lng = span / (cpr_nl(r_lat) - i).max(1.0);
+    let xz = (CPR_SCALE * modulo(lng, d_lng) / d_lng + 0.5).floor();
+
+    (yz as u32 & 0x1FFFF, xz as u32 & 0x1FFFF)
+}
+
+/// 空中位置 CPR 编码 (NZ = 15)，返回 17 位 (YZ, XZ)
+pub fn cpr_encode(lat: f64, lng: f64, odd: bool) -> (u32, u32) {
+    cpr_encode_span(lat, lng, odd, 360.0)
+}
+
+/// 地面位置 CPR 编码
+pub fn cpr_encode_surface(lat: f64, lng: f64, odd: bool) -> (u32, u32) {
+    cpr_encode_span(lat, lng, odd, 90.0)
+}
+
+/// 组装 ADS-B 消息
+fn assemble_message(df: u8, ca: u8, icao: u32, payload: u64) -> String {
+    // 112 bits total: DF(5) + CA(3) + ICAO(24) + Payload(56) + PI(24)
+    let mut msg: u128 = 0;
+    msg |= (df as u128) << 107;
+    msg |= (ca as u128) << 104;
+    msg |= (icao as u128) << 80;
+    msg |= (payload as u128) << 24;
+    // DF17/18 的 PI 字段为前 88 位的 CRC-24 余数（不与地址异或），解码端重新计算得到 0
+    msg |= crc24(&(msg >> 24).to_be_bytes()[5..]) as u128;
+
+    format!("{:028X}", msg)
+}
+
+/// 按分辨率量化为非负整数字段：四舍六入五成双，结果限制在 0..=max，非有限值返回 0
+pub(crate) fn quantize(value: f64, resolution: f64, max: u64) -> u64 {
+    let steps = (value / resolution).round_ties_even();
+    if steps.is_nan() {
+        return 0;
+    }
+    steps.clamp(0.0, max as f64) as u64
+}
+
+/// 将角度 (度) 量化为 `bits` 位的环形字段，360° 回绕为 0
+pub(crate) fn quantize_angle(degrees: f64, bits: u32) -> u64 {
+    let full = 1u64 << bits;
+    quantize(degrees.rem_euclid(360.0), 360.0 / full as f64, full) % full
+}
+
+/// Q 位 25 ft 编码可表示的最高高度 (ft)
+const Q_BIT_MAX_ALTITUDE_FT: f64 = 50175.0;
+/// Gillham 100 ft 编码可表示的最高高度 (ft)
+const GILLHAM_MAX_ALTITUDE_FT: f64 = 126700.0;
+
+/// 编码空中位置消息的 12 位高度字段 (ft)
+///
+/// 50175 ft 以下置 Q 位，按 25 ft 分辨率编码 N = (高度 + 1000) / 25，Q 位插在
+/// N 的低 4 位之前；更高的高度按 Gillham 格雷码以 100 ft 分辨率编码。
+/// 低于 -1000 ft 按 -1000 ft、超出 126700 ft 按上限编码，非有限值返回 0 (高度不可用)。
+pub fn encode_altitude_ft(alt: f64) -> u16 {
+    if !alt.is_finite() {
+        return 0;
+    }
+    if alt <= Q_BIT_MAX_ALTITUDE_FT {
+        let n = quantize(alt + 1000.0, 25.0, 0x7FF) as u16;
+        return ((n & 0x7F0) << 1) | 0x010 | (n & 0x00F);
+    }
+
+    // Gillham：高度 (100 ft) + 13 = 500 ft 档 × 5 + 100 ft 档 (1-5)
+    let hundreds = quantize(alt.min(GILLHAM_MAX_ALTITUDE_FT), 100.0, 1267) as u16 + 12;
+    let five_hundreds = hundreds / 5;
+    let mut one_hundreds = hundreds % 5 + 1;
+    if five_hundreds & 1 == 1 {
+        one_hundreds = 6 - one_hundreds;
+    }
+    if one_hundreds == 5 {
+        one_hundreds = 7; // 格雷码中 5 与 7 互换
+    }
+    let gray_500 = five_hundreds ^ (five_hundreds >> 1);
+    let gray_100 = one_hundreds ^ (one_hundreds >> 1);
+
+    // AC 位序：C1 A1 C2 A2 C4 A4 M B1 Q B2 D2 B4 D4，500 ft 档依次为 D2 D4 A1 A2 A4 B1 B2 B4
+    let bit = |value: u16, index: u16, mask: u16| if value >> index & 1 == 1 { mask } else { 0 };
+    let ac13 = bit(gray_100, 2, 0x1000)
+        | bit(gray_100, 1, 0x0400)
+        | bit(gray_100, 0, 0x0100)
+        | bit(gray_500, 7, 0x0004)
+        | bit(gray_500, 6, 0x0001)
+        | bit(gray_500, 5, 0x0800)
+        | bit(gray_500, 4, 0x0200)
+        | bit(gray_500, 3, 0x0080)
+        | bit(gray_500, 2, 0x0020)
+        | bit(gray_500, 1, 0x0008)
+        | bit(gray_500, 0, 0x0002);
+    // 去掉 M 位得到 12 位字段
+    ((ac13 & 0x1F80) >> 1) | (ac13 & 0x003F)
+}
+
+/// 编码 DF0/4/20 的 13 位 AC 字段：在 12 位高度字段中插入 M 位 (恒为 0)
+fn encode_ac13(altitude_ft: f64) -> u16 {
+    let ac12 = encode_altitude_ft(altitude_ft);
+    ((ac12 & 0xFC0) << 1) | (ac12 & 0x03F)
+}
+
+/// 编码 DF5/21 的 13 位识别码字段，`squawk` 为八进制四位编码
+fn encode_id13(squawk: u16) -> u16 {
+    let digit = |shift: u16| (squawk >> shift) & 0x7;
+    let (a, b, c, d) = (digit(9), digit(6), digit(3), digit(0));
+    // 位序：C1 A1 C2 A2 C4 A4 X B1 D1 B2 D2 B4 D4
+    let place = |value: u16, masks: [u16; 3]| {
+        (0..3)
+            .filter(|&i| value & (1 << i) != 0)
+            .fold(0, |acc, i| acc | masks[i])
+    };
+    place(a, [0x0800, 0x0200, 0x0080])
+        | place(b, [0x0020, 0x0008, 0x0002])
+        | place(c, [0x1000, 0x0400, 0x0100])
+        | place(d, [0x0010, 0x0004, 0x0001])
+}
+
+/// 将航班号编码为 8 个 6 位字符 (48 位)，不足补空格，字符集外的字符记为空格
+fn encode_callsign(callsign: &str) -> u64 {
+    let mut chars = callsign.trim().bytes().map(|c| c.to_ascii_uppercase());
+    (0..8).fold(0u64, |acc, _| {
+        let code = chars
+            .next()
+            .and_then(|c| {
+                crate::decoder::CALLSIGN_CHARSET
+                    .iter()
+                    .position(|&x| x == c && c != b'#')
+            })
+            .unwrap_or(32);
+        (acc << 6) | code as u64
+    })
+}
+
+/// 由调用方提供的比特构造任意 DF 的消息，校验字段按实际数据计算（用于解码器模糊测试）
+///
+/// DF 16-31 为 112 位长消息，其余为 56 位短消息。`payload_bits` 为 DF 之后、校验字段之前
+/// 的全部比特（短消息 27 位，长消息 83 位）。`ap_address` 为 Some 时校验字段为 CRC 与
+/// 该地址异或得到的 AP，否则为纯 CRC。
+pub fn assemble_raw(df: u8, payload_bits: u128, ap_address: Option<u32>) -> Result<String, String> {
+    if df > 31 {
+        return Err(format!("DF {} does not fit in 5 bits", df));
+    }
+    let total_bits = if df >= 16 { 112 } else { 56 };
+    let payload_width = total_bits - 5 - 24;
+    if payload_bits >> payload_width != 0 {
+        return Err(format!(
+            "Payload does not fit in {} bits for DF{}",
+            payload_width, df
+        ));
+    }
+    if let Some(address) = ap_address {
+        if address > 0xFF_FFFF {
+            return Err(format!("Address {:X} does not fit in 24 bits", address));
+        }
+    }
+
+    let data = ((df as u128) << payload_width) | payload_bits;
+    let data_bytes = (total_bits - 24) / 8;
+    let crc = crc24(&data.to_be_bytes()[16 - data_bytes..]);
+    let parity = crc ^ ap_address.unwrap_or(0);
+    Ok(format!(
+        "{:0width$X}",
+        (data << 24) | parity as u128,
+        width = total_bits / 4
+    ))
+}
+
+/// Mode S CRC-24 生成多项式 (x^24 + x^23 + ... + x^3 + 1)
+pub const CRC24_POLY: u32 = 0x1FF_F409;
+
+/// 计算 Mode S CRC-24 余数
+///
+/// 对数据部分 (长消息前 11 字节) 计算得到校验码；
+/// 对包含校验字段的完整消息计算，余数为 0 表示校验通过。
+pub fn crc24(bits: &[u8]) -> u32 {
+    let mut crc: u32 = 0;
+    for &byte in bits {
+        crc ^= (byte as u32) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x100_0000 != 0 {
+                crc ^= CRC24_POLY;
+            }
+        }
+    }
+    crc & 0xFF_FFFF
+}
+
+/// 可设定种子的伪随机数生成器 (xorshift64*，不依赖外部库)
+#[derive(Debug, Clone)]
+pub struct SimRng {
+    state: u64,
+}
+
+impl SimRng {
+    pub fn new(seed: u64) -> Self {
+        // 通过 SplitMix64 打散种子，避免 0 状态和相近种子产生相近序列
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        SimRng {
+            state: if z == 0 { 0x2545_F491_4F6C_DD1D } else { z },
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// [0, 1) 区间均匀分布
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// [min, max] 闭区间整数
+    pub fn range_i32(&mut self, min: i32, max: i32) -> i32 {
+        let span = (max as i64 - min as i64 + 1) as u64;
+        (min as i64 + (self.next_u64() % span) as i64) as i32
+    }
+}
+
+/// 当前墙钟时间 (Unix ms)，系统时钟早于 1970 年时返回 0
+///
+/// 批次时间戳、录制与 SBS 输出都使用该时间，彼此可直接对齐。
+pub fn unix_time_ms() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// 基于系统时间生成默认种子
+fn time_seed() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
         .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .subsec_nanos() as i32;
-    min + (nanos.abs() % (max - min + 1))
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
 }
 
 #[cfg(test)]
@@ -229,11 +3061,1848 @@ mod tests {
     #[test]
     fn test_simulator() {
         let mut sim = AdsbSimulator::new(22.5431, 114.0579);
-        sim.generate_mock_aircrafts(5);
+        sim.generate_mock_aircrafts(5).unwrap();
         
         assert_eq!(sim.get_aircrafts().len(), 5);
         
         let messages = sim.generate_all_messages();
-        assert_eq!(messages.len(), 10); // 5 飞机 * 2 消息类型
+        let count = |message_type: MessageType| {
+            messages.iter().filter(|m| m.message_type == message_type).count()
+        };
+        assert_eq!(count(MessageType::Position) + count(MessageType::Velocity), 10); // 5 飞机 * 位置、速度
+        assert_eq!(count(MessageType::AllCall), 5); // 每秒一次全呼应答
+        assert!(count(MessageType::Identification) <= 5); // 识别消息按周期错开发送
+    }
+
+    #[test]
+    fn test_unique_icao_for_large_fleet() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 273);
+        sim.generate_mock_aircrafts(5000).unwrap();
+        let ids: HashSet<&str> = sim.get_aircrafts().iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids.len(), 5000);
+        for id in ids {
+            assert_eq!(id.len(), 6);
+            let address = u32::from_str_radix(id, 16).unwrap();
+            assert!((ICAO_ADDRESS_MIN..=ICAO_ADDRESS_MAX).contains(&address));
+        }
+
+        // 超出 24 位地址空间时报错，原有机队保持不变
+        assert!(sim.generate_mock_aircrafts(1 << 24).is_err());
+        assert_eq!(sim.get_aircrafts().len(), 5000);
+    }
+
+    #[test]
+    fn test_geofence_replaces_departed_aircraft() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 274);
+        sim.generate_mock_aircrafts(6).unwrap();
+        assert!(sim.set_max_radius_deg(Some(0.0)).is_err());
+        sim.set_max_radius_deg(Some(0.8)).unwrap();
+
+        let escaped = sim.get_aircrafts()[2].id.clone();
+        sim.aircrafts[2].lat = sim.center_lat + 1.0;
+        sim.update_positions();
+
+        let fleet = sim.get_aircrafts();
+        assert_eq!(fleet.len(), 6);
+        assert!(fleet.iter().all(|a| a.id != escaped));
+        let ids: HashSet<&str> = fleet.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids.len(), 6);
+
+        // 新飞机出现在半径边缘并朝中心飞行
+        let spawned = fleet.last().unwrap();
+        let (d_lat, d_lng) = (sim.center_lat - spawned.lat, sim.center_lng - spawned.lng);
+        assert!(d_lat.hypot(d_lng) <= 0.8);
+        let inward = d_lng.atan2(d_lat).to_degrees().rem_euclid(360.0);
+        assert!(geo::heading_difference(spawned.heading, inward).abs() <= 31.0);
+
+        // 相同种子得到相同的替换飞机
+        let mut again = AdsbSimulator::with_seed(22.5431, 114.0579, 274);
+        again.generate_mock_aircrafts(6).unwrap();
+        again.set_max_radius_deg(Some(0.8)).unwrap();
+        again.aircrafts[2].lat = again.center_lat + 1.0;
+        again.update_positions();
+        assert_eq!(again.get_aircrafts().last().unwrap().id, spawned.id);
+
+        // 随机数下一个抽到的地址已被等待放行的飞机占用时重新抽取
+        let mut rng = again.rng.clone();
+        let available = (ICAO_ADDRESS_MAX - ICAO_ADDRESS_MIN + 1) as u64;
+        let next = ICAO_ADDRESS_MIN + (rng.next_u64() % available) as u32;
+        let mut pending = again.aircrafts[0].clone();
+        pending.id = format!("{:06X}", next);
+        again.departures.push(PendingDeparture {
+            release_tick: u64::MAX,
+            aircraft: pending.clone(),
+            plan: None,
+        });
+        let mut used = again.icao_in_use();
+        assert!(used.contains(&next));
+        assert_ne!(again.allocate_icao(&mut used), pending.id);
+    }
+
+    #[test]
+    fn test_set_seed_reproduces_scene() {
+        let run = |sim: &mut AdsbSimulator| {
+            sim.generate_mock_aircrafts(6).unwrap();
+            for _ in 0..30 {
+                sim.update_positions();
+            }
+            sim.get_aircrafts().clone()
+        };
+
+        let mut first = AdsbSimulator::with_seed(22.5431, 114.0579, 42);
+        let expected = run(&mut first);
+
+        let mut second = AdsbSimulator::new(22.5431, 114.0579);
+        second.set_seed(42);
+        assert_eq!(second.get_seed(), 42);
+        assert_eq!(run(&mut second), expected);
+
+        let mut other = AdsbSimulator::with_seed(22.5431, 114.0579, 7);
+        assert_ne!(run(&mut other), expected);
+    }
+
+    #[test]
+    fn test_same_seed_bit_identical_over_100_ticks() {
+        let run = |seed: u64| {
+            let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, seed);
+            sim.generate_mock_aircrafts(8).unwrap();
+            let mut messages = Vec::new();
+            for _ in 0..100 {
+                sim.update_positions();
+                messages.extend(
+                    sim.generate_all_messages()
+                        .into_iter()
+                        .map(|m| m.hex_message),
+                );
+            }
+            let states: Vec<[u64; 5]> = sim
+                .get_aircrafts()
+                .iter()
+                .map(|a| [a.lat, a.lng, a.altitude, a.speed, a.heading].map(f64::to_bits))
+                .collect();
+            (states, messages)
+        };
+
+        assert_eq!(run(2024), run(2024));
+        assert_ne!(run(2024).0, run(2025).0);
+    }
+
+    #[test]
+    fn test_speed_altitude_coupling() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 9);
+        sim.set_speed_coupling(Some(SpeedAltitudeCoupling::default()));
+        sim.generate_mock_aircrafts(200).unwrap();
+
+        let average = |sim: &AdsbSimulator, low: bool| {
+            let speeds: Vec<f64> = sim
+                .get_aircrafts()
+                .iter()
+                .filter(|a| (a.altitude < 10000.0) == low)
+                .map(|a| a.speed)
+                .collect();
+            assert!(!speeds.is_empty());
+            speeds.iter().sum::<f64>() / speeds.len() as f64
+        };
+        assert!(average(&sim, false) > average(&sim, true) + 20.0);
+
+        // 机动过程中速度保持与高度的关联
+        for _ in 0..50 {
+            sim.update_positions();
+        }
+        assert!(average(&sim, false) > average(&sim, true));
+    }
+
+    #[test]
+    fn test_phase_speeds_beat_coupling_for_created_flights() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 10);
+        sim.set_speed_coupling(Some(SpeedAltitudeCoupling::default()));
+        sim.generate_mock_aircrafts(2).unwrap();
+        for aircraft in sim.aircrafts.iter_mut() {
+            (aircraft.altitude, aircraft.speed) = (6000.0, 250.0);
+            (aircraft.phase, aircraft.target_altitude) = (Some(FlightPhase::Cruise), None);
+        }
+        // 跑道远在 60 nm 外，观察期间不会接地
+        let (lat, lng) = geo::destination_point(22.5431, 114.0579, 180.0, 60.0);
+        let landing = sim.aircrafts[1].id.clone();
+        let runway = Runway {
+            lat,
+            lng,
+            heading: 0.0,
+        };
+        sim.land_aircraft(&landing, runway).unwrap();
+
+        for _ in 0..120 {
+            sim.update_positions();
+        }
+        // 着陆航班减速到进近速度，随机飞机按高度保持耦合速度
+        let approach_max = PhaseSpeedProfile::default().approach_max;
+        assert!(sim.aircrafts[1].speed <= approach_max + 1e-9);
+        let coupled = SpeedAltitudeCoupling::default().target_speed(sim.aircrafts[0].altitude);
+        assert!((sim.aircrafts[0].speed - coupled).abs() < 5.0);
+    }
+
+    #[test]
+    fn test_spoofer_conflicting_positions() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 5);
+        sim.generate_mock_aircrafts(3).unwrap();
+        let target = sim.get_aircrafts()[0].id.clone();
+
+        assert!(sim.add_spoofer("000000", (0.1, 0.1)).is_err());
+        sim.add_spoofer(&target, (0.2, -0.1)).unwrap();
+
+        let ghosts = sim.get_spoofed_aircrafts();
+        assert_eq!(ghosts.len(), 1);
+        assert_eq!(ghosts[0].id, target);
+        assert!((ghosts[0].lat - sim.get_aircrafts()[0].lat - 0.2).abs() < 1e-9);
+
+        let positions: Vec<_> = sim
+            .generate_all_messages()
+            .into_iter()
+            .filter(|m| m.aircraft_id == target && m.message_type == MessageType::Position)
+            .collect();
+        assert_eq!(positions.len(), 2);
+        assert_ne!(positions[0].hex_message, positions[1].hex_message);
+        // 报头 (DF/CA/ICAO) 相同，仅位置字段不同
+        assert_eq!(positions[0].hex_message[..8], positions[1].hex_message[..8]);
+
+        // 欺骗源存在时，真实飞机与欺骗源各自交替发送偶帧、奇帧
+        let frames = |sim: &mut AdsbSimulator| -> Vec<(bool, u128)> {
+            sim.generate_all_messages()
+                .into_iter()
+                .filter(|m| m.aircraft_id == target && m.message_type == MessageType::Position)
+                .map(|m| (m.spoofed, u128::from_str_radix(&m.hex_message, 16).unwrap()))
+                .collect()
+        };
+        for tick in 1..6 {
+            sim.update_positions();
+            let odd = tick % 2 == 1;
+            let sent = frames(&mut sim);
+            assert_eq!(sent.len(), 2);
+            for (_, frame) in sent {
+                // ME 第 22 位 (报文第 54 位) 为 CPR 奇偶标志
+                assert_eq!(frame >> 58 & 1 == 1, odd);
+            }
+        }
+
+        sim.clear_spoofers();
+        assert!(sim.get_spoofed_aircrafts().is_empty());
+
+        // 越过日界线的偏移回绕到合法经度
+        sim.aircrafts[0].lng = 179.95;
+        sim.add_spoofer(&target, (0.0, 0.1)).unwrap();
+        let ghost = &sim.get_spoofed_aircrafts()[0];
+        assert!((ghost.lng + 179.95).abs() < 1e-9);
+        assert!(validate_aircraft(ghost).is_ok());
+    }
+
+    #[test]
+    fn test_independent_position_velocity_cadence() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 5);
+        sim.set_cadence(MessageCadence {
+            position_every: 1,
+            velocity_every: 2,
+            ..MessageCadence::default()
+        });
+        sim.generate_mock_aircrafts(3).unwrap();
+
+        let mut counts: HashMap<(String, MessageType), usize> = HashMap::new();
+        for _ in 0..4 {
+            sim.update_positions();
+            for message in sim.generate_all_messages() {
+                let key = (message.aircraft_id, message.message_type);
+                *counts.entry(key).or_default() += 1;
+            }
+        }
+        // 4 个 tick 内每架飞机发送 4 条位置消息、2 条速度消息
+        for aircraft in sim.get_aircrafts() {
+            let count = |t| counts.get(&(aircraft.id.clone(), t)).copied().unwrap_or(0);
+            assert_eq!(count(MessageType::Position), 4);
+            assert_eq!(count(MessageType::Velocity), 2);
+        }
+    }
+
+    #[test]
+    fn test_schedule_matches_cadence() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 4);
+        sim.set_tick_interval_ms(500);
+        sim.set_cadence(MessageCadence {
+            position_every: 1,
+            velocity_every: 3,
+            identification_every: 5,
+            all_call_every: 2,
+            operational_status_every: 4,
+            status_every: 5,
+        });
+        sim.generate_mock_aircrafts(4).unwrap();
+        let icao = sim.get_aircrafts()[2].id.clone();
+
+        assert!(sim.get_schedule("FFFFFF").is_none());
+        let schedule = sim.get_schedule(&icao).unwrap();
+        assert_eq!(schedule.len(), 6);
+        assert_eq!(schedule[0].message_type, MessageType::Position);
+        assert_eq!(schedule[0].interval_ms, 500);
+        assert_eq!(schedule[1].message_type, MessageType::Velocity);
+        assert_eq!(schedule[1].interval_ms, 1500);
+        assert_eq!(schedule[2].message_type, MessageType::Identification);
+        assert_eq!(schedule[2].interval_ms, 2500);
+        assert_eq!(schedule[3].message_type, MessageType::AllCall);
+        assert_eq!(schedule[3].interval_ms, 1000);
+        assert_eq!(schedule[4].message_type, MessageType::OperationalStatus);
+        assert_eq!(schedule[4].interval_ms, 2000);
+        assert_eq!(schedule[5].message_type, MessageType::Status);
+        assert_eq!(schedule[5].interval_ms, 2500);
+
+        // 预测的下次发送时刻与实际发送一致
+        let velocity_next_tick = schedule[1].next_ms / 500;
+        let mut velocity_ticks = Vec::new();
+        for tick in 1..=6 {
+            sim.update_positions();
+            let sent = sim
+                .generate_all_messages()
+                .iter()
+                .any(|m| m.aircraft_id == icao && m.message_type == MessageType::Velocity);
+            if sent {
+                velocity_ticks.push(tick);
+            }
+        }
+        assert_eq!(velocity_ticks, vec![velocity_next_tick, velocity_next_tick + 3]);
+    }
+
+    #[test]
+    fn test_create_flight_great_circle() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 8);
+        sim.generate_mock_aircrafts(2).unwrap();
+
+        // 深圳 -> 上海
+        let (from, to) = ((22.639, 113.811), (31.144, 121.808));
+        let flight = sim.create_flight("cz3551", from, to, 35000.0, 450.0).unwrap();
+        assert_eq!(flight.callsign, "CZ3551");
+        assert_eq!(sim.get_aircrafts().len(), 3);
+
+        let plan = sim.get_flight_plan(&flight.id).unwrap();
+        let first = plan.waypoints.first().unwrap();
+        let last = plan.waypoints.last().unwrap();
+        assert!(geo::distance_nm(first.lat, first.lng, from.0, from.1) < 1.0);
+        assert!(geo::distance_nm(last.lat, last.lng, to.0, to.1) < 1.0);
+
+        // 爬升-巡航-下降剖面
+        let altitudes: Vec<f64> = plan.waypoints.iter().map(|w| w.altitude.unwrap()).collect();
+        assert_eq!(altitudes.iter().cloned().fold(0.0, f64::max), 35000.0);
+        assert!(altitudes[0] < 2000.0 && altitudes[altitudes.len() - 1] < 2000.0);
+        assert!(altitudes[1] > altitudes[0]);
+
+        // 起飞后航向指向目的地并持续爬升
+        for _ in 0..120 {
+            sim.update_positions();
+        }
+        let flown = sim.get_aircrafts().iter().find(|a| a.id == flight.id).unwrap();
+        assert!(flown.altitude > 3000.0);
+        let bearing = geo::initial_bearing(flown.lat, flown.lng, to.0, to.1);
+        assert!(geo::heading_difference(flown.heading, bearing).abs() < 10.0);
+
+        assert!(sim.create_flight(" ", from, to, 35000.0, 450.0).is_err());
+    }
+
+    #[test]
+    fn test_holding_stack_separation() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 34);
+        sim.generate_mock_aircrafts(0).unwrap();
+
+        let fix = (22.60, 114.10);
+        let stack = sim.create_holding_stack(fix, 4, 8000.0, 1000.0).unwrap();
+        assert_eq!(stack.len(), 4);
+        assert_eq!(sim.get_aircrafts().len(), 4);
+        for pair in stack.windows(2) {
+            assert_eq!(pair[1].altitude - pair[0].altitude, 1000.0);
+        }
+
+        // 盘旋过程中各层保持高度并停留在定位点附近
+        for _ in 0..300 {
+            sim.update_positions();
+        }
+        let mut altitudes: Vec<f64> = sim.get_aircrafts().iter().map(|a| a.altitude).collect();
+        altitudes.sort_by(f64::total_cmp);
+        for pair in altitudes.windows(2) {
+            assert!((pair[1] - pair[0] - 1000.0).abs() < 1.0);
+        }
+        for aircraft in sim.get_aircrafts() {
+            assert!(geo::distance_nm(aircraft.lat, aircraft.lng, fix.0, fix.1) < 5.0);
+        }
+
+        assert!(sim.create_holding_stack(fix, 0, 8000.0, 1000.0).is_err());
+        assert!(sim.create_holding_stack(fix, 3, 59000.0, 1000.0).is_err());
+        // 定位点无效时整个机群都不加入
+        let invalid = sim.create_holding_stack((95.0, 114.10), 3, 8000.0, 1000.0);
+        assert!(invalid.is_err());
+        assert_eq!(sim.get_aircrafts().len(), 4);
+
+        // 第二个等待机群的呼号不与现有机队重复
+        let second = sim.create_holding_stack((22.70, 114.20), 2, 8000.0, 1000.0);
+        let second = second.unwrap();
+        let callsigns: Vec<&str> = second.iter().map(|a| a.callsign.as_str()).collect();
+        assert_eq!(callsigns, ["HOLD05", "HOLD06"]);
+    }
+
+    #[test]
+    fn test_position_only_profile() {
+        let mut sim = AdsbSimulator::new(22.5431, 114.0579);
+        sim.generate_mock_aircrafts(3).unwrap();
+        let icao = sim.get_aircrafts()[1].id.clone();
+
+        assert!(sim.set_capability_profile(&icao, CapabilityProfile::PositionOnly));
+        assert!(!sim.set_capability_profile("FFFFFF", CapabilityProfile::PositionOnly));
+
+        for _ in 0..5 {
+            sim.update_positions();
+            let messages = sim.generate_all_messages();
+            let own: Vec<_> = messages.iter().filter(|m| m.aircraft_id == icao).collect();
+            assert!(!own.is_empty());
+            assert!(own.iter().all(|m| m.message_type == MessageType::Position));
+            // 其他飞机仍发送完整消息集
+            assert!(messages
+                .iter()
+                .any(|m| m.aircraft_id != icao && m.message_type == MessageType::Velocity));
+        }
+    }
+
+    #[test]
+    fn test_mock_climb_levels_off_at_target() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 270);
+        sim.generate_mock_aircrafts(8).unwrap();
+        let phases: Vec<FlightPhase> = sim.get_aircrafts().iter().filter_map(|a| a.phase).collect();
+        assert_eq!(phases.len(), 8);
+        for phase in [
+            FlightPhase::Climb,
+            FlightPhase::Cruise,
+            FlightPhase::Descent,
+        ] {
+            assert!(phases.contains(&phase), "no {:?} aircraft", phase);
+        }
+
+        let climbing = sim
+            .get_aircrafts()
+            .iter()
+            .find(|a| a.phase == Some(FlightPhase::Climb))
+            .unwrap()
+            .clone();
+        let target = climbing.target_altitude.unwrap();
+        assert!(target > climbing.altitude);
+
+        let mut previous = climbing.altitude;
+        for _ in 0..600 {
+            sim.update_positions();
+            let aircraft = sim.get_aircrafts().iter().find(|a| a.id == climbing.id).unwrap();
+            // 高度单调上升且垂直速率由阶段决定，不超过目标高度
+            assert!(aircraft.altitude >= previous && aircraft.altitude <= target);
+            assert!(aircraft.vertical_rate <= PHASE_VERTICAL_RATE_FPS * 60.0 + 1e-6);
+            previous = aircraft.altitude;
+        }
+
+        let aircraft = sim.get_aircrafts().iter().find(|a| a.id == climbing.id).unwrap();
+        assert_eq!(aircraft.phase, Some(FlightPhase::Cruise));
+        assert_eq!(aircraft.altitude, target);
+        assert_eq!(aircraft.vertical_rate, 0.0);
+    }
+
+    #[test]
+    fn test_descent_slows_to_speed_limit() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 9);
+        sim.generate_mock_aircrafts(1).unwrap();
+        {
+            let aircraft = &mut sim.aircrafts[0];
+            aircraft.altitude = 12000.0;
+            aircraft.speed = 480.0;
+            aircraft.phase = Some(FlightPhase::Descent);
+            aircraft.target_altitude = Some(4000.0);
+        }
+
+        let profile = PhaseSpeedProfile::default();
+        let mut previous = sim.get_aircrafts()[0].speed;
+        for _ in 0..400 {
+            sim.update_positions();
+            let aircraft = &sim.get_aircrafts()[0];
+            if aircraft.altitude < profile.speed_limit_altitude {
+                // 穿越 10000 ft 后持续减速，且每秒减速不超过加速度上限
+                assert!(aircraft.speed <= previous);
+                assert!(previous - aircraft.speed <= profile.acceleration + 1e-9);
+            }
+            previous = aircraft.speed;
+        }
+
+        let aircraft = &sim.get_aircrafts()[0];
+        assert_eq!(aircraft.altitude, 4000.0);
+        assert_eq!(aircraft.phase, Some(FlightPhase::Cruise));
+        assert!(aircraft.speed <= profile.speed_limit);
+        assert!(aircraft.speed >= profile.approach_max);
+    }
+
+    #[test]
+    fn test_land_aircraft_glideslope() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 37);
+        sim.generate_mock_aircrafts(1).unwrap();
+        let runway = Runway {
+            lat: 22.31,
+            lng: 113.92,
+            heading: 70.0,
+        };
+        // 从跑道延长线一侧 12 nm、偏离中心线约 1 nm 处以切入航向进近
+        let (lat, lng) = geo::destination_point(runway.lat, runway.lng, 245.0, 12.0);
+        let id = sim.aircrafts[0].id.clone();
+        sim.aircrafts[0] = Aircraft {
+            lat,
+            lng,
+            altitude: 3500.0,
+            speed: 220.0,
+            heading: 100.0,
+            phase: None,
+            ..sim.aircrafts[0].clone()
+        };
+        sim.land_aircraft(&id, runway).unwrap();
+
+        let mut angles = Vec::new();
+        let mut touchdown = None;
+        for _ in 0..3000 {
+            sim.update_positions();
+            let a = &sim.get_aircrafts()[0];
+            if a.on_ground {
+                touchdown = Some((a.lat, a.lng, a.speed));
+                break;
+            }
+            let distance = geo::distance_nm(runway.lat, runway.lng, a.lat, a.lng);
+            if (2.0..6.0).contains(&distance) {
+                angles.push((a.altitude / (distance * 6076.0)).atan().to_degrees());
+            }
+        }
+
+        // 最后进近段沿约 3° 下滑道下降，并在入口附近以进近速度接地
+        let (lat, lng, speed) = touchdown.expect("aircraft never touched down");
+        assert!(geo::distance_nm(runway.lat, runway.lng, lat, lng) < 0.5);
+        assert!(speed <= 180.0);
+        assert!(!angles.is_empty());
+        assert!(angles.iter().all(|angle| (angle - 3.0).abs() < 0.3));
+
+        // 接地后沿跑道方向滑跑并发送地面位置消息
+        sim.update_positions();
+        let a = &sim.get_aircrafts()[0];
+        assert_eq!((a.altitude, a.heading, a.phase), (0.0, 70.0, Some(FlightPhase::Taxi)));
+        let types: Vec<MessageType> = sim
+            .generate_all_messages()
+            .iter()
+            .map(|m| m.message_type)
+            .collect();
+        assert!(types.contains(&MessageType::SurfacePosition));
+        assert!(!types.contains(&MessageType::Position) && !types.contains(&MessageType::Velocity));
+        assert!(sim.land_aircraft(&id, runway).is_err());
+    }
+
+    #[test]
+    fn test_land_aircraft_from_far_side_holds_altitude() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 37);
+        sim.generate_mock_aircrafts(1).unwrap();
+        let runway = Runway {
+            lat: 22.31,
+            lng: 113.92,
+            heading: 70.0,
+        };
+        // 从跑道另一侧 (越过入口 5 nm) 顺跑道方向进入
+        let (lat, lng) = geo::destination_point(runway.lat, runway.lng, 70.0, 5.0);
+        let id = sim.aircrafts[0].id.clone();
+        sim.aircrafts[0] = Aircraft {
+            lat,
+            lng,
+            altitude: 3000.0,
+            speed: 220.0,
+            heading: 70.0,
+            phase: None,
+            ..sim.aircrafts[0].clone()
+        };
+        sim.land_aircraft(&id, runway).unwrap();
+
+        let (fix_lat, fix_lng) = geo::destination_point(runway.lat, runway.lng, 250.0, 8.0);
+        let mut at_fix = None;
+        let mut touchdown = None;
+        for _ in 0..5000 {
+            sim.update_positions();
+            let a = &sim.get_aircrafts()[0];
+            if at_fix.is_none() && geo::distance_nm(fix_lat, fix_lng, a.lat, a.lng) < 1.0 {
+                at_fix = Some((a.altitude, a.on_ground));
+            }
+            if a.on_ground {
+                touchdown = Some((a.lat, a.lng));
+                break;
+            }
+        }
+
+        // 绕回最后进近定位点之前保持高度，建立五边后才下降接地
+        let (altitude, on_ground) = at_fix.expect("aircraft never reached the FAF");
+        assert!(!on_ground);
+        assert_eq!(altitude, 3000.0);
+        let (lat, lng) = touchdown.expect("aircraft never touched down");
+        assert!(geo::distance_nm(runway.lat, runway.lng, lat, lng) < 0.5);
+    }
+
+    #[test]
+    fn test_parallel_departures_staggered() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 39);
+        sim.generate_mock_aircrafts(0).unwrap();
+        let runway_a = Runway {
+            lat: 22.300,
+            lng: 113.900,
+            heading: 70.0,
+        };
+        let runway_b = Runway {
+            lat: 22.285,
+            lng: 113.905,
+            heading: 70.0,
+        };
+        let created = sim
+            .create_parallel_departures(runway_a, runway_b, 3, 90)
+            .unwrap();
+        assert_eq!(created.len(), 6);
+        // 以空中状态在越障高度出现，而不是 0 ft 的空中飞机
+        let liftoff = FIELD_ELEVATION_FT + DEPARTURE_SCREEN_HEIGHT_FT;
+        for aircraft in &created {
+            assert!(!aircraft.on_ground);
+            assert_eq!(aircraft.altitude, liftoff);
+            assert!(aircraft.vertical_rate > 0.0);
+        }
+
+        // 每 90 s 两条跑道各放行一架
+        let mut released_at = Vec::new();
+        for tick in 0..=200 {
+            while released_at.len() < sim.get_aircrafts().len() {
+                released_at.push(tick);
+            }
+            sim.update_positions();
+        }
+        assert_eq!(released_at, vec![0, 0, 90, 90, 180, 180]);
+
+        // 首批飞机沿跑道方向持续爬升
+        let first = sim.get_aircrafts().iter().find(|a| a.callsign == "DEPA01").unwrap();
+        assert!(first.altitude > 5000.0);
+        assert!(geo::heading_difference(first.heading, 70.0).abs() < 5.0);
+        let last = sim.get_aircrafts().iter().find(|a| a.callsign == "DEPB03").unwrap();
+        assert!(last.altitude > 0.0 && last.altitude < first.altitude);
+
+        let crossing = Runway {
+            heading: 160.0,
+            ..runway_b
+        };
+        assert!(sim.create_parallel_departures(runway_a, crossing, 1, 60).is_err());
+    }
+
+    #[test]
+    fn test_spawned_aircraft_acquires_gnss() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 4);
+        sim.generate_mock_aircrafts(2).unwrap();
+        let mut spawned = sim.get_aircrafts()[0].clone();
+        spawned.id = "ABCDEF".to_string();
+        spawned.nic = 8;
+        sim.spawn_aircraft(spawned.clone()).unwrap();
+        // 用户提供的飞机按给定 NIC 立即可用
+        spawned.id = "ABCDE0".to_string();
+        sim.add_aircraft(spawned).unwrap();
+        let user = |sim: &AdsbSimulator| sim.get_aircraft("ABCDE0").unwrap().nic;
+        assert_eq!(user(&sim), 8);
+
+        let own = |sim: &AdsbSimulator| {
+            sim.get_aircrafts()
+                .iter()
+                .find(|a| a.id == "ABCDEF")
+                .unwrap()
+                .nic
+        };
+        let positions = |sim: &mut AdsbSimulator| {
+            sim.generate_all_messages()
+                .iter()
+                .filter(|m| m.aircraft_id == "ABCDEF" && m.message_type == MessageType::Position)
+                .count()
+        };
+
+        for _ in 0..DEFAULT_ACQUISITION_TICKS {
+            assert_eq!(own(&sim), 0);
+            assert_eq!(positions(&mut sim), 0);
+            assert!(user(&sim) > 0);
+            sim.update_positions();
+        }
+
+        // 捕获完成后恢复原 NIC 并开始发送位置
+        assert_eq!(own(&sim), 8);
+        assert_eq!(positions(&mut sim), 1);
+        for _ in 0..10 {
+            sim.update_positions();
+            assert!(own(&sim) > 0);
+            assert_eq!(positions(&mut sim), 1);
+        }
+    }
+
+    #[test]
+    fn test_cpr_robustness_bounds() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 6);
+        sim.generate_mock_aircrafts(8).unwrap();
+        assert_eq!(sim.analyze_cpr_robustness(20), 1.0);
+
+        sim.set_impairment(ChannelImpairment {
+            dropout_rate: 1.0,
+            bit_error_rate: 0.0,
+        });
+        assert_eq!(sim.analyze_cpr_robustness(20), 0.0);
+        let messages = sim.generate_all_messages();
+        assert!(sim.apply_impairment(messages).is_empty());
+
+        // 中等丢帧率下大部分飞机仍可解出位置
+        sim.set_impairment(ChannelImpairment {
+            dropout_rate: 0.3,
+            bit_error_rate: 1e-4,
+        });
+        let fraction = sim.analyze_cpr_robustness(20);
+        assert!(fraction > 0.5 && fraction <= 1.0);
+    }
+
+    #[test]
+    fn test_reception_falls_off_with_distance() {
+        let (lat, lng) = (22.5431, 114.0579);
+        let mut sim = AdsbSimulator::with_seed(lat, lng, 281);
+        sim.generate_mock_aircrafts(2).unwrap();
+        let (far_lat, far_lng) = geo::destination_point(lat, lng, 90.0, 300.0);
+        let positions = [(lat, lng), (far_lat, far_lng)];
+        for (aircraft, (a_lat, a_lng)) in sim.aircrafts.iter_mut().zip(positions) {
+            (aircraft.lat, aircraft.lng) = (a_lat, a_lng);
+            aircraft.altitude = 35000.0;
+            aircraft.speed = 0.0;
+            aircraft.vertical_rate = 0.0;
+            aircraft.phase = None;
+        }
+        let (overhead, far) = (sim.aircrafts[0].id.clone(), sim.aircrafts[1].id.clone());
+
+        // 默认不启用接收模型：消息全部保留且不附带信号强度
+        let messages = sim.generate_all_messages();
+        let all = messages.len();
+        let delivered = sim.apply_reception(messages);
+        assert_eq!(delivered.len(), all);
+        assert!(delivered.iter().all(|m| m.rssi.is_none()));
+
+        sim.set_reception(Some(ReceptionModel {
+            receiver_position: (lat, lng),
+            half_range_nm: DEFAULT_RECEPTION_HALF_RANGE_NM,
+        }))
+        .unwrap();
+        let (mut heard_overhead, mut heard_far) = (0, 0);
+        for _ in 0..50 {
+            sim.update_positions();
+            let messages = sim.generate_all_messages();
+            for message in sim.apply_reception(messages) {
+                let rssi = message.rssi.unwrap();
+                if message.aircraft_id == overhead {
+                    heard_overhead += 1;
+                    assert!(rssi > -20.0, "{}", rssi);
+                } else if message.aircraft_id == far {
+                    heard_far += 1;
+                }
+            }
+        }
+        assert!(heard_overhead >= 50, "{} overhead", heard_overhead);
+        assert!(heard_far * 10 < heard_overhead, "{} far", heard_far);
+
+        assert!(sim
+            .set_reception(Some(ReceptionModel {
+                receiver_position: (lat, lng),
+                half_range_nm: 0.0,
+            }))
+            .is_err());
+    }
+
+    #[test]
+    fn test_reception_keeps_spoofer_geometry_separate() {
+        let (lat, lng) = (22.5431, 114.0579);
+        let mut sim = AdsbSimulator::with_seed(lat, lng, 282);
+        sim.generate_mock_aircrafts(1).unwrap();
+        let aircraft = &mut sim.aircrafts[0];
+        (aircraft.lat, aircraft.lng, aircraft.altitude) = (lat, lng, 35000.0);
+        (aircraft.speed, aircraft.vertical_rate, aircraft.phase) = (0.0, 0.0, None);
+        let target = aircraft.id.clone();
+        // 欺骗源位于视距之外 (约 300 nm)
+        sim.add_spoofer(&target, (5.0, 0.0)).unwrap();
+        sim.set_reception(Some(ReceptionModel {
+            receiver_position: (lat, lng),
+            half_range_nm: DEFAULT_RECEPTION_HALF_RANGE_NM,
+        }))
+        .unwrap();
+
+        // 同一 ICAO 下，真实飞机按其自身位置被接收，欺骗源的消息全部丢失
+        let (mut real, mut spoofed) = (0, 0);
+        for _ in 0..20 {
+            sim.update_positions();
+            let messages = sim.generate_all_messages();
+            assert!(messages.iter().any(|m| m.spoofed));
+            for message in sim.apply_reception(messages) {
+                assert_eq!(message.aircraft_id, target);
+                if message.spoofed {
+                    spoofed += 1;
+                } else {
+                    real += 1;
+                    assert!(message.rssi.unwrap() > -20.0);
+                }
+            }
+        }
+        assert!(real >= 20, "{} real", real);
+        assert_eq!(spoofed, 0);
+    }
+
+    #[test]
+    fn test_airport_beacon_surface_position() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 2);
+        sim.generate_mock_aircrafts(2).unwrap();
+        let beacon = AirportBeacon {
+            icao: "F00001".to_string(),
+            lat: 22.6393,
+            lng: 113.8107,
+        };
+        sim.set_airport_beacon(Some(beacon.clone())).unwrap();
+
+        // 以机场附近的参考点做本地 CPR 解码 (地面区间跨度 90°)
+        let local_decode = |hex: &str| {
+            let frame = u128::from_str_radix(hex, 16).unwrap();
+            let me = (frame >> 24) as u64 & 0xFF_FFFF_FFFF_FFFF;
+            assert_eq!(me >> 51, 5);
+            let i = ((me >> 34) & 1) as f64;
+            let yz = ((me >> 17) & 0x1FFFF) as f64 / CPR_SCALE;
+            let xz = (me & 0x1FFFF) as f64 / CPR_SCALE;
+            let (ref_lat, ref_lng) = (22.6, 113.8);
+
+            let d_lat = 90.0 / (60.0 - i);
+            let j = (ref_lat / d_lat).floor()
+                + (0.5 + ref_lat.rem_euclid(d_lat) / d_lat - yz).floor();
+            let lat = d_lat * (j + yz);
+            let d_lng = 90.0 / (cpr_nl(lat) - i).max(1.0);
+            let m = (ref_lng / d_lng).floor()
+                + (0.5 + ref_lng.rem_euclid(d_lng) / d_lng - xz).floor();
+            (lat, d_lng * (m + xz))
+        };
+
+        let mut frames = Vec::new();
+        for _ in 0..4 {
+            let beacon_msgs: Vec<_> = sim
+                .generate_all_messages()
+                .into_iter()
+                .filter(|m| m.message_type == MessageType::SurfacePosition)
+                .collect();
+            assert_eq!(beacon_msgs.len(), 1);
+            assert_eq!(beacon_msgs[0].aircraft_id, "F00001");
+            assert!(beacon_msgs[0].hex_message.starts_with("90F00001"));
+
+            let (lat, lng) = local_decode(&beacon_msgs[0].hex_message);
+            assert!(geo::distance_nm(lat, lng, beacon.lat, beacon.lng) < 0.003);
+            frames.push(beacon_msgs[0].hex_message.clone());
+            sim.update_positions();
+        }
+
+        // 奇偶帧交替，同一奇偶的帧完全相同
+        assert_ne!(frames[0], frames[1]);
+        assert_eq!(frames[0], frames[2]);
+        assert_eq!(frames[1], frames[3]);
+
+        let invalid = AirportBeacon {
+            icao: "XYZ".to_string(),
+            ..beacon
+        };
+        assert!(sim.set_airport_beacon(Some(invalid)).is_err());
+    }
+
+    #[test]
+    fn test_two_waypoint_route_turns() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 64);
+        sim.generate_mock_aircrafts(1).unwrap();
+        let aircraft = &mut sim.aircrafts[0];
+        (aircraft.lat, aircraft.lng) = (22.50, 114.00);
+        (aircraft.heading, aircraft.speed) = (0.0, 400.0);
+        let id = aircraft.id.clone();
+        let first = Waypoint {
+            lat: 22.55,
+            lng: 114.00,
+            altitude: None,
+        };
+        let second = Waypoint {
+            lat: 22.55,
+            lng: 114.10,
+            altitude: None,
+        };
+        sim.assign_route(
+            &id,
+            FlightPlan {
+                waypoints: vec![first, second],
+                on_complete: RouteCompletion::Loop,
+            },
+        )
+        .unwrap();
+
+        // 飞向第一个航路点时保持向北，到达后转向东侧的第二个航路点
+        let mut passed_first = false;
+        let mut turned = false;
+        for _ in 0..200 {
+            sim.update_positions();
+            let aircraft = &sim.get_aircrafts()[0];
+            if !passed_first {
+                assert!(geo::heading_difference(0.0, aircraft.heading).abs() < 1e-9);
+                let to_first = geo::distance_nm(aircraft.lat, aircraft.lng, first.lat, first.lng);
+                passed_first = to_first < WAYPOINT_ARRIVAL_NM;
+            } else {
+                let bearing =
+                    geo::initial_bearing(aircraft.lat, aircraft.lng, second.lat, second.lng);
+                if geo::heading_difference(aircraft.heading, bearing).abs() < MAX_TURN_RATE_DEG {
+                    turned = true;
+                    break;
+                }
+            }
+        }
+        assert!(passed_first && turned);
+        assert!(sim.get_aircrafts()[0].heading > 45.0);
+    }
+
+    #[test]
+    fn test_short_flight_completes() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 12);
+        sim.generate_mock_aircrafts(2).unwrap();
+
+        let (from, to) = ((22.50, 114.00), (22.60, 114.05));
+        let total = geo::distance_nm(from.0, from.1, to.0, to.1);
+        let flight = sim.create_flight("MU5101", from, to, 3000.0, 300.0).unwrap();
+
+        let mut completed = Vec::new();
+        for _ in 0..3000 {
+            sim.update_positions();
+            completed.extend(sim.take_completed_flights());
+            if !completed.is_empty() {
+                break;
+            }
+        }
+
+        assert_eq!(completed.len(), 1);
+        assert_eq!(completed[0].aircraft_id, flight.id);
+        assert_eq!(completed[0].callsign, "MU5101");
+        assert!(completed[0].distance_nm >= total - WAYPOINT_ARRIVAL_NM);
+        assert!(sim.get_aircrafts().iter().all(|a| a.id != flight.id));
+        assert!(sim.get_flight_plan(&flight.id).is_none());
+        assert_eq!(sim.get_aircrafts().len(), 2);
+        assert!(sim.take_completed_flights().is_empty());
+    }
+
+    #[test]
+    fn test_due_east_longitude_scaled_by_latitude() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 65);
+        sim.generate_mock_aircrafts(1).unwrap();
+        let mut aircraft = sim.get_aircrafts()[0].clone();
+        (aircraft.heading, aircraft.speed) = (90.0, 360.0);
+
+        // 以 360 kts 向正东飞行 1 小时：航程 360 nm，经度变化为 360 / (60 · cos φ) 度
+        for latitude in [0.0, 22.5431, 60.0, 80.0] {
+            (aircraft.lat, aircraft.lng) = (latitude, 114.0579);
+            for _ in 0..3600 {
+                (aircraft.lat, aircraft.lng) = advance_position(&aircraft, &[], 1.0);
+            }
+            let expected = 360.0 / (60.0 * f64::cos(latitude.to_radians()));
+            let delta = aircraft.lng - 114.0579;
+            assert!(
+                (delta - expected).abs() < expected * 0.01,
+                "latitude {}: {} vs {}",
+                latitude,
+                delta,
+                expected
+            );
+            assert!((aircraft.lat - latitude).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_layered_wind_drift() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 13);
+        sim.generate_mock_aircrafts(2).unwrap();
+        for (aircraft, altitude) in sim.aircrafts.iter_mut().zip([4000.0, 11000.0]) {
+            aircraft.altitude = altitude;
+            aircraft.speed = 0.0;
+            aircraft.phase = None;
+        }
+        // 低空 20 kts 西风，8000 ft 以上 100 kts 西风
+        sim.set_wind_layers(vec![
+            WindLayer {
+                floor_ft: 8000.0,
+                direction_deg: 270.0,
+                speed_kts: 100.0,
+            },
+            WindLayer {
+                floor_ft: 0.0,
+                direction_deg: 270.0,
+                speed_kts: 20.0,
+            },
+        ])
+        .unwrap();
+
+        let start: Vec<(f64, f64)> = sim.get_aircrafts().iter().map(|a| (a.lat, a.lng)).collect();
+        for _ in 0..30 {
+            sim.update_positions();
+        }
+        let drift: Vec<(f64, f64)> = sim
+            .get_aircrafts()
+            .iter()
+            .zip(&start)
+            .map(|(a, (lat, lng))| (a.lat - lat, a.lng - lng))
+            .collect();
+
+        // 西风使飞机向东飘移，高空飘移量为低空的 5 倍
+        // 球面上沿正东方向前进时纬度只有二阶的微小变化
+        for (d_lat, d_lng) in &drift {
+            assert!(d_lat.abs() < 1e-6);
+            assert!(*d_lng > 0.0);
+        }
+        // 经度变化随纬度缩放，按飘移距离比较
+        let distance: Vec<f64> = sim
+            .get_aircrafts()
+            .iter()
+            .zip(&start)
+            .map(|(a, (lat, lng))| geo::distance_nm(*lat, *lng, a.lat, a.lng))
+            .collect();
+        assert!((distance[1] / distance[0] - 5.0).abs() < 1e-6);
+
+        assert!(sim
+            .set_wind_layers(vec![WindLayer {
+                floor_ft: 0.0,
+                direction_deg: 0.0,
+                speed_kts: -5.0,
+            }])
+            .is_err());
+    }
+
+    #[test]
+    fn test_crosswind_track_differs_from_heading() {
+        use crate::decoder::{decode_message, MessageBody};
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 14);
+        sim.generate_mock_aircrafts(1).unwrap();
+        sim.aircrafts[0].heading = 0.0;
+        sim.aircrafts[0].speed = 400.0;
+        sim.aircrafts[0].phase = None;
+        // 40 kts 西风从左侧吹向机头朝北的飞机
+        sim.set_wind(40.0, 270.0).unwrap();
+        sim.update_positions();
+
+        let aircraft = &sim.get_aircrafts()[0];
+        let air = aircraft.speed;
+        let drift = 40.0_f64.atan2(air).to_degrees();
+        let hex = AdsbSimulator::generate_velocity_message(aircraft);
+        let MessageBody::Velocity { speed, heading, .. } = decode_message(&hex).unwrap().body
+        else {
+            panic!("expected a velocity message");
+        };
+        // 航迹角相对航向向右偏出侧风造成的偏流角，地速为两向量合成
+        let track = heading.unwrap();
+        let offset = (track - aircraft.heading + 540.0) % 360.0 - 180.0;
+        assert!((offset - drift).abs() < 0.5, "{} vs {}", offset, drift);
+        assert!((speed.unwrap() - air.hypot(40.0)).abs() < 1.5);
+        assert!((aircraft.track - track).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_message_type_wire_format() {
+        let expected = [
+            (MessageType::Identification, "identification"),
+            (MessageType::SurfacePosition, "surface_position"),
+            (MessageType::Position, "position"),
+            (MessageType::Velocity, "velocity"),
+            (MessageType::Status, "status"),
+            (MessageType::TargetState, "target_state"),
+            (MessageType::OperationalStatus, "operational_status"),
+        ];
+        for (message_type, text) in expected {
+            let json = serde_json::to_string(&message_type).unwrap();
+            assert_eq!(json, format!("\"{}\"", text));
+            assert_eq!(serde_json::from_str::<MessageType>(&json).unwrap(), message_type);
+        }
+
+        let event: AdsbEvent = serde_json::from_str(
+            r#"{"hex_message":"8D40621D58C382D690C8AC2863A7","aircraft_id":"40621D","message_type":"velocity"}"#,
+        )
+        .unwrap();
+        assert_eq!(event.message_type, MessageType::Velocity);
+        assert_eq!(MessageType::from_type_code(11), MessageType::Position);
+        // 类型码只对 DF17/18 有意义，DF11 为全呼应答
+        let by_df = |df: u8, type_code: u8| MessageType::from_downlink_format(df, type_code);
+        assert_eq!(by_df(17, 11), MessageType::Position);
+        assert_eq!(by_df(11, 0), MessageType::AllCall);
+        assert_eq!(by_df(5, 0), MessageType::Other);
+    }
+
+    #[test]
+    fn test_assemble_raw_parity() {
+        // 已知 DF17 位置消息：CA=5、ICAO=40621D
+        let payload = (5u128 << 80) | (0x40621Du128 << 56) | 0x58C382D690C8AC;
+        assert_eq!(assemble_raw(17, payload, None).unwrap(), "8D40621D58C382D690C8AC2863A7");
+
+        let bytes = |hex: &str| -> Vec<u8> {
+            (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+                .collect()
+        };
+        let mut rng = SimRng::new(241);
+        for df in 0..32u8 {
+            let width = if df >= 16 { 83 } else { 27 };
+            let payload = (((rng.next_u64() as u128) << 64) | rng.next_u64() as u128)
+                & ((1u128 << width) - 1);
+
+            // CRC 模式：整条消息的余数为 0
+            let crc = bytes(&assemble_raw(df, payload, None).unwrap());
+            assert_eq!(crc.len() * 8, width + 29);
+            assert_eq!(crc24(&crc), 0);
+
+            // AP 模式：数据部分的 CRC 与校验字段异或后得到地址
+            let address = (rng.next_u64() & 0xFF_FFFF) as u32;
+            let ap = bytes(&assemble_raw(df, payload, Some(address)).unwrap());
+            let (data, pi) = ap.split_at(ap.len() - 3);
+            let pi = u32::from_be_bytes([0, pi[0], pi[1], pi[2]]);
+            assert_eq!(crc24(data) ^ pi, address);
+        }
+
+        let surveillance = assemble_raw(4, 0x1234, Some(0xABCDEF)).unwrap();
+        assert_eq!(crate::decoder::decode_message(&surveillance).unwrap().icao, "ABCDEF");
+
+        assert!(assemble_raw(32, 0, None).is_err());
+        assert!(assemble_raw(4, 1 << 27, None).is_err());
+        assert!(assemble_raw(4, 0, Some(0x100_0000)).is_err());
+    }
+
+    #[test]
+    fn test_semicircular_flight_levels() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 43);
+        sim.set_semicircular_levels(true);
+        sim.generate_mock_aircrafts(40).unwrap();
+
+        for aircraft in sim.get_aircrafts() {
+            let thousands = aircraft.altitude / 1000.0;
+            assert_eq!(thousands.fract(), 0.0);
+            if aircraft.heading < 180.0 {
+                assert_eq!(thousands as i64 % 2, 1, "eastbound {}", aircraft.callsign);
+            } else {
+                assert_eq!(thousands as i64 % 2, 0, "westbound {}", aircraft.callsign);
+            }
+        }
+        assert_eq!(semicircular_level(6200.0, 90.0), 7000.0);
+        assert_eq!(semicircular_level(6800.0, 270.0), 6000.0);
+        assert_eq!(semicircular_level(400.0, 270.0), 2000.0);
+    }
+
+    #[test]
+    fn test_interrogation_replies() {
+        use crate::decoder::{decode_message, MessageBody};
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 50);
+        sim.generate_mock_aircrafts(2).unwrap();
+        let aircraft = sim.get_aircrafts()[0].clone();
+
+        let reply = sim.interrogate(&aircraft.id, 4).unwrap().unwrap();
+        assert_eq!(reply.len(), 14);
+        let decoded = decode_message(&reply).unwrap();
+        assert_eq!(decoded.df, 4);
+        assert_eq!(decoded.icao, aircraft.id); // 由 AP 恢复地址
+        let MessageBody::Altitude {
+            altitude: Some(altitude),
+        } = decoded.body
+        else {
+            panic!("expected an altitude reply, got {:?}", decoded.body);
+        };
+        assert!((altitude - aircraft.altitude).abs() <= 12.5);
+
+        let identity = sim.interrogate(&aircraft.id, 5).unwrap().unwrap();
+        assert_eq!(
+            decode_message(&identity).unwrap().body,
+            MessageBody::Identity {
+                squawk: "1200".to_string()
+            }
+        );
+
+        // Comm-B 长应答：AP 同样可恢复地址，MB 字段携带航班号
+        let comm_b = sim.interrogate(&aircraft.id, 20).unwrap().unwrap();
+        assert_eq!(comm_b.len(), 28);
+        assert_eq!(&comm_b[8..10], "20");
+        let data = u128::from_str_radix(&comm_b[..22], 16).unwrap();
+        let ap = u32::from_str_radix(&comm_b[22..], 16).unwrap();
+        assert_eq!(
+            format!("{:06X}", crc24(&data.to_be_bytes()[5..]) ^ ap),
+            aircraft.id
+        );
+        assert_eq!(
+            encode_callsign(&aircraft.callsign),
+            u64::from_str_radix(&comm_b[10..22], 16).unwrap()
+        );
+
+        assert!(sim.interrogate(&aircraft.id, 11).is_err());
+        assert!(sim.interrogate("000000", 4).is_err());
+        sim.set_source(&aircraft.id, AdsbSource::TisB);
+        assert_eq!(sim.interrogate(&aircraft.id, 4).unwrap(), None);
+    }
+
+    #[test]
+    fn test_vertical_rate_climb() {
+        use crate::decoder::{decode_message, MessageBody};
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 60);
+        sim.generate_mock_aircrafts(4).unwrap();
+        // 生成的机队中既有爬升也有下降的飞机
+        assert!(sim.get_aircrafts().iter().any(|a| a.vertical_rate > 0.0));
+        assert!(sim.get_aircrafts().iter().any(|a| a.vertical_rate < 0.0));
+
+        let mut aircraft = sim.get_aircrafts()[2].clone();
+        aircraft.id = "ABC123".to_string();
+        aircraft.altitude = 5000.0;
+        aircraft.vertical_rate = 1600.0;
+        // 不启用阶段状态机时按给定垂直速率爬升
+        aircraft.phase = None;
+        sim.add_aircraft(aircraft).unwrap();
+        for _ in 0..60 {
+            sim.update_positions();
+        }
+        let climbed = sim.get_aircrafts().iter().find(|a| a.id == "ABC123").unwrap();
+        assert!((climbed.altitude - 6600.0).abs() < 1.0, "{}", climbed.altitude);
+
+        // 速度消息中的垂直速率按 64 ft/min 分辨率编码，下降时符号位置 1
+        let mut descending = climbed.clone();
+        descending.vertical_rate = -1600.0;
+        for (aircraft, expected) in [(climbed, 1600.0), (&descending, -1600.0)] {
+            let hex = AdsbSimulator::generate_velocity_message(aircraft);
+            let MessageBody::Velocity { vertical_rate, .. } = decode_message(&hex).unwrap().body
+            else {
+                panic!("expected a velocity message");
+            };
+            assert_eq!(vertical_rate, Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_get_aircraft_by_icao() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 61);
+        sim.generate_mock_aircrafts(5).unwrap();
+        let known = sim.get_aircrafts()[3].clone();
+
+        let found = sim.get_aircraft(&known.id.to_lowercase()).unwrap();
+        assert_eq!(found.callsign, known.callsign);
+        assert!(sim.get_aircraft("000000").is_none());
+    }
+
+    #[test]
+    fn test_grounded_aircraft_emits_surface_position() {
+        use crate::decoder::{decode_message, MessageBody};
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 63);
+        sim.generate_mock_aircrafts(1).unwrap();
+        sim.aircrafts[0].on_ground = true;
+        sim.aircrafts[0].speed = 250.0;
+        sim.set_wind(40.0, 270.0).unwrap();
+        sim.update_positions();
+
+        // 地面飞机停在机场标高，速度受滑行上限约束，不受风影响
+        let aircraft = sim.get_aircrafts()[0].clone();
+        assert_eq!((aircraft.altitude, aircraft.vertical_rate), (0.0, 0.0));
+        assert!(aircraft.speed <= PhaseSpeedProfile::default().taxi_max);
+        assert_eq!(aircraft.ground_speed, aircraft.speed);
+
+        let messages = sim.generate_all_messages();
+        let airborne = [MessageType::Position, MessageType::Velocity];
+        assert!(messages.iter().all(|m| !airborne.contains(&m.message_type)));
+        let surface = messages
+            .iter()
+            .find(|m| m.message_type == MessageType::SurfacePosition)
+            .unwrap();
+        let decoded = decode_message(&surface.hex_message).unwrap();
+        assert!((5..=8).contains(&decoded.type_code));
+        let MessageBody::SurfacePosition {
+            ground_speed,
+            track,
+            ..
+        } = decoded.body
+        else {
+            panic!("expected a surface position message");
+        };
+        assert!((ground_speed.unwrap() - aircraft.speed).abs() <= 1.0);
+        assert!((track.unwrap() - aircraft.track).abs() < 3.0);
+    }
+
+    #[test]
+    fn test_tisb_source_uses_df18() {
+        use crate::decoder::{decode_message, MessageSource};
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 64);
+        sim.generate_mock_aircrafts(2).unwrap();
+        let native = sim.get_aircrafts()[0].id.clone();
+        let tisb = sim.get_aircrafts()[1].id.clone();
+        assert!(sim.set_source(&tisb, AdsbSource::TisB));
+        sim.update_positions();
+
+        // 首字节为 DF(5 位) + CF(3 位)：DF17 CA=5 为 0x8D，DF18 CF=2 为 0x92
+        let messages = sim.generate_all_messages();
+        for message in messages.iter().filter(|m| m.aircraft_id == tisb) {
+            assert!(message.hex_message.starts_with("92"));
+            let decoded = decode_message(&message.hex_message).unwrap();
+            assert_eq!((decoded.df, decoded.ca), (18, 2));
+            assert_eq!(decoded.source, MessageSource::TisB);
+        }
+        assert!(messages
+            .iter()
+            .filter(|m| m.aircraft_id == native && m.message_type != MessageType::AllCall)
+            .all(|m| m.hex_message.starts_with("8D")));
+    }
+
+    #[test]
+    fn test_load_aircrafts_from_json() {
+        use serde_json::json;
+
+        let path = std::env::temp_dir().join(format!("adsb_scenario_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        let entry = |id: &str, heading: f64| {
+            json!({
+                "id": id, "callsign": "TEST", "lat": 22.6, "lng": 114.1,
+                "altitude": 8000.0, "speed": 250.0, "heading": heading, "nic": 8
+            })
+        };
+        let write = |fleet: Vec<_>| fs::write(path, json!(fleet).to_string()).unwrap();
+        let ids = ["A00001", "A00002", "a00003"];
+        write(ids.map(|id| entry(id, 90.0)).to_vec());
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 65);
+        sim.generate_mock_aircrafts(5).unwrap();
+        assert_eq!(sim.load_aircrafts_from_json(path), Ok(3));
+        let loaded: Vec<&str> = sim.get_aircrafts().iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(loaded, ids);
+        sim.update_positions();
+        assert!(sim.get_aircrafts().iter().all(|a| a.lng > 114.1));
+
+        // 任一飞机不合法时拒绝整个文件，当前机队不变
+        write(vec![entry("B00001", 90.0), entry("B00002", 400.0)]);
+        let err = sim.load_aircrafts_from_json(path).unwrap_err();
+        assert!(err.contains("entry 1: Aircraft B00002: heading"), "{}", err);
+        write(vec![entry("B00001", 90.0), entry("b00001", 90.0)]);
+        let err = sim.load_aircrafts_from_json(path).unwrap_err();
+        assert!(err.contains("duplicate"), "{}", err);
+        assert_eq!(sim.get_aircrafts().len(), 3);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_scenario_export_round_trip() {
+        let path = std::env::temp_dir().join(format!("adsb_snapshot_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 66);
+        sim.generate_mock_aircrafts(4).unwrap();
+        for _ in 0..3 {
+            sim.update_positions();
+        }
+        let icao = sim.get_aircrafts()[1].id.clone();
+        let emergency = Some(EmergencyKind::RadioFailure);
+        sim.set_emergency(&icao, emergency).unwrap();
+        sim.set_source(&icao, AdsbSource::TisB);
+
+        let json = sim.export_aircrafts_to_json().unwrap();
+        assert!(json.contains("\n  \"center_lat\": 22.5431,"));
+        let snapshot: ScenarioFile = serde_json::from_str(&json).unwrap();
+        fs::write(path, &json).unwrap();
+
+        // 捕获中的飞机导出配置的 NIC；重新加载时不经历捕获，逐字段一致
+        let mut reloaded = AdsbSimulator::with_seed(0.0, 0.0, 67);
+        reloaded.set_acquisition_ticks(0);
+        reloaded.load_aircrafts_from_json(path).unwrap();
+        assert_eq!(reloaded.get_aircrafts(), &snapshot.aircrafts);
+        assert!(snapshot.aircrafts.iter().all(|a| a.nic > 0));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_heavy_category_in_identification() {
+        use crate::decoder::{decode_message, MessageBody};
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 68);
+        sim.generate_mock_aircrafts(100).unwrap();
+        // 航空公司航班以大型、重型客机为主
+        let airliners = sim
+            .get_aircrafts()
+            .iter()
+            .filter(|a| [AircraftCategory::Large, AircraftCategory::Heavy].contains(&a.category))
+            .count();
+        assert!(airliners >= 80, "{} large/heavy aircraft", airliners);
+
+        let mut aircraft = sim.get_aircrafts()[0].clone();
+        aircraft.category = AircraftCategory::Heavy;
+        let decoded = decode_message(&AdsbSimulator::generate_identification_message(&aircraft));
+        let decoded = decoded.unwrap();
+        assert_eq!(decoded.type_code, 4);
+        // A5：重型 (> 300 000 lb)
+        let MessageBody::Identification { category, .. } = decoded.body else {
+            panic!("expected an identification message");
+        };
+        assert_eq!(category, 5);
+        let heavy = AircraftCategory::from_code(category);
+        assert_eq!(heavy, Some(AircraftCategory::Heavy));
+    }
+
+    #[test]
+    fn test_remove_aircraft() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 62);
+        sim.generate_mock_aircrafts(4).unwrap();
+        let icao = sim.get_aircrafts()[1].id.clone();
+
+        assert!(sim.remove_aircraft(&icao.to_lowercase()));
+        assert_eq!(sim.get_aircrafts().len(), 3);
+        assert!(sim.get_aircrafts().iter().all(|a| a.id != icao));
+        assert!(!sim.remove_aircraft(&icao));
+
+        sim.update_positions();
+        assert!(sim.generate_all_messages().iter().all(|m| m.aircraft_id != icao));
+    }
+
+    #[test]
+    fn test_tick_hook_applies_each_tick() {
+        use crate::decoder::{decode_message, MessageBody};
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 59);
+        sim.generate_mock_aircrafts(3).unwrap();
+        let icao = sim.get_aircrafts()[1].id.clone();
+        let target = icao.clone();
+        sim.add_tick_hook(move |aircrafts, tick| {
+            if let Some(aircraft) = aircrafts.iter_mut().find(|a| a.id == target) {
+                aircraft.altitude = 5000.0 + tick as f64 * 100.0;
+            }
+        });
+
+        for tick in 1..=5u64 {
+            sim.update_positions();
+            let expected = 5000.0 + tick as f64 * 100.0;
+            let aircraft = sim.get_aircrafts().iter().find(|a| a.id == icao).unwrap();
+            assert_eq!(aircraft.altitude, expected);
+            // 回调在消息生成之前执行，位置消息携带修改后的高度
+            let position = sim
+                .generate_all_messages()
+                .into_iter()
+                .find(|m| m.aircraft_id == icao && m.message_type == MessageType::Position)
+                .unwrap();
+            let MessageBody::Position { altitude, .. } =
+                decode_message(&position.hex_message).unwrap().body
+            else {
+                panic!("expected a position message");
+            };
+            assert_eq!(altitude, Some(expected));
+        }
+
+        sim.clear_tick_hooks();
+        sim.update_positions();
+        let aircraft = sim.get_aircrafts().iter().find(|a| a.id == icao).unwrap();
+        assert_ne!(aircraft.altitude, 5600.0);
+    }
+
+    #[test]
+    fn test_tick_hook_output_is_sanitized() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 60);
+        sim.generate_mock_aircrafts(3).unwrap();
+        for _ in 0..3 {
+            sim.update_positions();
+        }
+        let removed = sim.get_aircrafts()[0].id.clone();
+        let fix = Waypoint {
+            lat: 22.6,
+            lng: 114.1,
+            altitude: None,
+        };
+        let plan = FlightPlan {
+            waypoints: vec![fix],
+            on_complete: RouteCompletion::Hold,
+        };
+        sim.assign_route(&removed, plan).unwrap();
+        assert!(sim.get_history().contains_key(&removed));
+
+        let target = removed.clone();
+        sim.add_tick_hook(move |aircrafts, _| {
+            aircrafts.retain(|a| a.id != target);
+            // 注入一架 ICAO 重复的飞机和一架纬度非法的飞机
+            let mut duplicate = aircrafts[0].clone();
+            duplicate.id = duplicate.id.to_ascii_lowercase();
+            let mut invalid = aircrafts[1].clone();
+            invalid.id = "ABCDEF".to_string();
+            invalid.lat = 95.0;
+            aircrafts.push(duplicate);
+            aircrafts.push(invalid);
+        });
+        sim.update_positions();
+        sim.clear_tick_hooks();
+
+        let ids: Vec<&str> = sim.get_aircrafts().iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids.len(), 2);
+        assert!(!ids.contains(&"ABCDEF"));
+        assert!(!sim.routes.contains_key(&removed));
+        assert!(!sim.get_history().contains_key(&removed));
+        // 剩余飞机的消息仍可正常生成
+        sim.update_positions();
+        assert!(!sim.generate_all_messages().is_empty());
+    }
+
+    #[test]
+    fn test_all_call_reply() {
+        use crate::decoder::{crc_syndrome, decode_message};
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 57);
+        sim.generate_mock_aircrafts(3).unwrap();
+        let aircraft = sim.get_aircrafts()[0].clone();
+
+        let reply = AdsbSimulator::generate_all_call_reply(&aircraft);
+        assert_eq!(reply.len() * 4, 56);
+        // II = 0 时 PI 即 CRC，校验余数为 0，地址从 AA 字段恢复
+        assert_eq!(crc_syndrome(&reply), Ok(0));
+        let decoded = decode_message(&reply).unwrap();
+        assert_eq!(decoded.df, 11);
+        assert_eq!(decoded.ca, 5);
+        assert_eq!(decoded.icao, aircraft.id);
+
+        // 突发只包含装有应答机的飞机
+        sim.set_source(&aircraft.id, AdsbSource::TisB);
+        let burst = sim.all_call_burst();
+        assert_eq!(burst.len(), 2);
+        assert!(burst.iter().all(|m| m.aircraft_id != aircraft.id));
+        assert!(sim
+            .generate_all_messages()
+            .iter()
+            .all(|m| m.aircraft_id != aircraft.id || m.message_type != MessageType::AllCall));
+    }
+
+    #[test]
+    fn test_squawk_json_round_trip() {
+        use serde_json::json;
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 269);
+        sim.generate_mock_aircrafts(1).unwrap();
+        let mut aircraft = sim.get_aircrafts()[0].clone();
+        assert_eq!(aircraft.squawk, 0o1200);
+
+        // 导出为与解码器相同的四位八进制字符串，读回后数值不变
+        let mut json = serde_json::to_value(&aircraft).unwrap();
+        assert_eq!(json["squawk"], "1200");
+        let parsed: Aircraft = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(parsed, aircraft);
+
+        aircraft.squawk = 0o0017;
+        let text = serde_json::to_string(&aircraft).unwrap();
+        assert!(text.contains("\"squawk\":\"0017\""), "{}", text);
+
+        json["squawk"] = "7700".into();
+        let parsed: Aircraft = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(parsed.squawk, 0o7700);
+        assert_eq!(parsed.emergency_state(), Some(EmergencyKind::General));
+
+        // 非八进制、位数不对或数值形式的编码都被拒绝
+        for invalid in [json!("7800"), json!("770"), json!("+770"), json!(7700)] {
+            json["squawk"] = invalid;
+            assert!(serde_json::from_value::<Aircraft>(json.clone()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_emergency_status_message() {
+        use crate::decoder::{crc_syndrome, decode_message, MessageBody};
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 269);
+        sim.generate_mock_aircrafts(2).unwrap();
+        let icao = sim.get_aircrafts()[0].id.clone();
+        assert!(sim
+            .get_aircrafts()
+            .iter()
+            .all(|a| a.squawk == 0o1200 && a.emergency.is_none()));
+
+        sim.set_emergency(&icao, Some(EmergencyKind::General))
+            .unwrap();
+        let aircraft = sim.get_aircrafts()[0].clone();
+        assert_eq!(aircraft.squawk, 0o7700);
+
+        // 紧急状态期间每次都发送 TC 28，紧急状态子字段为 1
+        for _ in 0..3 {
+            sim.update_positions();
+            let status: Vec<AdsbEvent> = sim
+                .generate_all_messages()
+                .into_iter()
+                .filter(|m| m.aircraft_id == icao && m.message_type == MessageType::Status)
+                .collect();
+            assert_eq!(status.len(), 1);
+            assert_eq!(crc_syndrome(&status[0].hex_message), Ok(0));
+            let decoded = decode_message(&status[0].hex_message).unwrap();
+            assert_eq!(decoded.type_code, 28);
+            assert_eq!(
+                decoded.body,
+                MessageBody::Status {
+                    emergency: 1,
+                    squawk: "7700".to_string()
+                }
+            );
+        }
+
+        // 仅设置应答机编码 7500 也按劫机报告
+        let mut hijacked = aircraft.clone();
+        hijacked.emergency = None;
+        hijacked.squawk = 0o7500;
+        let hex = AdsbSimulator::generate_status_message(&hijacked);
+        let MessageBody::Status { emergency, .. } = decode_message(&hex).unwrap().body else {
+            panic!("expected a status message");
+        };
+        assert_eq!(emergency, 5);
+
+        sim.set_emergency(&icao, None).unwrap();
+        assert_eq!(sim.get_aircrafts()[0].squawk, 0o1200);
+        assert!(sim
+            .set_emergency("FFFFFF", Some(EmergencyKind::Hijack))
+            .is_err());
+    }
+
+    #[test]
+    fn test_operational_status_ident_and_ra() {
+        use crate::decoder::{crc_syndrome, decode_message, MessageBody};
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 62);
+        sim.generate_mock_aircrafts(2).unwrap();
+        let mut aircraft = sim.get_aircrafts()[0].clone();
+        let decode = |aircraft: &Aircraft, tcas_ra: bool| {
+            let hex = AdsbSimulator::generate_operational_status_message(aircraft, tcas_ra);
+            assert_eq!(crc_syndrome(&hex), Ok(0));
+            let decoded = decode_message(&hex).unwrap();
+            assert_eq!(decoded.type_code, 31);
+            let MessageBody::OperationalStatus {
+                capability_class,
+                operational_mode,
+                version,
+                ..
+            } = decoded.body
+            else {
+                panic!("unexpected body {:?}", decoded.body);
+            };
+            assert_eq!(version, aircraft.version);
+            (capability_class, operational_mode)
+        };
+
+        // 默认：TCAS 与 1090ES IN，运行模式为空
+        assert_eq!(decode(&aircraft, false), (0x3000, 0));
+        // SPI 激活时运行模式报告 IDENT (ME 28)
+        aircraft.spi = true;
+        assert_eq!(decode(&aircraft, false).1, 0x1000);
+        // TCAS RA (ME 27) 仅在装有 TCAS 时报告
+        assert_eq!(decode(&aircraft, true).1, 0x3000);
+        aircraft.capability_class.tcas = false;
+        assert_eq!(decode(&aircraft, true), (0x1000, 0x1000));
+
+        // 两架飞机接近到 TCAS 门限内时双方均处于 RA 状态
+        let (lat, lng, altitude) = (aircraft.lat, aircraft.lng, aircraft.altitude);
+        sim.aircrafts[1].lat = lat + 0.005;
+        sim.aircrafts[1].lng = lng;
+        sim.aircrafts[1].altitude = altitude + 300.0;
+        sim.aircrafts[1].on_ground = false;
+        sim.aircrafts[0].on_ground = false;
+        assert_eq!(sim.tcas_ra_aircraft().len(), 2);
+        sim.aircrafts[1].altitude = altitude + 1000.0;
+        assert!(sim.tcas_ra_aircraft().is_empty());
+    }
+
+    #[test]
+    fn test_integrity_categories_encoded() {
+        use crate::decoder::{decode_message, MessageBody};
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 280);
+        sim.generate_mock_aircrafts(7).unwrap();
+        // 生成的 NACp 随 NIC 升高而不降低
+        let mut fleet = sim.get_aircrafts().clone();
+        fleet.sort_by_key(|a| a.nic);
+        assert!(fleet.windows(2).all(|pair| pair[0].nacp <= pair[1].nacp));
+        assert!(fleet.windows(2).all(|pair| pair[0].nacv <= pair[1].nacv));
+
+        let mut aircraft = fleet[0].clone();
+        aircraft.nacp = 10;
+        aircraft.nacv = 3;
+        aircraft.sil = 2;
+        // NACp 位于 ME 45-48，SIL 位于 ME 51-52
+        let hex = AdsbSimulator::generate_operational_status_message(&aircraft, false);
+        let me = u64::from_str_radix(&hex[8..22], 16).unwrap();
+        assert_eq!((me >> 51, (me >> 8) & 0xF, (me >> 4) & 0x3), (31, 10, 2));
+        let MessageBody::OperationalStatus { nacp, sil, .. } = decode_message(&hex).unwrap().body
+        else {
+            panic!("expected an operational status message");
+        };
+        assert_eq!((nacp, sil), (10, 2));
+
+        // NACv 位于速度消息 ME 11-13
+        let hex = AdsbSimulator::generate_velocity_message(&aircraft);
+        let me = u64::from_str_radix(&hex[8..22], 16).unwrap();
+        assert_eq!((me >> 43) & 0x7, 3);
+        let MessageBody::Velocity { nacv, .. } = decode_message(&hex).unwrap().body else {
+            panic!("expected a velocity message");
+        };
+        assert_eq!(nacv, 3);
+
+        aircraft.nacv = 5;
+        assert!(sim.add_aircraft(aircraft).is_err());
+    }
+
+    #[test]
+    fn test_assemble_message_crc_parity() {
+        // 已知正确的 DF17 空中位置消息，PI 为 2863A7
+        let hex = assemble_message(17, 5, 0x40621D, 0x58C382D690C8AC);
+        assert_eq!(hex, "8D40621D58C382D690C8AC2863A7");
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 51);
+        sim.generate_mock_aircrafts(3).unwrap();
+        sim.update_positions();
+        for message in sim.generate_all_messages() {
+            assert_eq!(crate::decoder::crc_syndrome(&message.hex_message), Ok(0));
+        }
+    }
+
+    #[test]
+    fn test_history_budget_evicts_oldest() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 52);
+        sim.generate_mock_aircrafts(4).unwrap();
+        sim.set_history_budget(10);
+        for _ in 0..5 {
+            sim.update_positions();
+        }
+
+        let total: usize = sim.get_history().values().map(VecDeque::len).sum();
+        assert_eq!(total, 10);
+        let tracks: Vec<&VecDeque<TrackPoint>> = sim.get_history().values().collect();
+        assert_eq!(tracks.len(), 4);
+        // 最新两个时刻完整保留，其余为上一时刻的部分点
+        let latest = tracks[0].back().unwrap().time;
+        for track in &tracks {
+            assert_eq!(track.back().unwrap().time, latest);
+            assert!(track.front().unwrap().time >= latest - 2.0);
+            assert!(track.iter().zip(track.iter().skip(1)).all(|(a, b)| a.time < b.time));
+        }
+
+        sim.set_history_budget(3);
+        let total: usize = sim.get_history().values().map(VecDeque::len).sum();
+        assert_eq!(total, 3);
+    }
+
+    #[test]
+    fn test_cpr_global_roundtrip() {
+        use crate::decoder::cpr_decode_airborne;
+
+        for &(lat, lng) in &[(22.5431, 114.0579), (52.2572, 3.9190), (-33.9461, -151.1772)] {
+            let even = cpr_encode(lat, lng, false);
+            let odd = cpr_encode(lat, lng, true);
+            for latest_odd in [false, true] {
+                let (dlat, dlng) = cpr_decode_airborne(even, odd, latest_odd).unwrap();
+                assert!(geo::distance_nm(lat, lng, dlat, dlng) * 1852.0 < 5.0);
+            }
+        }
+
+        // 连续的位置消息奇偶帧交替，取最近一对即可解出当前位置
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 53);
+        sim.set_acquisition_ticks(0);
+        sim.generate_mock_aircrafts(2).unwrap();
+        let mut frames: Vec<u128> = Vec::new();
+        let id = sim.get_aircrafts()[0].id.clone();
+        for _ in 0..2 {
+            sim.update_positions();
+            let message = sim
+                .generate_all_messages()
+                .into_iter()
+                .find(|m| m.aircraft_id == id && m.message_type == MessageType::Position)
+                .unwrap();
+            frames.push(u128::from_str_radix(&message.hex_message, 16).unwrap());
+        }
+        let fields = |msg: u128| {
+            let me = (msg >> 24) as u64;
+            ((me >> 34) & 1 == 1, ((me >> 17) & 0x1FFFF) as u32, (me & 0x1FFFF) as u32)
+        };
+        let (first_odd, lat0, lng0) = fields(frames[0]);
+        let (second_odd, lat1, lng1) = fields(frames[1]);
+        assert!(!first_odd && second_odd);
+        let (lat, lng) = cpr_decode_airborne((lat0, lng0), (lat1, lng1), true).unwrap();
+        let aircraft = &sim.get_aircrafts()[0];
+        assert!(geo::distance_nm(aircraft.lat, aircraft.lng, lat, lng) * 1852.0 < 10.0);
+    }
+
+    #[test]
+    fn test_encode_altitude_ft() {
+        use crate::decoder::decode_altitude;
+
+        // DO-260B 示例：38000 ft 为 0xC38 (N = 1560，Q 位置位)
+        assert_eq!(encode_altitude_ft(38000.0), 0b1100_0011_1000);
+        assert_eq!(encode_altitude_ft(0.0), 0b0000_0101_1000);
+        assert_eq!(encode_altitude_ft(-1000.0), 0x010);
+        assert_eq!(encode_altitude_ft(f64::NAN), 0);
+
+        for alt in [-1000.0, 0.0, 1225.0, 38000.0, 50175.0] {
+            assert_eq!(decode_altitude(encode_altitude_ft(alt)), Some(alt));
+        }
+        // 超出 Q 位范围改用 Gillham 100 ft 编码
+        for alt in [50200.0, 60000.0, 75300.0, 126700.0] {
+            let code = encode_altitude_ft(alt);
+            assert_eq!(code & 0x010, 0);
+            assert_eq!(decode_altitude(code), Some(alt), "altitude {}", alt);
+        }
+        // 越界值按边界编码
+        assert_eq!(decode_altitude(encode_altitude_ft(-5000.0)), Some(-1000.0));
+        assert_eq!(decode_altitude(encode_altitude_ft(200000.0)), Some(126700.0));
+    }
+
+    #[test]
+    fn test_field_quantization_boundaries() {
+        use crate::decoder::decode_altitude;
+
+        // 正好落在 25 ft 台阶上的高度不受浮点误差影响
+        assert_eq!(decode_altitude(encode_altitude_ft(1025.0)), Some(1025.0));
+        assert_eq!(decode_altitude(encode_altitude_ft(1025.0 - 1e-9)), Some(1025.0));
+        assert_eq!(decode_altitude(encode_altitude_ft(1024.9)), Some(1025.0));
+        // 半台阶取偶数：N = 80.5 -> 80，N = 81.5 -> 82
+        assert_eq!(decode_altitude(encode_altitude_ft(1012.5)), Some(1000.0));
+        assert_eq!(decode_altitude(encode_altitude_ft(1037.5)), Some(1050.0));
+
+        assert_eq!(quantize(2.5, 1.0, 10), 2);
+        assert_eq!(quantize(3.5, 1.0, 10), 4);
+        assert_eq!(quantize(-3.0, 1.0, 10), 0);
+        assert_eq!(quantize(99.0, 1.0, 10), 10);
+        assert_eq!(quantize(f64::NAN, 1.0, 10), 0);
+
+        // 航迹角接近 360° 时回绕为 0，而不是溢出到第 8 位
+        assert_eq!(quantize_angle(359.0, 7), 0);
+        assert_eq!(quantize_angle(-90.0, 7), 96);
+        assert_eq!(quantize_angle(180.0, 7), 64);
+
+        // 地面速度区间上端取整到下一区间的起始编码
+        assert_eq!(encode_ground_movement(1.99), encode_ground_movement(2.0));
+        assert_eq!(encode_ground_movement(15.0), 39);
+        assert_eq!(encode_ground_movement(500.0), 124);
+    }
+
+    #[test]
+    fn test_identification_message() {
+        use crate::decoder::{decode_message, CALLSIGN_CHARSET};
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 55);
+        sim.generate_mock_aircrafts(3).unwrap();
+        let mut aircraft = sim.get_aircrafts()[0].clone();
+        aircraft.callsign = "CSN123".to_string();
+
+        let hex = AdsbSimulator::generate_identification_message(&aircraft);
+        let decoded = decode_message(&hex).unwrap();
+        assert_eq!((decoded.df, decoded.type_code), (17, 4));
+        let me = u64::from_str_radix(&hex[8..22], 16).unwrap();
+        assert_eq!((me >> 48) & 0x7, 3); // 类别 A3
+        let callsign: String = (0..8)
+            .map(|i| CALLSIGN_CHARSET[((me >> (42 - 6 * i)) & 0x3F) as usize] as char)
+            .collect();
+        assert_eq!(callsign, "CSN123  ");
+
+        // 每架飞机每 5 个 tick 发送一次识别消息
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for _ in 0..10 {
+            sim.update_positions();
+            for message in sim.generate_all_messages() {
+                if message.message_type == MessageType::Identification {
+                    *counts.entry(message.aircraft_id).or_insert(0) += 1;
+                }
+            }
+        }
+        assert_eq!(counts.len(), 3);
+        assert!(counts.values().all(|&count| count == 2));
     }
 }
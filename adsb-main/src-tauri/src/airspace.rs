@@ -0,0 +1,150 @@
+use crate::adsb::Aircraft;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// 禁飞区/限制区（多边形顶点为 (纬度, 经度)）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoFlyZone {
+    pub name: String,
+    pub polygon: Vec<(f64, f64)>,
+}
+
+/// 空域侵入事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AirspaceViolation {
+    pub aircraft_id: String,
+    pub callsign: String,
+    pub zone: String,
+    pub lat: f64,      // 进入点纬度
+    pub lng: f64,      // 进入点经度
+    pub altitude: f64, // 进入时高度 (ft)
+}
+
+/// 射线法判断点是否在多边形内
+pub fn point_in_polygon(lat: f64, lng: f64, polygon: &[(f64, f64)]) -> bool {
+    if polygon.len() < 3 {
+        return false;
+    }
+
+    let mut inside = false;
+    let mut j = polygon.len() - 1;
+    for i in 0..polygon.len() {
+        let (lat_i, lng_i) = polygon[i];
+        let (lat_j, lng_j) = polygon[j];
+        if (lat_i > lat) != (lat_j > lat)
+            && lng < (lng_j - lng_i) * (lat - lat_i) / (lat_j - lat_i) + lng_i
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// 空域监视器：跟踪每架飞机是否位于各禁飞区内，仅在进入时产生一次告警
+pub struct AirspaceMonitor {
+    zones: Vec<NoFlyZone>,
+    inside: HashSet<(String, usize)>,
+}
+
+impl AirspaceMonitor {
+    pub fn new(zones: Vec<NoFlyZone>) -> Self {
+        AirspaceMonitor {
+            zones,
+            inside: HashSet::new(),
+        }
+    }
+
+    /// 检查当前位置，返回本次新发生的侵入事件
+    pub fn check(&mut self, aircrafts: &[Aircraft]) -> Vec<AirspaceViolation> {
+        let mut violations = Vec::new();
+
+        for (index, zone) in self.zones.iter().enumerate() {
+            for aircraft in aircrafts {
+                let key = (aircraft.id.clone(), index);
+                if point_in_polygon(aircraft.lat, aircraft.lng, &zone.polygon) {
+                    if self.inside.insert(key) {
+                        violations.push(AirspaceViolation {
+                            aircraft_id: aircraft.id.clone(),
+                            callsign: aircraft.callsign.clone(),
+                            zone: zone.name.clone(),
+                            lat: aircraft.lat,
+                            lng: aircraft.lng,
+                            altitude: aircraft.altitude,
+                        });
+                    }
+                } else {
+                    // 离开后再次进入会重新告警
+                    self.inside.remove(&key);
+                }
+            }
+        }
+
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adsb::AdsbSimulator;
+
+    fn square(name: &str, lat: f64, lng: f64, half: f64) -> NoFlyZone {
+        NoFlyZone {
+            name: name.to_string(),
+            polygon: vec![
+                (lat - half, lng - half),
+                (lat - half, lng + half),
+                (lat + half, lng + half),
+                (lat + half, lng - half),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_point_in_polygon() {
+        let zone = square("A", 22.5, 114.0, 0.1);
+        assert!(point_in_polygon(22.5, 114.0, &zone.polygon));
+        assert!(!point_in_polygon(22.7, 114.0, &zone.polygon));
+        assert!(!point_in_polygon(22.5, 114.0, &zone.polygon[..2]));
+    }
+
+    #[test]
+    fn test_violation_fires_once_on_entry() {
+        let mut sim = AdsbSimulator::new(22.5431, 114.0579);
+        sim.generate_mock_aircrafts(2).unwrap();
+        let mut aircrafts = sim.get_aircrafts().clone();
+        aircrafts[0].lat = 30.0;
+        aircrafts[0].lng = 120.0;
+        aircrafts[1].lat = 0.0;
+        aircrafts[1].lng = 0.0;
+
+        let mut monitor = AirspaceMonitor::new(vec![
+            square("R1", 30.0, 120.0, 0.05),
+            square("R2", 10.0, 10.0, 0.05),
+        ]);
+
+        let violations = monitor.check(&aircrafts);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].aircraft_id, aircrafts[0].id);
+        assert_eq!(violations[0].zone, "R1");
+        assert_eq!((violations[0].lat, violations[0].lng), (30.0, 120.0));
+
+        // 仍在区域内，不重复告警
+        aircrafts[0].lat += 0.01;
+        assert!(monitor.check(&aircrafts).is_empty());
+
+        // 离开后再次进入
+        aircrafts[0].lat = 31.0;
+        assert!(monitor.check(&aircrafts).is_empty());
+        aircrafts[0].lat = 30.0;
+        assert_eq!(monitor.check(&aircrafts).len(), 1);
+
+        // 第二个区域同样生效
+        aircrafts[1].lat = 10.0;
+        aircrafts[1].lng = 10.0;
+        let violations = monitor.check(&aircrafts);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].zone, "R2");
+    }
+}
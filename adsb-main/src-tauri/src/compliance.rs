@@ -0,0 +1,107 @@
+use crate::adsb::Aircraft;
+use serde::{Deserialize, Serialize};
+
+/// 强制要求的 ADS-B 版本 (DO-260B)
+const REQUIRED_VERSION: u8 = 2;
+/// 最低 NIC (Rc < 0.2 nm)
+const MIN_NIC: u8 = 7;
+/// 最低 NACp (EPU < 0.05 nm)
+const MIN_NACP: u8 = 8;
+/// 要求的 SIL (每飞行小时失效概率 ≤ 1e-7)
+const REQUIRED_SIL: u8 = 3;
+
+/// 不符合强制要求的飞机及原因
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceIssue {
+    pub aircraft_id: String,
+    pub callsign: String,
+    pub reasons: Vec<String>,
+}
+
+/// 机队 ADS-B Out 强制要求符合性报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceReport {
+    pub total: usize,
+    pub compliant: usize,
+    pub compliant_fraction: f64, // 空机队时为 0
+    pub issues: Vec<ComplianceIssue>,
+}
+
+/// 按 ADS-B Out 强制要求（参照 14 CFR 91.227：版本 2、NIC ≥ 7、NACp ≥ 8、SIL 3）检查机队
+pub fn compliance_report(aircrafts: &[Aircraft]) -> ComplianceReport {
+    let issues: Vec<ComplianceIssue> = aircrafts
+        .iter()
+        .filter_map(|aircraft| {
+            let mut reasons = Vec::new();
+            if aircraft.version < REQUIRED_VERSION {
+                reasons.push(format!(
+                    "version {} below {}",
+                    aircraft.version, REQUIRED_VERSION
+                ));
+            }
+            if aircraft.nic < MIN_NIC {
+                reasons.push(format!("NIC {} below {}", aircraft.nic, MIN_NIC));
+            }
+            if aircraft.nacp < MIN_NACP {
+                reasons.push(format!("NACp {} below {}", aircraft.nacp, MIN_NACP));
+            }
+            if aircraft.sil < REQUIRED_SIL {
+                reasons.push(format!("SIL {} below {}", aircraft.sil, REQUIRED_SIL));
+            }
+            (!reasons.is_empty()).then(|| ComplianceIssue {
+                aircraft_id: aircraft.id.clone(),
+                callsign: aircraft.callsign.clone(),
+                reasons,
+            })
+        })
+        .collect();
+
+    let total = aircrafts.len();
+    let compliant = total - issues.len();
+    ComplianceReport {
+        total,
+        compliant,
+        compliant_fraction: if total > 0 {
+            compliant as f64 / total as f64
+        } else {
+            0.0
+        },
+        issues,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adsb::AdsbSimulator;
+
+    #[test]
+    fn test_compliant_fraction() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 54);
+        sim.generate_mock_aircrafts(4).unwrap();
+        let mut aircrafts = sim.get_aircrafts().clone();
+        for aircraft in &mut aircrafts {
+            aircraft.nic = 8;
+            aircraft.nacp = 9;
+        }
+        aircrafts[1].version = 1;
+        aircrafts[2].nacp = 6;
+        aircrafts[2].sil = 1;
+
+        let report = compliance_report(&aircrafts);
+        assert_eq!((report.total, report.compliant), (4, 2));
+        assert_eq!(report.compliant_fraction, 0.5);
+        let ids: Vec<&str> = report
+            .issues
+            .iter()
+            .map(|i| i.aircraft_id.as_str())
+            .collect();
+        assert_eq!(
+            ids,
+            vec![aircrafts[1].id.as_str(), aircrafts[2].id.as_str()]
+        );
+        assert_eq!(report.issues[1].reasons.len(), 2);
+
+        assert_eq!(compliance_report(&[]).compliant_fraction, 0.0);
+    }
+}
@@ -0,0 +1,1129 @@
+use crate::adsb::{cpr_nl, crc24};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::OnceLock;
+
+/// 长消息位数
+const LONG_MESSAGE_BITS: usize = 112;
+/// 短消息位数
+const SHORT_MESSAGE_BITS: usize = 56;
+
+/// 识别消息的 6 位字符集（`#` 为未定义编码）
+pub const CALLSIGN_CHARSET: &[u8; 64] =
+    b"#ABCDEFGHIJKLMNOPQRSTUVWXYZ##### ###############0123456789######";
+
+/// 解码后的 ADS-B 消息
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedMessage {
+    pub df: u8,        // 下行格式
+    pub ca: u8,        // DF17 为能力字段 CA，DF18 为控制字段 CF
+    pub icao: String,  // ICAO 地址 (DF18 为 AA 字段)
+    pub type_code: u8, // 类型码
+    pub source: MessageSource,
+    pub body: MessageBody,
+}
+
+/// 消息来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageSource {
+    /// DF17：机载应答机直接发送的 ADS-B
+    Adsb,
+    /// DF18 CF=0/1：非应答机设备发送的 ADS-B
+    NonTransponder,
+    /// DF18 CF=2/3/5：地面站广播的 TIS-B
+    TisB,
+    /// DF18 CF=6：地面站转发的 ADS-R
+    Adsr,
+    /// DF18 其他 CF 值
+    Unknown,
+    /// 非扩展电文的 Mode S 应答 (DF0/4/5/11)
+    ModeS,
+}
+
+impl MessageSource {
+    /// 根据 DF18 的 CF 字段判断来源
+    fn from_cf(cf: u8) -> Self {
+        match cf {
+            0 | 1 => MessageSource::NonTransponder,
+            2 | 3 | 5 => MessageSource::TisB,
+            6 => MessageSource::Adsr,
+            _ => MessageSource::Unknown,
+        }
+    }
+}
+
+/// 按类型码区分的消息内容
+#[derive(Debug, Clone, PartialEq)]
+pub enum MessageBody {
+    /// 识别消息 (TC 1-4)
+    Identification {
+        category: u8,     // 发射机类别子字段
+        callsign: String, // 航班号，去除尾部空格
+    },
+    /// 空中位置消息 (TC 9-18)
+    Position {
+        altitude: Option<f64>, // 气压高度 (ft)，无效编码时为 None
+        odd: bool,             // CPR 奇帧
+        lat_cpr: u32,          // 17 位 CPR 纬度
+        lng_cpr: u32,          // 17 位 CPR 经度
+    },
+    /// 地面位置消息 (TC 5-8)：不含高度，运动信息为地面移动速度与地面航迹
+    SurfacePosition {
+        ground_speed: Option<f64>, // 地面移动速度 (kts)，不可用时为 None
+        track: Option<f64>,        // 地面航迹 (0-360°)，状态位无效时为 None
+        odd: bool,                 // CPR 奇帧
+        lat_cpr: u32,              // 17 位 CPR 纬度
+        lng_cpr: u32,              // 17 位 CPR 经度
+    },
+    /// 空中速度消息 (TC 19)：子类型 1/2 为地速与航迹角，3/4 为空速与航向
+    Velocity {
+        speed: Option<f64>,         // 速度 (kts)，无效时为 None
+        heading: Option<f64>,       // 航迹角或航向 (0-360°)，无效时为 None
+        nacv: u8,                   // 速度精度类别 NACv (版本 0 为 NUCr)
+        vertical_rate: Option<f64>, // 垂直速率 (ft/min，上升为正)，无效时为 None
+    },
+    /// 飞机状态消息 (TC 28 子类型 1)
+    Status {
+        emergency: u8,  // 紧急/优先状态 (0 为无紧急情况)
+        squawk: String, // 四位八进制应答机编码
+    },
+    /// 运行状态消息 (TC 31)
+    OperationalStatus {
+        surface: bool,         // 子类型 1 为地面
+        capability_class: u16, // 16 位能力类别编码
+        operational_mode: u16, // 16 位运行模式编码
+        version: u8,           // ADS-B 版本号
+        nacp: u8,              // 位置精度类别
+        sil: u8,               // 源完整性等级
+    },
+    /// 高度应答 (DF0/4)
+    Altitude {
+        altitude: Option<f64>, // 气压高度 (ft)，无效编码时为 None
+    },
+    /// 识别码应答 (DF5)
+    Identity {
+        squawk: String, // 四位八进制应答机编码
+    },
+    /// 尚未支持解析的类型
+    Unknown,
+}
+
+/// 解码错误
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeError {
+    /// 包含非十六进制字符
+    InvalidHex,
+    /// 长度不符（十六进制字符数）
+    InvalidLength(usize),
+    /// 不支持的下行格式
+    UnsupportedFormat(u8),
+    /// 长度与下行格式不符（十六进制字符数）
+    LengthMismatch { df: u8, expected: usize, actual: usize },
+    /// CRC 校验失败且无法纠正，附带校验余数 (syndrome)
+    CrcMismatch(u32),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidHex => write!(f, "message contains non-hex characters"),
+            DecodeError::InvalidLength(len) => {
+                write!(f, "invalid message length: {} hex chars", len)
+            }
+            DecodeError::UnsupportedFormat(df) => write!(f, "unsupported downlink format DF{}", df),
+            DecodeError::LengthMismatch {
+                df,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "DF{} requires {} hex chars, got {}",
+                df, expected, actual
+            ),
+            DecodeError::CrcMismatch(syndrome) => {
+                write!(f, "CRC mismatch (syndrome {:06X})", syndrome)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// 下行格式对应的消息位数，未知格式返回 None
+pub fn message_bits(df: u8) -> Option<usize> {
+    match df {
+        0 | 4 | 5 | 11 => Some(SHORT_MESSAGE_BITS),
+        16..=21 | 24 => Some(LONG_MESSAGE_BITS),
+        _ => None,
+    }
+}
+
+/// 解码 Mode S 消息
+///
+/// 根据 DF 判断应为 56 位 (14 个十六进制字符) 还是 112 位 (28 个十六进制字符)，
+/// 长度不符时返回 `LengthMismatch`。长消息目前支持 DF17/18 扩展电文，
+/// 短消息支持 DF0/4/5/11。
+pub fn decode_message(hex: &str) -> Result<DecodedMessage, DecodeError> {
+    let (msg, bits) = parse_message(hex)?;
+    if bits == SHORT_MESSAGE_BITS {
+        return decode_short_message(msg as u64);
+    }
+
+    let df = ((msg >> 107) & 0x1F) as u8;
+    let ca = ((msg >> 104) & 0x7) as u8;
+    let source = match df {
+        17 => MessageSource::Adsb,
+        18 => MessageSource::from_cf(ca),
+        _ => return Err(DecodeError::UnsupportedFormat(df)),
+    };
+    let icao = format!("{:06X}", (msg >> 80) & 0xFF_FFFF);
+    let me = ((msg >> 24) & 0xFF_FFFF_FFFF_FFFF) as u64;
+    let type_code = (me >> 51) as u8;
+
+    // TIS-B 粗略格式 (CF=3) 的 ME 字段布局不同，只解析报头
+    let body = match type_code {
+        _ if df == 18 && ca == 3 => MessageBody::Unknown,
+        1..=4 => MessageBody::Identification {
+            category: ((me >> 48) & 0x7) as u8,
+            callsign: decode_callsign(me),
+        },
+        5..=8 => MessageBody::SurfacePosition {
+            ground_speed: decode_ground_movement((me >> 44) & 0x7F),
+            track: ((me >> 43) & 1 == 1).then(|| ((me >> 36) & 0x7F) as f64 * 360.0 / 128.0),
+            odd: (me >> 34) & 1 == 1,
+            lat_cpr: ((me >> 17) & 0x1FFFF) as u32,
+            lng_cpr: (me & 0x1FFFF) as u32,
+        },
+        9..=18 => MessageBody::Position {
+            altitude: decode_altitude(((me >> 36) & 0xFFF) as u16),
+            odd: (me >> 34) & 1 == 1,
+            lat_cpr: ((me >> 17) & 0x1FFFF) as u32,
+            lng_cpr: (me & 0x1FFFF) as u32,
+        },
+        19 => decode_velocity(me),
+        28 if (me >> 48) & 0x7 == 1 => MessageBody::Status {
+            emergency: ((me >> 45) & 0x7) as u8,
+            squawk: decode_squawk(((me >> 32) & 0x1FFF) as u16),
+        },
+        31 => MessageBody::OperationalStatus {
+            surface: (me >> 48) & 0x7 == 1,
+            capability_class: ((me >> 32) & 0xFFFF) as u16,
+            operational_mode: ((me >> 16) & 0xFFFF) as u16,
+            version: ((me >> 13) & 0x7) as u8,
+            nacp: ((me >> 8) & 0xF) as u8,
+            sil: ((me >> 4) & 0x3) as u8,
+        },
+        _ => MessageBody::Unknown,
+    };
+
+    Ok(DecodedMessage {
+        df,
+        ca,
+        icao,
+        type_code,
+        source,
+        body,
+    })
+}
+
+/// 解码识别消息中的 8 个 6 位字符
+fn decode_callsign(me: u64) -> String {
+    let callsign: String = (0..8)
+        .map(|i| CALLSIGN_CHARSET[((me >> (42 - 6 * i)) & 0x3F) as usize] as char)
+        .collect();
+    callsign.trim_end().to_string()
+}
+
+/// 解码空中速度消息 (TC 19)，未知子类型返回 `Unknown`
+fn decode_velocity(me: u64) -> MessageBody {
+    let subtype = (me >> 48) & 0x7;
+    // 子类型 2/4 为超音速，速度单位为 4 kts
+    let scale = if matches!(subtype, 2 | 4) { 4.0 } else { 1.0 };
+    let magnitude = |raw: u64| (raw != 0).then(|| (raw - 1) as f64 * scale);
+
+    let (speed, heading) = match subtype {
+        1 | 2 => {
+            let component = |shift: u32| {
+                let value = magnitude((me >> shift) & 0x3FF)?;
+                Some(if (me >> (shift + 10)) & 1 == 1 {
+                    -value
+                } else {
+                    value
+                })
+            };
+            match (component(32), component(21)) {
+                (Some(east), Some(north)) => (
+                    Some(east.hypot(north)),
+                    Some(east.atan2(north).to_degrees().rem_euclid(360.0)),
+                ),
+                _ => (None, None),
+            }
+        }
+        3 | 4 => {
+            let heading =
+                ((me >> 42) & 1 == 1).then(|| ((me >> 32) & 0x3FF) as f64 * 360.0 / 1024.0);
+            (magnitude((me >> 21) & 0x3FF), heading)
+        }
+        _ => return MessageBody::Unknown,
+    };
+    let vertical_rate = match (me >> 10) & 0x1FF {
+        0 => None,
+        raw => {
+            let rate = (raw - 1) as f64 * 64.0;
+            Some(if (me >> 19) & 1 == 1 { -rate } else { rate })
+        }
+    };
+
+    MessageBody::Velocity {
+        speed,
+        heading,
+        nacv: ((me >> 43) & 0x7) as u8,
+        vertical_rate,
+    }
+}
+
+/// 解码 56 位短消息
+fn decode_short_message(msg: u64) -> Result<DecodedMessage, DecodeError> {
+    let df = ((msg >> 51) & 0x1F) as u8;
+    let ca = ((msg >> 48) & 0x7) as u8;
+    let code13 = ((msg >> 24) & 0x1FFF) as u16;
+
+    // DF11 直接携带地址；其余格式的校验字段为 CRC 与地址的异或 (AP)
+    let icao = match df {
+        11 => (msg >> 24) as u32 & 0xFF_FFFF,
+        _ => crc24(&((msg >> 24) as u32).to_be_bytes()) ^ (msg as u32 & 0xFF_FFFF),
+    };
+    let body = match df {
+        0 | 4 => MessageBody::Altitude {
+            altitude: decode_ac13(code13),
+        },
+        5 => MessageBody::Identity {
+            squawk: decode_squawk(code13),
+        },
+        11 => MessageBody::Unknown,
+        _ => return Err(DecodeError::UnsupportedFormat(df)),
+    };
+
+    Ok(DecodedMessage {
+        df,
+        ca,
+        icao: format!("{:06X}", icao),
+        type_code: 0,
+        source: MessageSource::ModeS,
+        body,
+    })
+}
+
+/// 消息字段说明，位序号从 1 开始、最高位在前（与 DO-260B 文档一致）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MessageField {
+    pub name: String,
+    pub start_bit: usize, // 起始位 (含)
+    pub end_bit: usize,   // 结束位 (含)
+    pub raw: u64,         // 字段原始值
+    pub meaning: String,  // 解码含义
+}
+
+/// 逐字段拆解 DF17/18 长消息，字段按位序排列并覆盖全部 112 位
+pub fn explain_message(hex: &str) -> Result<Vec<MessageField>, DecodeError> {
+    let decoded = decode_message(hex)?;
+    if !matches!(decoded.df, 17 | 18) {
+        return Err(DecodeError::UnsupportedFormat(decoded.df));
+    }
+    let msg = parse_long_message(hex)?;
+    let raw = |start: usize, end: usize| {
+        ((msg >> (LONG_MESSAGE_BITS - end)) & ((1u128 << (end - start + 1)) - 1)) as u64
+    };
+    let mut fields = Vec::new();
+    let mut push = |name: &str, start: usize, end: usize, meaning: String| {
+        fields.push(MessageField {
+            name: name.to_string(),
+            start_bit: start,
+            end_bit: end,
+            raw: raw(start, end),
+            meaning,
+        });
+    };
+
+    let df_meaning = match decoded.df {
+        17 => "DF17 extended squitter (transponder)".to_string(),
+        _ => format!("DF18 extended squitter ({:?})", decoded.source),
+    };
+    push("DF", 1, 5, df_meaning);
+    if decoded.df == 17 {
+        push("CA", 6, 8, format!("capability {}", decoded.ca));
+    } else {
+        push("CF", 6, 8, format!("control field {} ({:?})", decoded.ca, decoded.source));
+    }
+    push("ICAO", 9, 32, format!("address {}", decoded.icao));
+    push("TC", 33, 37, describe_type_code(decoded.type_code).to_string());
+
+    match (decoded.type_code, &decoded.body) {
+        (1..=4, _) => {
+            push("CAT", 38, 40, format!("emitter category {}", raw(38, 40)));
+            for i in 0..8 {
+                let start = 41 + i * 6;
+                let c = CALLSIGN_CHARSET[raw(start, start + 5) as usize] as char;
+                push(&format!("C{}", i + 1), start, start + 5, format!("'{}'", c));
+            }
+        }
+        (5..=8, MessageBody::SurfacePosition { ground_speed, .. }) => {
+            let movement = match ground_speed {
+                Some(kts) => format!("{} kts", kts),
+                None => "movement unavailable".to_string(),
+            };
+            push("MOV", 38, 44, movement);
+            push("S", 45, 45, format!("ground track valid: {}", raw(45, 45) == 1));
+            push("TRK", 46, 52, format!("{:.1} deg", raw(46, 52) as f64 * 360.0 / 128.0));
+            push("T", 53, 53, format!("UTC synchronized: {}", raw(53, 53) == 1));
+            let odd = raw(54, 54) == 1;
+            push("F", 54, 54, if odd { "odd CPR frame" } else { "even CPR frame" }.to_string());
+            push("LAT-CPR", 55, 71, format!("{} / 131072", raw(55, 71)));
+            push("LON-CPR", 72, 88, format!("{} / 131072", raw(72, 88)));
+        }
+        (9..=18, MessageBody::Position { altitude, .. }) => {
+            push("SS", 38, 39, format!("surveillance status {}", raw(38, 39)));
+            push("SAF", 40, 40, format!("NIC supplement-B {}", raw(40, 40)));
+            let alt_meaning = match altitude {
+                Some(ft) => format!("{} ft", ft),
+                None => "altitude unavailable".to_string(),
+            };
+            push("ALT", 41, 52, alt_meaning);
+            push("T", 53, 53, format!("UTC synchronized: {}", raw(53, 53) == 1));
+            let odd = raw(54, 54) == 1;
+            push("F", 54, 54, if odd { "odd CPR frame" } else { "even CPR frame" }.to_string());
+            push("LAT-CPR", 55, 71, format!("{} / 131072", raw(55, 71)));
+            push("LON-CPR", 72, 88, format!("{} / 131072", raw(72, 88)));
+        }
+        (19, _) => {
+            let subtype = raw(38, 40);
+            push("ST", 38, 40, format!("velocity subtype {}", subtype));
+            push("IC", 41, 41, format!("intent change flag {}", raw(41, 41)));
+            push("IFR", 42, 42, format!("IFR capability flag {}", raw(42, 42)));
+            push("NACv", 43, 45, format!("velocity accuracy category {}", raw(43, 45)));
+            if matches!(subtype, 1 | 2) {
+                let direction = |bit: usize, positive: &str, negative: &str| {
+                    if raw(bit, bit) == 0 { positive } else { negative }.to_string()
+                };
+                push("EW-DIR", 46, 46, direction(46, "east", "west"));
+                push("EW-VEL", 47, 56, speed_meaning(raw(47, 56)));
+                push("NS-DIR", 57, 57, direction(57, "north", "south"));
+                push("NS-VEL", 58, 67, speed_meaning(raw(58, 67)));
+            } else {
+                push("HDG-S", 46, 46, format!("heading available: {}", raw(46, 46) == 1));
+                push("HDG", 47, 56, format!("{:.1} deg", raw(47, 56) as f64 * 360.0 / 1024.0));
+                let kind = if raw(57, 57) == 0 { "IAS" } else { "TAS" };
+                push("AS-T", 57, 57, kind.to_string());
+                push("AS", 58, 67, speed_meaning(raw(58, 67)));
+            }
+            let vr_source = if raw(68, 68) == 0 { "GNSS" } else { "barometric" };
+            push("VR-SRC", 68, 68, format!("{} vertical rate", vr_source));
+            let vr_sign = if raw(69, 69) == 0 { "climb" } else { "descent" };
+            push("VR-S", 69, 69, vr_sign.to_string());
+            let vr = match raw(70, 78) {
+                0 => "vertical rate unavailable".to_string(),
+                n => format!("{} ft/min", (n - 1) * 64),
+            };
+            push("VR", 70, 78, vr);
+            push("RES", 79, 80, "reserved".to_string());
+            let diff_sign = if raw(81, 81) == 0 { "above" } else { "below" };
+            push("GH-S", 81, 81, format!("GNSS {} baro", diff_sign));
+            let diff = match raw(82, 88) {
+                0 => "difference unavailable".to_string(),
+                n => format!("{} ft", (n - 1) * 25),
+            };
+            push("GH-DIFF", 82, 88, diff);
+        }
+        _ => push("ME", 38, 88, "message data (not broken down)".to_string()),
+    }
+
+    let syndrome = crc24(&msg.to_be_bytes()[2..]);
+    let parity = if syndrome == 0 {
+        "CRC parity OK".to_string()
+    } else {
+        format!("CRC mismatch (syndrome {:06X})", syndrome)
+    };
+    push("PI", 89, 112, parity);
+    Ok(fields)
+}
+
+/// 比特串的显示顺序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BitOrder {
+    /// 每字节最高位在前，即空中传输顺序
+    #[default]
+    MsbFirst,
+    /// 每字节最低位在前（常见于按字节内位序号读取的代码）
+    LsbFirst,
+}
+
+/// 消息的多种表示形式，比特串按字节分组，并在下一行以 `^` 标出每个字段的起始位
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MessageRepresentation {
+    pub hex: String,
+    pub bytes: Vec<u8>,
+    pub bit_order: BitOrder,
+    pub bits: String,          // 以空格分隔字节的比特串
+    pub field_markers: String, // 与 bits 等长，字段起始位处为 `^`
+}
+
+/// 以十六进制、字节数组和指定位序的比特串表示 DF17/18 消息，并标出各字段起始位置
+pub fn message_representation(
+    hex: &str,
+    bit_order: BitOrder,
+) -> Result<MessageRepresentation, DecodeError> {
+    let fields = explain_message(hex)?;
+    let hex = hex.trim().to_uppercase();
+    let bytes: Vec<u8> = parse_long_message(&hex)?.to_be_bytes()[2..].to_vec();
+
+    // 字节内第 i 位（0 为最高位）的显示列：每字节 8 列加 1 个分隔空格
+    let column = |byte: usize, bit: usize| {
+        let offset = match bit_order {
+            BitOrder::MsbFirst => bit,
+            BitOrder::LsbFirst => 7 - bit,
+        };
+        byte * 9 + offset
+    };
+    let width = bytes.len() * 9 - 1;
+    let mut bits = vec![b' '; width];
+    for (i, byte) in bytes.iter().enumerate() {
+        for bit in 0..8 {
+            bits[column(i, bit)] = if byte & (0x80 >> bit) != 0 { b'1' } else { b'0' };
+        }
+    }
+    let mut markers = vec![b' '; width];
+    for field in &fields {
+        let index = field.start_bit - 1;
+        markers[column(index / 8, index % 8)] = b'^';
+    }
+
+    Ok(MessageRepresentation {
+        hex,
+        bytes,
+        bit_order,
+        bits: String::from_utf8(bits).unwrap_or_default(),
+        field_markers: String::from_utf8(markers)
+            .unwrap_or_default()
+            .trim_end()
+            .to_string(),
+    })
+}
+
+/// 类型码含义
+fn describe_type_code(type_code: u8) -> &'static str {
+    match type_code {
+        1..=4 => "aircraft identification",
+        5..=8 => "surface position",
+        9..=18 => "airborne position (barometric altitude)",
+        19 => "airborne velocity",
+        20..=22 => "airborne position (GNSS height)",
+        28 => "aircraft status",
+        29 => "target state and status",
+        31 => "aircraft operational status",
+        _ => "reserved",
+    }
+}
+
+/// 地面移动速度 (kts)：0 与 125-127 为不可用，1 为静止，124 表示 175 kts 以上
+fn decode_ground_movement(raw: u64) -> Option<f64> {
+    // (起始编码, 区间下限, 步长)
+    const BANDS: [(u64, f64, f64); 7] = [
+        (2, 0.125, 0.146),
+        (9, 1.0, 0.25),
+        (13, 2.0, 0.5),
+        (39, 15.0, 1.0),
+        (94, 70.0, 2.0),
+        (109, 100.0, 5.0),
+        (124, 175.0, 0.0),
+    ];
+    match raw {
+        0 | 125.. => None,
+        1 => Some(0.0),
+        _ => {
+            let &(base, low, step) = BANDS.iter().rev().find(|(base, _, _)| raw >= *base)?;
+            Some(low + (raw - base) as f64 * step)
+        }
+    }
+}
+
+/// 速度分量字段含义（0 表示不可用，其余为值减 1，单位 kts）
+fn speed_meaning(raw: u64) -> String {
+    match raw {
+        0 => "speed unavailable".to_string(),
+        n => format!("{} kts", n - 1),
+    }
+}
+
+/// 纠错解码结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorrectedMessage {
+    pub message: DecodedMessage,
+    pub hex: String,        // 纠正后的消息
+    pub corrected_bits: u8, // 纠正的位数 (0 表示原消息校验通过)
+}
+
+/// 计算消息的 CRC 校验余数，0 表示校验通过
+///
+/// 短消息中 DF11 的余数为询问机识别码（断续振荡为 0），DF0/4/5 的余数为飞机地址。
+pub fn crc_syndrome(hex: &str) -> Result<u32, DecodeError> {
+    let (msg, bits) = parse_message(hex)?;
+    Ok(crc24(&msg.to_be_bytes()[16 - bits / 8..]))
+}
+
+/// 带 CRC 纠错的解码
+///
+/// 校验余数非零时，利用 CRC 的线性性质查找能产生相同余数的 1 位
+/// （`max_bits` >= 2 时还包括 2 位）错误，翻转这些位后重新解码。
+pub fn decode_message_with_correction(
+    hex: &str,
+    max_bits: u8,
+) -> Result<CorrectedMessage, DecodeError> {
+    let msg = parse_long_message(hex)?;
+    let syndrome = crc24(&msg.to_be_bytes()[2..]);
+
+    let flip = if syndrome == 0 {
+        Some(0u128)
+    } else {
+        let table = single_bit_syndromes();
+        let single = || {
+            table
+                .iter()
+                .position(|&s| s == syndrome)
+                .map(bit_mask)
+        };
+        let double = || {
+            (0..LONG_MESSAGE_BITS).find_map(|i| {
+                (i + 1..LONG_MESSAGE_BITS)
+                    .find(|&j| table[i] ^ table[j] == syndrome)
+                    .map(|j| bit_mask(i) | bit_mask(j))
+            })
+        };
+        match max_bits {
+            0 => None,
+            1 => single(),
+            _ => single().or_else(double),
+        }
+    };
+
+    let flip = flip.ok_or(DecodeError::CrcMismatch(syndrome))?;
+    let corrected_hex = format!("{:028X}", msg ^ flip);
+    Ok(CorrectedMessage {
+        message: decode_message(&corrected_hex)?,
+        hex: corrected_hex,
+        corrected_bits: flip.count_ones() as u8,
+    })
+}
+
+/// 第 i 位 (0 为最高位) 的掩码
+fn bit_mask(i: usize) -> u128 {
+    1u128 << (LONG_MESSAGE_BITS - 1 - i)
+}
+
+/// 每个单比特错误对应的校验余数
+fn single_bit_syndromes() -> &'static [u32; LONG_MESSAGE_BITS] {
+    static TABLE: OnceLock<[u32; LONG_MESSAGE_BITS]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = [0u32; LONG_MESSAGE_BITS];
+        for (i, syndrome) in table.iter_mut().enumerate() {
+            *syndrome = crc24(&bit_mask(i).to_be_bytes()[2..]);
+        }
+        table
+    })
+}
+
+/// 校验格式并解析消息，返回 (消息值, 位数)
+fn parse_message(hex: &str) -> Result<(u128, usize), DecodeError> {
+    let hex = hex.trim();
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(DecodeError::InvalidHex);
+    }
+    if hex.len() != SHORT_MESSAGE_BITS / 4 && hex.len() != LONG_MESSAGE_BITS / 4 {
+        return Err(DecodeError::InvalidLength(hex.len()));
+    }
+
+    let msg = u128::from_str_radix(hex, 16).map_err(|_| DecodeError::InvalidHex)?;
+    let bits = hex.len() * 4;
+    // DF24 只用前两位标识
+    let df = match (msg >> (bits - 5)) as u8 {
+        df if df >= 24 => 24,
+        df => df,
+    };
+    let expected = message_bits(df).ok_or(DecodeError::UnsupportedFormat(df))?;
+    if expected != bits {
+        return Err(DecodeError::LengthMismatch {
+            df,
+            expected: expected / 4,
+            actual: hex.len(),
+        });
+    }
+    Ok((msg, bits))
+}
+
+/// 校验格式并解析为 112 位整数
+fn parse_long_message(hex: &str) -> Result<u128, DecodeError> {
+    let hex = hex.trim();
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(DecodeError::InvalidHex);
+    }
+    if hex.len() != 28 {
+        return Err(DecodeError::InvalidLength(hex.len()));
+    }
+    u128::from_str_radix(hex, 16).map_err(|_| DecodeError::InvalidHex)
+}
+
+/// 解码空中位置消息中的 12 位高度字段 (ft)
+///
+/// Q 位 (0x010) 置位时为 25 ft 分辨率的二进制编码；
+/// 否则为 100 ft 分辨率的 Gillham 格雷码。全零表示高度不可用。
+pub fn decode_altitude(ac12: u16) -> Option<f64> {
+    let ac12 = ac12 & 0xFFF;
+    if ac12 == 0 {
+        return None;
+    }
+
+    if ac12 & 0x010 != 0 {
+        let n = ((ac12 & 0xFE0) >> 1) | (ac12 & 0x00F);
+        return Some(n as f64 * 25.0 - 1000.0);
+    }
+
+    // 插入 M 位 (恒为 0) 还原为 13 位 AC 字段后按 Gillham 码解码
+    let ac13 = ((ac12 & 0xFC0) << 1) | (ac12 & 0x03F);
+    gillham_to_altitude(ac13).map(|hundreds| hundreds as f64 * 100.0)
+}
+
+/// 解码 DF0/4 中的 13 位 AC 字段 (ft)
+///
+/// M 位 (0x040) 置位表示米制高度，暂不支持。
+pub fn decode_ac13(ac13: u16) -> Option<f64> {
+    if ac13 & 0x040 != 0 {
+        return None;
+    }
+    // 去掉 M 位还原为 12 位高度字段
+    decode_altitude(((ac13 & 0x1F80) >> 1) | (ac13 & 0x3F))
+}
+
+/// 由一对空中 CPR 偶帧/奇帧 (YZ, XZ) 做全局解码，返回 (纬度, 经度)
+///
+/// `latest_odd` 表示较新的一帧是否为奇帧，结果对应较新一帧的位置；
+/// 两帧跨越经度分区边界时无法解码，返回 None。
+pub fn cpr_decode_airborne(
+    even: (u32, u32),
+    odd: (u32, u32),
+    latest_odd: bool,
+) -> Option<(f64, f64)> {
+    const NZ: f64 = 15.0;
+    const SCALE: f64 = 131072.0;
+    let modulo = |x: f64, y: f64| x - y * (x / y).floor();
+    let (lat_even, lng_even) = (even.0 as f64 / SCALE, even.1 as f64 / SCALE);
+    let (lat_odd, lng_odd) = (odd.0 as f64 / SCALE, odd.1 as f64 / SCALE);
+
+    let d_lat_even = 360.0 / (4.0 * NZ);
+    let d_lat_odd = 360.0 / (4.0 * NZ - 1.0);
+    let j = (59.0 * lat_even - 60.0 * lat_odd + 0.5).floor();
+    let wrap = |lat: f64| if lat >= 270.0 { lat - 360.0 } else { lat };
+    let r_lat_even = wrap(d_lat_even * (modulo(j, 60.0) + lat_even));
+    let r_lat_odd = wrap(d_lat_odd * (modulo(j, 59.0) + lat_odd));
+    if cpr_nl(r_lat_even) != cpr_nl(r_lat_odd) {
+        return None;
+    }
+
+    let (lat, nl) = if latest_odd {
+        (r_lat_odd, cpr_nl(r_lat_odd))
+    } else {
+        (r_lat_even, cpr_nl(r_lat_even))
+    };
+    let m = (lng_even * (nl - 1.0) - lng_odd * nl + 0.5).floor();
+    let n = (nl - latest_odd as u8 as f64).max(1.0);
+    let lng_frac = if latest_odd { lng_odd } else { lng_even };
+    let lng = 360.0 / n * (modulo(m, n) + lng_frac);
+    let lng = if lng >= 180.0 { lng - 360.0 } else { lng };
+    Some((lat, lng))
+}
+
+/// 解码 13 位识别码字段为四位八进制应答机编码
+fn decode_squawk(id13: u16) -> String {
+    // 位序：C1 A1 C2 A2 C4 A4 X B1 D1 B2 D2 B4 D4
+    let bit = |mask: u16| (id13 & mask != 0) as u16;
+    let a = bit(0x0800) | bit(0x0200) << 1 | bit(0x0080) << 2;
+    let b = bit(0x0020) | bit(0x0008) << 1 | bit(0x0002) << 2;
+    let c = bit(0x1000) | bit(0x0400) << 1 | bit(0x0100) << 2;
+    let d = bit(0x0010) | bit(0x0004) << 1 | bit(0x0001) << 2;
+    format!("{}{}{}{}", a, b, c, d)
+}
+
+/// 13 位 AC 字段 (Gillham 编码) 转高度，单位 100 ft
+fn gillham_to_altitude(ac13: u16) -> Option<i32> {
+    // AC 字段位序：C1 A1 C2 A2 C4 A4 M B1 Q B2 D2 B4 D4
+    let bit = |mask: u16| ac13 & mask != 0;
+    let (c1, a1, c2, a2, c4, a4) = (
+        bit(0x1000),
+        bit(0x0800),
+        bit(0x0400),
+        bit(0x0200),
+        bit(0x0100),
+        bit(0x0080),
+    );
+    let (b1, d1, b2, d2, b4, d4) = (
+        bit(0x0020),
+        bit(0x0010),
+        bit(0x0008),
+        bit(0x0004),
+        bit(0x0002),
+        bit(0x0001),
+    );
+
+    // D1 不用于高度；C 位全零为非法码
+    if d1 || !(c1 || c2 || c4) {
+        return None;
+    }
+
+    let mut one_hundreds: i32 = 0;
+    if c1 {
+        one_hundreds ^= 0x7;
+    }
+    if c2 {
+        one_hundreds ^= 0x3;
+    }
+    if c4 {
+        one_hundreds ^= 0x1;
+    }
+    // 7 与 5 互换
+    if one_hundreds & 5 == 5 {
+        one_hundreds ^= 2;
+    }
+    if one_hundreds > 5 {
+        return None;
+    }
+
+    let mut five_hundreds: i32 = 0;
+    for (set, mask) in [
+        (d2, 0x0FF),
+        (d4, 0x07F),
+        (a1, 0x03F),
+        (a2, 0x01F),
+        (a4, 0x00F),
+        (b1, 0x007),
+        (b2, 0x003),
+        (b4, 0x001),
+    ] {
+        if set {
+            five_hundreds ^= mask;
+        }
+    }
+
+    if five_hundreds & 1 != 0 {
+        one_hundreds = 6 - one_hundreds;
+    }
+
+    Some(five_hundreds * 5 + one_hundreds - 13)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_decode_known_position_altitude() {
+        // 公开的 DF17 空中位置样例，高度 38000 ft
+        let decoded = decode_message("8D40621D58C382D690C8AC2863A7").unwrap();
+        assert_eq!(decoded.df, 17);
+        assert_eq!(decoded.icao, "40621D");
+        assert_eq!(decoded.type_code, 11);
+        match decoded.body {
+            MessageBody::Position { altitude, .. } => {
+                assert!((altitude.unwrap() - 38000.0).abs() <= 25.0);
+            }
+            other => panic!("expected position, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_tisb_position() {
+        use crate::adsb::AdsbSimulator;
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 1);
+        sim.generate_mock_aircrafts(1).unwrap();
+        let aircraft = &sim.get_aircrafts()[0];
+
+        // 将生成的 DF17 位置消息改写为 DF18 CF=2 (精细 TIS-B)
+        let df17 = AdsbSimulator::generate_position_message(aircraft, false);
+        let mut msg = u128::from_str_radix(&df17, 16).unwrap();
+        msg = (msg & !(0xFFu128 << 104)) | ((18u128 << 3 | 2) << 104);
+        let df18 = format!("{:028X}", msg);
+
+        let decoded = decode_message(&df18).unwrap();
+        assert_eq!(decoded.df, 18);
+        assert_eq!(decoded.ca, 2);
+        assert_eq!(decoded.source, MessageSource::TisB);
+        assert_eq!(decoded.icao, aircraft.id);
+        assert!(matches!(decoded.body, MessageBody::Position { .. }));
+
+        let direct = decode_message(&df17).unwrap();
+        assert_eq!(direct.source, MessageSource::Adsb);
+    }
+
+    #[test]
+    fn test_decode_source_types() {
+        use crate::adsb::{AdsbSimulator, AdsbSource};
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 1);
+        sim.generate_mock_aircrafts(3).unwrap();
+        let ids: Vec<String> = sim.get_aircrafts().iter().map(|a| a.id.clone()).collect();
+        assert!(sim.set_source(&ids[1], AdsbSource::TisB));
+        assert!(sim.set_source(&ids[2], AdsbSource::Adsr));
+
+        let expected = [
+            (17, 5, MessageSource::Adsb),
+            (18, 2, MessageSource::TisB),
+            (18, 6, MessageSource::Adsr),
+        ];
+        for (aircraft, (df, cf, source)) in sim.get_aircrafts().iter().zip(expected) {
+            for hex in [
+                AdsbSimulator::generate_position_message(aircraft, false),
+                AdsbSimulator::generate_velocity_message(aircraft),
+            ] {
+                let decoded = decode_message(&hex).unwrap();
+                assert_eq!((decoded.df, decoded.ca, decoded.source), (df, cf, source));
+                assert_eq!(decoded.icao, aircraft.id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_generated_messages_round_trip() {
+        use crate::adsb::{AdsbSimulator, MessageType};
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 58);
+        sim.generate_mock_aircrafts(6).unwrap();
+        let mut seen = BTreeSet::new();
+        for _ in 0..5 {
+            sim.update_positions();
+            for message in sim.generate_all_messages() {
+                let aircraft = sim
+                    .get_aircrafts()
+                    .iter()
+                    .find(|a| a.id == message.aircraft_id)
+                    .unwrap();
+                let decoded = decode_message(&message.hex_message).unwrap();
+                assert_eq!(decoded.icao, aircraft.id);
+                match (message.message_type, decoded.body) {
+                    (MessageType::Position, MessageBody::Position { altitude, .. }) => {
+                        assert!((altitude.unwrap() - aircraft.altitude).abs() <= 12.5);
+                    }
+                    (
+                        MessageType::Velocity,
+                        MessageBody::Velocity {
+                            speed,
+                            heading,
+                            nacv,
+                            vertical_rate,
+                        },
+                    ) => {
+                        assert_eq!(nacv, aircraft.nacv);
+                        assert!((speed.unwrap() - aircraft.ground_speed).abs() < 1.0);
+                        assert!((vertical_rate.unwrap() - aircraft.vertical_rate).abs() <= 32.0);
+                        let diff = (heading.unwrap() - aircraft.track).rem_euclid(360.0);
+                        assert!(diff.min(360.0 - diff) < 0.5);
+                    }
+                    (MessageType::Identification, MessageBody::Identification { callsign, .. }) => {
+                        assert_eq!(callsign, aircraft.callsign.trim());
+                    }
+                    (MessageType::AllCall, MessageBody::Unknown) => assert_eq!(decoded.df, 11),
+                    (MessageType::Status, MessageBody::Status { emergency, squawk }) => {
+                        assert_eq!(emergency, 0);
+                        assert_eq!(squawk, format!("{:04o}", aircraft.squawk));
+                    }
+                    (
+                        MessageType::OperationalStatus,
+                        MessageBody::OperationalStatus {
+                            version,
+                            nacp,
+                            sil,
+                            ..
+                        },
+                    ) => {
+                        let expected = (aircraft.version, aircraft.nacp, aircraft.sil);
+                        assert_eq!((version, nacp, sil), expected);
+                    }
+                    (message_type, body) => {
+                        panic!("{:?} message decoded as {:?}", message_type, body)
+                    }
+                }
+                seen.insert(message.message_type);
+            }
+        }
+        assert!(seen.contains(&MessageType::Identification));
+
+        assert_eq!(
+            decode_message("8D40621D58C382"),
+            Err(DecodeError::LengthMismatch {
+                df: 17,
+                expected: 28,
+                actual: 14
+            })
+        );
+    }
+
+    #[test]
+    fn test_crc_error_correction() {
+        let valid = "8D40621D58C382D690C8AC2863A7";
+        assert_eq!(crc_syndrome(valid), Ok(0));
+        let original = decode_message(valid).unwrap();
+        let msg = u128::from_str_radix(valid, 16).unwrap();
+
+        // 原消息无需纠正
+        let result = decode_message_with_correction(valid, 1).unwrap();
+        assert_eq!(result.corrected_bits, 0);
+
+        // 单比特错误 (位于高度字段)
+        let one_bit = format!("{:028X}", msg ^ bit_mask(45));
+        assert_ne!(crc_syndrome(&one_bit), Ok(0));
+        let result = decode_message_with_correction(&one_bit, 1).unwrap();
+        assert_eq!(result.corrected_bits, 1);
+        assert_eq!(result.hex, valid);
+        assert_eq!(result.message, original);
+
+        // 双比特错误仅在允许 2 位纠错时恢复
+        let two_bits = format!("{:028X}", msg ^ bit_mask(10) ^ bit_mask(70));
+        assert!(matches!(
+            decode_message_with_correction(&two_bits, 1),
+            Err(DecodeError::CrcMismatch(_))
+        ));
+        let result = decode_message_with_correction(&two_bits, 2).unwrap();
+        assert_eq!(result.corrected_bits, 2);
+        assert_eq!(result.message, original);
+
+        assert!(matches!(
+            decode_message_with_correction(&one_bit, 0),
+            Err(DecodeError::CrcMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_altitude_q_bit() {
+        assert_eq!(decode_altitude(0xC38), Some(38000.0));
+        assert_eq!(decode_altitude(0x000), None);
+    }
+
+    #[test]
+    fn test_decode_altitude_gillham() {
+        // 所有 Q=0 的合法 Gillham 码应一一对应 -1200..=126700 ft 的 100 ft 台阶
+        let mut altitudes = BTreeSet::new();
+        let mut valid = 0;
+        for ac12 in 0..0x1000u16 {
+            if ac12 & 0x010 != 0 {
+                continue;
+            }
+            if let Some(alt) = decode_altitude(ac12) {
+                valid += 1;
+                altitudes.insert(alt as i64);
+            }
+        }
+        let expected: BTreeSet<i64> = (-12..=1267).map(|h| h * 100).collect();
+        assert_eq!(valid, expected.len());
+        assert_eq!(altitudes, expected);
+    }
+
+    #[test]
+    fn test_explain_position_fields() {
+        let fields = explain_message("8D40621D58C382D690C8AC2863A7").unwrap();
+
+        // 字段首尾相接覆盖全部 112 位
+        assert_eq!(fields.first().unwrap().start_bit, 1);
+        assert_eq!(fields.last().unwrap().end_bit, 112);
+        for pair in fields.windows(2) {
+            assert_eq!(pair[0].end_bit + 1, pair[1].start_bit);
+        }
+
+        let field = |name: &str| fields.iter().find(|f| f.name == name).unwrap();
+        assert_eq!(field("DF").raw, 17);
+        assert_eq!(field("ICAO").raw, 0x40621D);
+        assert_eq!(field("TC").raw, 11);
+        assert_eq!(field("ALT").meaning, "38000 ft");
+        assert_eq!(field("F").raw, 0);
+        assert_eq!(field("PI").meaning, "CRC parity OK");
+    }
+
+    #[test]
+    fn test_message_representation_bit_order() {
+        let hex = "8D40621D58C382D690C8AC2863A7";
+        let msb = message_representation(hex, BitOrder::MsbFirst).unwrap();
+        assert_eq!(msb.bytes.len(), 14);
+        assert_eq!(msb.bytes[..4], [0x8D, 0x40, 0x62, 0x1D]);
+        assert!(msb.bits.starts_with("10001101 01000000 01100010 00011101 01011000"));
+        // DF 从第 1 位、CA 从第 6 位、ICAO 从第 9 位、TC 从第 33 位开始
+        assert!(msb.field_markers.starts_with("^    ^   ^"));
+        assert_eq!(msb.field_markers.as_bytes()[36], b'^');
+
+        // 最低位在前时每字节比特反转，字段起点随之移动
+        let lsb = message_representation(hex, BitOrder::LsbFirst).unwrap();
+        assert!(lsb.bits.starts_with("10110001 00000010"));
+        assert_eq!(lsb.field_markers.find('^'), Some(2));
+        assert_eq!(lsb.bits.len(), msb.bits.len());
+    }
+
+    /// 构造带 AP 字段的短消息：数据部分 32 位，校验字段为 CRC 与地址的异或
+    fn short_message(data: u32, icao: u32) -> String {
+        format!("{:08X}{:06X}", data, crc24(&data.to_be_bytes()) ^ icao)
+    }
+
+    #[test]
+    fn test_decode_short_message_lengths() {
+        // DF4 高度应答，AC 字段 0x1838 (Q 位置位) 即 38000 ft
+        let df4 = short_message((4 << 27) | 0x1838, 0x40621D);
+        let decoded = decode_message(&df4).unwrap();
+        assert_eq!(decoded.df, 4);
+        assert_eq!(decoded.icao, "40621D");
+        assert_eq!(decoded.source, MessageSource::ModeS);
+        assert_eq!(
+            decoded.body,
+            MessageBody::Altitude {
+                altitude: Some(38000.0)
+            }
+        );
+
+        // 同样的 DF4 数据写成长消息长度会被拒绝
+        assert_eq!(
+            decode_message(&format!("{}00000000000000", df4)),
+            Err(DecodeError::LengthMismatch {
+                df: 4,
+                expected: 14,
+                actual: 28
+            })
+        );
+        // DF17 截断为短消息长度同样被拒绝
+        assert_eq!(
+            decode_message("8D40621D58C382"),
+            Err(DecodeError::LengthMismatch {
+                df: 17,
+                expected: 28,
+                actual: 14
+            })
+        );
+
+        // DF5 识别码应答：7700 = A7 B7 C0 D0
+        let df5 = short_message((5 << 27) | 0x0AAA, 0xABCDEF);
+        let decoded = decode_message(&df5).unwrap();
+        assert_eq!(decoded.icao, "ABCDEF");
+        assert_eq!(
+            decoded.body,
+            MessageBody::Identity {
+                squawk: "7700".to_string()
+            }
+        );
+
+        // DF11 全呼应答直接携带地址
+        assert_eq!(decode_message("5D40621D000000").unwrap().icao, "40621D");
+    }
+
+    #[test]
+    fn test_decode_rejects_malformed() {
+        assert_eq!(decode_message("8D40621D"), Err(DecodeError::InvalidLength(8)));
+        assert_eq!(
+            decode_message("8D40621D58C382D690C8AC2863AZ"),
+            Err(DecodeError::InvalidHex)
+        );
+    }
+}
@@ -0,0 +1,630 @@
+use crate::adsb::{AdsbSimulator, Aircraft, TrackPoint};
+use crate::decoder::{self, BitOrder};
+use crate::geo;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde_json::{json, Value};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+const FEET_TO_METERS: f64 = 0.3048;
+
+/// 生成 GeoJSON FeatureCollection
+///
+/// 每架飞机对应一个 Point 要素，属性字段沿用 dump1090 `aircraft.json` 的命名
+/// (`hex`/`flight`/`altitude`/`track`/`speed`)，可直接加载到 Leaflet/Mapbox 等地图库。
+pub fn aircrafts_to_geojson(aircrafts: &[Aircraft]) -> Value {
+    let features: Vec<Value> = aircrafts
+        .iter()
+        .map(|aircraft| {
+            json!({
+                "type": "Feature",
+                "geometry": {
+                    "type": "Point",
+                    // GeoJSON 坐标顺序为 [经度, 纬度]
+                    "coordinates": [aircraft.lng, aircraft.lat],
+                },
+                "properties": {
+                    "hex": aircraft.id.to_lowercase(),
+                    "flight": aircraft.callsign,
+                    "altitude": aircraft.altitude.round() as i64,
+                    "track": aircraft.track,
+                    "speed": aircraft.ground_speed,
+                    "nic": aircraft.nic,
+                },
+            })
+        })
+        .collect();
+
+    json!({
+        "type": "FeatureCollection",
+        "features": features,
+    })
+}
+
+/// 将 GeoJSON 写入文件
+pub fn export_geojson(aircrafts: &[Aircraft], path: &str) -> Result<(), String> {
+    let geojson = aircrafts_to_geojson(aircrafts);
+    let text = serde_json::to_string_pretty(&geojson).map_err(|e| e.to_string())?;
+    fs::write(path, text).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+/// 生成 CesiumJS 使用的 CZML 文档
+///
+/// 第一个包为 document 包，时钟区间覆盖整个记录时段；之后每架飞机一个包，
+/// 位置使用带时间采样的 `cartographicDegrees` (经度, 纬度, 高度 m)。
+pub fn simulator_to_czml(simulator: &AdsbSimulator) -> Value {
+    let epoch_ms = simulator.start_time_ms();
+    let history = simulator.get_history();
+
+    let span = history
+        .values()
+        .flat_map(|track| track.iter().map(|p| p.time))
+        .fold(None, |acc: Option<(f64, f64)>, t| match acc {
+            Some((start, end)) => Some((start.min(t), end.max(t))),
+            None => Some((t, t)),
+        })
+        .unwrap_or((0.0, 0.0));
+    let interval = |start: f64, end: f64| {
+        format!(
+            "{}/{}",
+            iso8601(epoch_ms + (start * 1000.0) as u64),
+            iso8601(epoch_ms + (end * 1000.0) as u64)
+        )
+    };
+
+    let mut packets = vec![json!({
+        "id": "document",
+        "name": "ADS-B session",
+        "version": "1.0",
+        "clock": {
+            "interval": interval(span.0, span.1),
+            "currentTime": iso8601(epoch_ms + (span.0 * 1000.0) as u64),
+            "multiplier": 1,
+        },
+    })];
+
+    for (icao, track) in history {
+        let (Some(first), Some(last)) = (track.front(), track.back()) else {
+            continue;
+        };
+        let name = simulator
+            .get_aircrafts()
+            .iter()
+            .find(|a| &a.id == icao)
+            .map(|a| a.callsign.clone())
+            .unwrap_or_else(|| icao.clone());
+
+        let samples: Vec<f64> = track
+            .iter()
+            .flat_map(|p| [p.time, p.lng, p.lat, p.altitude * FEET_TO_METERS])
+            .collect();
+
+        packets.push(json!({
+            "id": icao,
+            "name": name,
+            "availability": interval(first.time, last.time),
+            "position": {
+                "epoch": iso8601(epoch_ms),
+                "cartographicDegrees": samples,
+            },
+            "point": { "pixelSize": 8 },
+            "label": { "text": name },
+        }));
+    }
+
+    Value::Array(packets)
+}
+
+/// 将 CZML 写入文件
+pub fn export_czml(simulator: &AdsbSimulator, path: &str) -> Result<(), String> {
+    let czml = simulator_to_czml(simulator);
+    let text = serde_json::to_string_pretty(&czml).map_err(|e| e.to_string())?;
+    fs::write(path, text).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+/// 生成单架飞机 readsb/tar1090 格式的航迹 (`trace_full_<hex>.json`)
+///
+/// `timestamp` 为首个航迹点的 Unix 时间 (s)，`trace` 中每个点为
+/// `[相对秒数, 纬度, 经度, 高度 ft, 地速 kts, 航迹角, 标志, 垂直速率 ft/min, 飞机信息, 来源]`，
+/// 地速、航迹角与垂直速率由相邻航迹点推算；飞机信息仅在首个点给出 (`{"flight": 呼号}`)，
+/// 其余点为 null。注册号 `r` 未知，不输出。
+fn tar1090_trace(icao: &str, callsign: &str, epoch_ms: u64, track: &[TrackPoint]) -> Value {
+    let base = track.first().map_or(0.0, |p| p.time);
+    let timestamp = epoch_ms as f64 / 1000.0 + base;
+    let points: Vec<Value> = track
+        .iter()
+        .enumerate()
+        .map(|(i, point)| {
+            // 首个点与下一点配对，其余与前一点配对
+            let (from, to) = match (i.checked_sub(1), track.get(i + 1)) {
+                (Some(prev), _) => (Some(&track[prev]), point),
+                (None, Some(next)) => (Some(point), next),
+                (None, None) => (None, point),
+            };
+            let motion = from.filter(|from| to.time > from.time).map(|from| {
+                let dt = to.time - from.time;
+                let distance = geo::distance_nm(from.lat, from.lng, to.lat, to.lng);
+                (
+                    distance / dt * 3600.0,
+                    geo::initial_bearing(from.lat, from.lng, to.lat, to.lng),
+                    (to.altitude - from.altitude) / dt * 60.0,
+                )
+            });
+            json!([
+                point.time - base,
+                point.lat,
+                point.lng,
+                point.altitude.round() as i64,
+                motion.map(|(gs, _, _)| gs),
+                motion.map(|(_, track, _)| track),
+                // 标志位 2：新航段开始
+                if i == 0 { 2 } else { 0 },
+                motion.map(|(_, _, rate)| rate.round() as i64),
+                (i == 0).then(|| json!({ "flight": callsign })),
+                "adsb_icao",
+            ])
+        })
+        .collect();
+
+    json!({
+        "icao": icao.to_lowercase(),
+        "timestamp": timestamp,
+        "trace": points,
+    })
+}
+
+/// 按 tar1090 历史目录结构导出全部航迹，返回写入的航迹数
+///
+/// 每架飞机写入 gzip 压缩的 `traces/<hex 末两位>/trace_full_<hex>.json`，
+/// 另在 `dir` 下写入未压缩的 `index.json`，列出各航迹文件及点数。
+pub fn export_tar1090_traces(simulator: &AdsbSimulator, dir: &str) -> Result<usize, String> {
+    let epoch_ms = simulator.start_time_ms();
+    let mut index = Vec::new();
+    let mut latest: f64 = 0.0;
+    for (icao, track) in simulator.get_history() {
+        if track.is_empty() {
+            continue;
+        }
+        let hex = icao.to_lowercase();
+        let callsign = simulator
+            .get_aircrafts()
+            .iter()
+            .find(|a| &a.id == icao)
+            .map(|a| a.callsign.trim().to_string())
+            .unwrap_or_default();
+        let points: Vec<TrackPoint> = track.iter().copied().collect();
+        latest = points.iter().fold(latest, |acc, p| acc.max(p.time));
+        let trace = tar1090_trace(&hex, &callsign, epoch_ms, &points);
+
+        let shard = &hex[hex.len().saturating_sub(2)..];
+        let relative = format!("traces/{}/trace_full_{}.json", shard, hex);
+        let path = Path::new(dir).join(&relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(trace.to_string().as_bytes())
+            .map_err(|e| e.to_string())?;
+        let bytes = encoder.finish().map_err(|e| e.to_string())?;
+        fs::write(&path, bytes)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+        index.push(json!({
+            "hex": hex,
+            "flight": callsign,
+            "file": relative,
+            "points": points.len(),
+        }));
+    }
+
+    let count = index.len();
+    let index = json!({
+        "now": epoch_ms as f64 / 1000.0 + latest,
+        "traces": index,
+    });
+    let path = Path::new(dir).join("index.json");
+    let text = serde_json::to_string_pretty(&index).map_err(|e| e.to_string())?;
+    fs::write(&path, text).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(count)
+}
+
+/// 由 ICAO 地址派生稳定的显示颜色 (`#RRGGBB`)
+///
+/// 对地址做 FNV-1a 哈希后映射到色相与亮度，饱和度固定，保证同一架飞机在不同会话中颜色一致。
+pub fn icao_color(icao: &str) -> String {
+    let hash = icao
+        .trim()
+        .to_uppercase()
+        .bytes()
+        .fold(0x811C_9DC5u32, |h, b| (h ^ b as u32).wrapping_mul(0x0100_0193));
+    let hue = (hash % 360) as f64;
+    let lightness = 0.45 + ((hash >> 16) % 16) as f64 / 100.0; // 0.45-0.60
+    let saturation = 0.7;
+
+    // HSL 转 RGB
+    let c = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let x = c * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let m = lightness - c / 2.0;
+    let (r, g, b) = match (hue / 60.0) as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let channel = |v: f64| ((v + m) * 255.0).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", channel(r), channel(g), channel(b))
+}
+
+/// 生成对齐的纯文本机队表（按呼号排序），便于命令行调试与问题报告
+pub fn fleet_table(aircrafts: &[Aircraft]) -> String {
+    let mut sorted: Vec<&Aircraft> = aircrafts.iter().collect();
+    sorted.sort_by(|a, b| a.callsign.cmp(&b.callsign).then_with(|| a.id.cmp(&b.id)));
+
+    let header = [
+        "ID", "CALLSIGN", "LAT", "LNG", "ALTITUDE", "SPEED", "HEADING", "NIC",
+    ];
+    let rows: Vec<[String; 8]> = sorted
+        .iter()
+        .map(|aircraft| {
+            [
+                aircraft.id.clone(),
+                aircraft.callsign.clone(),
+                format!("{:.5}", aircraft.lat),
+                format!("{:.5}", aircraft.lng),
+                format!("{:.0}", aircraft.altitude),
+                format!("{:.0}", aircraft.speed),
+                format!("{:.1}", aircraft.heading),
+                aircraft.nic.to_string(),
+            ]
+        })
+        .collect();
+
+    let mut widths = header.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let format_row = |cells: &[String]| {
+        cells
+            .iter()
+            .zip(widths)
+            .enumerate()
+            // 文本列左对齐，数值列右对齐
+            .map(|(i, (cell, width))| {
+                if i < 2 {
+                    format!("{:<width$}", cell)
+                } else {
+                    format!("{:>width$}", cell)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = vec![
+        format_row(&header.map(String::from)),
+        format_row(&widths.map(|width| "-".repeat(width))),
+    ];
+    lines.extend(rows.iter().map(|row| format_row(row)));
+    lines.join("\n")
+}
+
+/// 生成单条消息的逐字段讲解页面 (HTML)
+///
+/// 上方为按字段分组的 112 位比特条，其下按 `bit_order` 给出十六进制、字节数组和
+/// 比特串对照并标出字段起始位，最后的表格列出各字段的位范围、原始值和解码含义。
+pub fn message_explainer_html(hex: &str, bit_order: BitOrder) -> Result<String, String> {
+    let fields = decoder::explain_message(hex).map_err(|e| e.to_string())?;
+    let repr = decoder::message_representation(hex, bit_order).map_err(|e| e.to_string())?;
+    let byte_array = repr
+        .bytes
+        .iter()
+        .map(|b| format!("0x{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let hex = hex.trim().to_uppercase();
+    let bits = format!("{:0112b}", u128::from_str_radix(&hex, 16).map_err(|e| e.to_string())?);
+
+    let mut names = String::new();
+    let mut groups = String::new();
+    let mut rows = String::new();
+    for (i, field) in fields.iter().enumerate() {
+        let name = html_escape(&field.name);
+        let class = format!("f{}", i % 2);
+        names.push_str(&format!("<th class=\"{}\">{}</th>", class, name));
+        groups.push_str(&format!(
+            "<td class=\"{}\" title=\"{} bits {}-{}\"><code>{}</code></td>",
+            class,
+            name,
+            field.start_bit,
+            field.end_bit,
+            &bits[field.start_bit - 1..field.end_bit]
+        ));
+        rows.push_str(&format!(
+            "<tr><td>{}</td><td>{}-{}</td><td>{} (0x{:X})</td><td>{}</td></tr>\n",
+            name,
+            field.start_bit,
+            field.end_bit,
+            field.raw,
+            field.raw,
+            html_escape(&field.meaning)
+        ));
+    }
+
+    Ok(format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>ADS-B message {hex}</title>
+<style>
+body {{ font-family: sans-serif; }}
+table {{ border-collapse: collapse; margin-bottom: 1em; }}
+th, td {{ border: 1px solid #999; padding: 2px 6px; }}
+.bits td {{ word-break: break-all; }}
+.f0 {{ background: #e8f0fe; }}
+.f1 {{ background: #fef3e8; }}
+</style>
+</head>
+<body>
+<h1>{hex}</h1>
+<table class="bits">
+<tr>{names}</tr>
+<tr>{groups}</tr>
+</table>
+<pre class="repr">Hex:   {hex}
+Bytes: [{byte_array}]
+Bits ({order:?}):
+{bits}
+{markers}</pre>
+<table class="fields">
+<tr><th>Field</th><th>Bits</th><th>Raw</th><th>Meaning</th></tr>
+{rows}</table>
+</body>
+</html>
+"#,
+        order = repr.bit_order,
+        bits = repr.bits,
+        markers = repr.field_markers,
+    ))
+}
+
+/// 将消息讲解页面写入文件
+pub fn export_message_explainer(hex: &str, path: &str, bit_order: BitOrder) -> Result<(), String> {
+    let html = message_explainer_html(hex, bit_order)?;
+    fs::write(path, html).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+/// 转义 HTML 特殊字符
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Unix 毫秒时间转 ISO 8601 UTC 字符串 (如 `2025-02-21T18:06:00.000Z`)
+pub fn iso8601(unix_ms: u64) -> String {
+    let (year, month, day, hour, minute, second) = civil_from_unix(unix_ms / 1000);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        hour,
+        minute,
+        second,
+        unix_ms % 1000
+    )
+}
+
+/// Unix 秒转公历日期时间 (UTC)
+pub fn civil_from_unix(unix_secs: u64) -> (i64, u32, u32, u32, u32, u32) {
+    let days = (unix_secs / 86400) as i64;
+    let secs_of_day = unix_secs % 86400;
+
+    // Howard Hinnant 的 civil_from_days 算法
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (
+        year,
+        month,
+        day,
+        (secs_of_day / 3600) as u32,
+        (secs_of_day % 3600 / 60) as u32,
+        (secs_of_day % 60) as u32,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adsb::AdsbSimulator;
+
+    #[test]
+    fn test_icao_color_stable() {
+        assert_eq!(icao_color("780000"), icao_color("780000"));
+        assert_eq!(icao_color("abc123"), icao_color("ABC123"));
+
+        let colors: std::collections::HashSet<String> = ["780000", "781111", "782222", "ABC123"]
+            .iter()
+            .map(|icao| icao_color(icao))
+            .collect();
+        assert_eq!(colors.len(), 4);
+        assert!(colors.iter().all(|c| c.len() == 7 && c.starts_with('#')));
+    }
+
+    #[test]
+    fn test_fleet_table_row_per_aircraft() {
+        let mut sim = AdsbSimulator::new(22.5431, 114.0579);
+        sim.generate_mock_aircrafts(5).unwrap();
+
+        let table = fleet_table(sim.get_aircrafts());
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 2 + 5);
+
+        let columns: Vec<&str> = lines[0].split_whitespace().collect();
+        assert_eq!(
+            columns,
+            ["ID", "CALLSIGN", "LAT", "LNG", "ALTITUDE", "SPEED", "HEADING", "NIC"]
+        );
+
+        let callsigns: Vec<&str> = lines[2..]
+            .iter()
+            .map(|line| {
+                let cells: Vec<&str> = line.split_whitespace().collect();
+                assert_eq!(cells.len(), 8);
+                cells[1]
+            })
+            .collect();
+        let mut sorted = callsigns.clone();
+        sorted.sort();
+        assert_eq!(callsigns, sorted);
+
+        for aircraft in sim.get_aircrafts() {
+            assert!(lines.iter().any(|line| line.starts_with(&aircraft.id)));
+        }
+    }
+
+    #[test]
+    fn test_message_explainer_labels_position_fields() {
+        let html =
+            message_explainer_html("8D40621D58C382D690C8AC2863A7", BitOrder::MsbFirst).unwrap();
+
+        for label in ["DF", "CA", "ICAO", "TC", "ALT", "F", "LAT-CPR", "LON-CPR", "PI"] {
+            assert!(html.contains(&format!("<td>{}</td>", label)), "missing {}", label);
+        }
+        assert!(html.contains("38000 ft"));
+        assert!(html.contains("<td>9-32</td>"));
+        assert!(html.contains("<code>10001</code>"));
+        assert!(html.contains("Bytes: [0x8D, 0x40, 0x62, 0x1D,"));
+        assert!(html.contains("\n10001101 01000000 "));
+        assert!(message_explainer_html("not hex", BitOrder::MsbFirst).is_err());
+    }
+
+    #[test]
+    fn test_geojson_feature_per_aircraft() {
+        let mut sim = AdsbSimulator::new(22.5431, 114.0579);
+        sim.generate_mock_aircrafts(4).unwrap();
+
+        let text = aircrafts_to_geojson(sim.get_aircrafts()).to_string();
+        let parsed: Value = serde_json::from_str(&text).unwrap();
+
+        assert_eq!(parsed["type"], "FeatureCollection");
+        let features = parsed["features"].as_array().unwrap();
+        assert_eq!(features.len(), 4);
+
+        for (feature, aircraft) in features.iter().zip(sim.get_aircrafts()) {
+            assert_eq!(feature["type"], "Feature");
+            assert_eq!(feature["geometry"]["type"], "Point");
+            let coords = &feature["geometry"]["coordinates"];
+            assert!((coords[0].as_f64().unwrap() - aircraft.lng).abs() < 1e-9);
+            assert!((coords[1].as_f64().unwrap() - aircraft.lat).abs() < 1e-9);
+            assert_eq!(feature["properties"]["flight"], aircraft.callsign.as_str());
+            assert!(feature["properties"]["altitude"].is_i64());
+            assert!(feature["properties"]["track"].is_number());
+            assert!(feature["properties"]["speed"].is_number());
+        }
+    }
+
+    #[test]
+    fn test_czml_packet_per_aircraft() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 1);
+        sim.generate_mock_aircrafts(3).unwrap();
+        for _ in 0..5 {
+            sim.update_positions();
+        }
+
+        let czml = simulator_to_czml(&sim);
+        let packets = czml.as_array().unwrap();
+        assert_eq!(packets[0]["id"], "document");
+        assert_eq!(packets.len(), 1 + 3);
+
+        // 按 ICAO 顺序输出
+        let mut aircrafts = sim.get_aircrafts().clone();
+        aircrafts.sort_by(|a, b| a.id.cmp(&b.id));
+        for (packet, aircraft) in packets[1..].iter().zip(&aircrafts) {
+            assert_eq!(packet["id"], aircraft.id.as_str());
+            let samples = packet["position"]["cartographicDegrees"].as_array().unwrap();
+            // 初始点 + 5 次更新，每个采样 4 个数值
+            assert_eq!(samples.len(), 6 * 4);
+            assert_eq!(samples[4].as_f64(), Some(1.0));
+        }
+        assert_eq!(packets[1]["availability"], packets[0]["clock"]["interval"]);
+    }
+
+    #[test]
+    fn test_tar1090_trace_per_aircraft() {
+        use flate2::read::GzDecoder;
+        use std::io::Read;
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 63);
+        sim.generate_mock_aircrafts(3).unwrap();
+        for _ in 0..4 {
+            sim.update_positions();
+        }
+        let dir = std::env::temp_dir().join(format!("adsb_tar1090_{}", std::process::id()));
+        let dir_str = dir.to_str().unwrap();
+        assert_eq!(export_tar1090_traces(&sim, dir_str).unwrap(), 3);
+
+        let index: Value =
+            serde_json::from_str(&fs::read_to_string(dir.join("index.json")).unwrap()).unwrap();
+        let traces = index["traces"].as_array().unwrap();
+        assert_eq!(traces.len(), 3);
+        let mut aircrafts = sim.get_aircrafts().clone();
+        aircrafts.sort_by(|a, b| a.id.cmp(&b.id));
+        for (entry, aircraft) in traces.iter().zip(&aircrafts) {
+            let hex = aircraft.id.to_lowercase();
+            assert_eq!(entry["hex"], hex.as_str());
+            let mut text = String::new();
+            let file = fs::File::open(dir.join(entry["file"].as_str().unwrap())).unwrap();
+            GzDecoder::new(file).read_to_string(&mut text).unwrap();
+            let trace: Value = serde_json::from_str(&text).unwrap();
+
+            assert_eq!(trace["icao"], hex.as_str());
+            assert!(trace.get("r").is_none());
+            assert!(trace["timestamp"].as_f64().unwrap() > 1.0e9);
+            let points = trace["trace"].as_array().unwrap();
+            // 初始点 + 4 次更新
+            assert_eq!(points.len(), 5);
+            for (i, point) in points.iter().enumerate() {
+                assert_eq!(point[0].as_f64(), Some(i as f64));
+                assert!((point[1].as_f64().unwrap() - aircraft.lat).abs() < 1.0);
+                assert!(point[3].is_i64());
+                assert!(point[4].as_f64().unwrap() > 0.0);
+                assert!((0.0..360.0).contains(&point[5].as_f64().unwrap()));
+                assert!(point[7].is_i64());
+                if i == 0 {
+                    assert_eq!(point[8]["flight"], aircraft.callsign.trim());
+                } else {
+                    assert!(point[8].is_null());
+                }
+            }
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_iso8601() {
+        assert_eq!(iso8601(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(iso8601(1_740_161_160_123), "2025-02-21T18:06:00.123Z");
+    }
+}
@@ -0,0 +1,115 @@
+//! 球面大地几何辅助函数（球形地球模型，距离单位为海里）
+
+/// 地球平均半径 (nm)
+pub const EARTH_RADIUS_NM: f64 = 3440.065;
+
+/// 每海里的英尺数
+pub const FEET_PER_NM: f64 = 6076.12;
+
+/// 两点间大圆距离 (nm)
+pub fn distance_nm(lat1: f64, lng1: f64, lat2: f64, lng2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_phi = (lat2 - lat1).to_radians();
+    let d_lambda = (lng2 - lng1).to_radians();
+    let a = (d_phi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (d_lambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_NM * a.sqrt().atan2((1.0 - a).sqrt())
+}
+
+/// 从点 1 指向点 2 的大圆初始方位角 (度，0-360，正北为 0)
+pub fn initial_bearing(lat1: f64, lng1: f64, lat2: f64, lng2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_lambda = (lng2 - lng1).to_radians();
+    let y = d_lambda.sin() * phi2.cos();
+    let x = phi1.cos() * phi2.sin() - phi1.sin() * phi2.cos() * d_lambda.cos();
+    (y.atan2(x).to_degrees() + 360.0) % 360.0
+}
+
+/// 大圆航线上按比例 `fraction` (0-1) 插值的中间点
+pub fn intermediate_point(lat1: f64, lng1: f64, lat2: f64, lng2: f64, fraction: f64) -> (f64, f64) {
+    let (phi1, lambda1) = (lat1.to_radians(), lng1.to_radians());
+    let (phi2, lambda2) = (lat2.to_radians(), lng2.to_radians());
+    let delta = distance_nm(lat1, lng1, lat2, lng2) / EARTH_RADIUS_NM;
+    if delta.abs() < 1e-12 {
+        return (lat1, lng1);
+    }
+
+    let a = ((1.0 - fraction) * delta).sin() / delta.sin();
+    let b = (fraction * delta).sin() / delta.sin();
+    let x = a * phi1.cos() * lambda1.cos() + b * phi2.cos() * lambda2.cos();
+    let y = a * phi1.cos() * lambda1.sin() + b * phi2.cos() * lambda2.sin();
+    let z = a * phi1.sin() + b * phi2.sin();
+    (
+        z.atan2((x * x + y * y).sqrt()).to_degrees(),
+        y.atan2(x).to_degrees(),
+    )
+}
+
+/// 从起点沿方位角飞行 `distance` 海里后的位置
+pub fn destination_point(lat: f64, lng: f64, bearing: f64, distance: f64) -> (f64, f64) {
+    let phi1 = lat.to_radians();
+    let lambda1 = lng.to_radians();
+    let theta = bearing.to_radians();
+    let delta = distance / EARTH_RADIUS_NM;
+
+    let phi2 = (phi1.sin() * delta.cos() + phi1.cos() * delta.sin() * theta.cos()).asin();
+    let lambda2 = lambda1
+        + (theta.sin() * delta.sin() * phi1.cos()).atan2(delta.cos() - phi1.sin() * phi2.sin());
+    let lng2 = (lambda2.to_degrees() + 540.0) % 360.0 - 180.0;
+    (phi2.to_degrees(), lng2)
+}
+
+/// 两个航向之间的有符号差值 (度，-180..180)，正值表示需右转
+pub fn heading_difference(from: f64, to: f64) -> f64 {
+    (to - from + 540.0) % 360.0 - 180.0
+}
+
+/// 无线电视距 (nm)：`1.23 * (√h_aircraft + √h_receiver)`，高度单位 ft
+pub fn radar_horizon_nm(aircraft_altitude_ft: f64, receiver_altitude_ft: f64) -> f64 {
+    1.23 * (aircraft_altitude_ft.max(0.0).sqrt() + receiver_altitude_ft.max(0.0).sqrt())
+}
+
+/// 在 `range_nm` 处仍位于视距内的最低飞机高度 (ft)，即 `radar_horizon_nm` 的反函数
+///
+/// 接收站自身视距已覆盖该距离时返回 0。
+pub fn min_receivable_altitude_ft(range_nm: f64, receiver_altitude_ft: f64) -> f64 {
+    let root = range_nm.max(0.0) / 1.23 - receiver_altitude_ft.max(0.0).sqrt();
+    root.max(0.0).powi(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance_and_bearing() {
+        // 赤道上经度相差 1 度 ≈ 60 nm
+        assert!((distance_nm(0.0, 0.0, 0.0, 1.0) - 60.04).abs() < 0.1);
+        assert!((initial_bearing(0.0, 0.0, 0.0, 1.0) - 90.0).abs() < 1e-9);
+        assert!((initial_bearing(0.0, 0.0, 1.0, 0.0)).abs() < 1e-9);
+        assert!((heading_difference(350.0, 10.0) - 20.0).abs() < 1e-9);
+        assert!((heading_difference(10.0, 350.0) + 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_destination_and_intermediate() {
+        let (lat, lng) = destination_point(22.5, 114.0, 45.0, 100.0);
+        assert!((distance_nm(22.5, 114.0, lat, lng) - 100.0).abs() < 1e-6);
+
+        let (mid_lat, mid_lng) = intermediate_point(22.5, 114.0, 31.2, 121.5, 0.5);
+        let total = distance_nm(22.5, 114.0, 31.2, 121.5);
+        assert!((distance_nm(22.5, 114.0, mid_lat, mid_lng) - total / 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_min_receivable_altitude() {
+        // 地面接收站：10000 ft 的视距为 123 nm
+        assert!((min_receivable_altitude_ft(123.0, 0.0) - 10000.0).abs() < 1e-6);
+        // 100 ft 天线：123 nm 处需 8100 ft，12.3 nm 内任意高度均可接收
+        assert!((min_receivable_altitude_ft(123.0, 100.0) - 8100.0).abs() < 1e-6);
+        assert_eq!(min_receivable_altitude_ft(12.3, 100.0), 0.0);
+        assert!((min_receivable_altitude_ft(250.0, 0.0) - 41311.4).abs() < 0.1);
+
+        let altitude = min_receivable_altitude_ft(180.0, 50.0);
+        assert!((radar_horizon_nm(altitude, 50.0) - 180.0).abs() < 1e-9);
+    }
+}
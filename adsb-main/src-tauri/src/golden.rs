@@ -0,0 +1,188 @@
+use crate::adsb::{AdsbEvent, Aircraft};
+use crate::{build_simulator, SimulationConfig};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+
+/// 浮点字段比较的相对容差
+const FLOAT_TOLERANCE: f64 = 1e-12;
+
+/// 金标准记录：固定配置和种子运行指定步数后的消息与飞机状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoldenRecord {
+    pub config: SimulationConfig, // 运行配置（种子必定存在）
+    pub ticks: u32,               // 运行步数
+    pub messages: Vec<AdsbEvent>,
+    pub aircrafts: Vec<Aircraft>,
+}
+
+/// 单个字段的差异，`path` 形如 `aircrafts[2].altitude`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldDiff {
+    pub path: String,
+    pub expected: Value,
+    pub actual: Value,
+}
+
+/// 与金标准的比较结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GoldenDiff {
+    pub matches: bool,
+    pub differences: Vec<FieldDiff>,
+}
+
+/// 按配置运行 `ticks` 步，返回最后一步的消息与飞机状态
+fn run_scenario(
+    config: &SimulationConfig,
+    ticks: u32,
+) -> Result<(Vec<AdsbEvent>, Vec<Aircraft>), String> {
+    let mut simulator = build_simulator(config)?;
+    for _ in 0..ticks {
+        simulator.update_positions();
+    }
+    let messages = simulator.generate_all_messages();
+    let messages = simulator.apply_reception(messages);
+    Ok((
+        simulator.apply_impairment(messages),
+        simulator.get_aircrafts().clone(),
+    ))
+}
+
+/// 生成金标准记录；配置未指定种子时固定为本次使用的种子
+pub fn capture(config: &SimulationConfig, ticks: u32) -> Result<GoldenRecord, String> {
+    let mut config = config.clone();
+    if config.seed.is_none() {
+        config.seed = Some(build_simulator(&config)?.get_seed());
+    }
+    let (messages, aircrafts) = run_scenario(&config, ticks)?;
+    Ok(GoldenRecord {
+        config,
+        ticks,
+        messages,
+        aircrafts,
+    })
+}
+
+/// 用记录中的配置重新运行，并逐字段比较输出
+pub fn compare(record: &GoldenRecord) -> Result<GoldenDiff, String> {
+    let (messages, aircrafts) = run_scenario(&record.config, record.ticks)?;
+    let mut differences = Vec::new();
+    for (path, expected, actual) in [
+        (
+            "messages",
+            serde_json::to_value(&record.messages),
+            serde_json::to_value(&messages),
+        ),
+        (
+            "aircrafts",
+            serde_json::to_value(&record.aircrafts),
+            serde_json::to_value(&aircrafts),
+        ),
+    ] {
+        let expected = expected.map_err(|e| e.to_string())?;
+        let actual = actual.map_err(|e| e.to_string())?;
+        diff_values(path, &expected, &actual, &mut differences);
+    }
+    Ok(GoldenDiff {
+        matches: differences.is_empty(),
+        differences,
+    })
+}
+
+/// 递归比较两个 JSON 值，记录每个不同的叶子字段
+fn diff_values(path: &str, expected: &Value, actual: &Value, out: &mut Vec<FieldDiff>) {
+    match (expected, actual) {
+        (Value::Object(a), Value::Object(b)) => {
+            let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                diff_values(
+                    &format!("{}.{}", path, key),
+                    a.get(key).unwrap_or(&Value::Null),
+                    b.get(key).unwrap_or(&Value::Null),
+                    out,
+                );
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for i in 0..a.len().max(b.len()) {
+                diff_values(
+                    &format!("{}[{}]", path, i),
+                    a.get(i).unwrap_or(&Value::Null),
+                    b.get(i).unwrap_or(&Value::Null),
+                    out,
+                );
+            }
+        }
+        // 浮点数经过文本往返可能有末位误差
+        (Value::Number(a), Value::Number(b))
+            if a.as_f64().zip(b.as_f64()).is_some_and(|(a, b)| {
+                (a - b).abs() <= FLOAT_TOLERANCE * a.abs().max(b.abs()).max(1.0)
+            }) => {}
+        _ if expected != actual => out.push(FieldDiff {
+            path: path.to_string(),
+            expected: expected.clone(),
+            actual: actual.clone(),
+        }),
+        _ => {}
+    }
+}
+
+/// 生成金标准记录并写入文件
+pub fn save_golden(
+    config: &SimulationConfig,
+    ticks: u32,
+    path: &str,
+) -> Result<GoldenRecord, String> {
+    let record = capture(config, ticks)?;
+    let text = serde_json::to_string_pretty(&record).map_err(|e| e.to_string())?;
+    fs::write(path, text).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(record)
+}
+
+/// 读取金标准文件并与当前输出比较
+pub fn compare_to_golden(path: &str) -> Result<GoldenDiff, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let record: GoldenRecord = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+    compare(&record)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_golden_match_and_diff() {
+        let config = SimulationConfig {
+            aircraft_count: 4,
+            seed: Some(7),
+            ..SimulationConfig::default()
+        };
+        let path = std::env::temp_dir().join(format!("adsb_golden_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let record = save_golden(&config, 10, path).unwrap();
+        assert!(!record.messages.is_empty());
+        let diff = compare_to_golden(path).unwrap();
+        assert!(diff.matches, "{:?}", diff.differences);
+        fs::remove_file(path).unwrap();
+
+        // 更换种子后同样的期望输出不再匹配
+        let mut changed = record;
+        changed.config.seed = Some(8);
+        let diff = compare(&changed).unwrap();
+        assert!(!diff.matches);
+        assert!(diff.differences.iter().any(|d| d.path.starts_with("aircrafts[")));
+    }
+
+    #[test]
+    fn test_diff_reports_field_path() {
+        let expected = serde_json::json!({"aircrafts": [{"altitude": 1000.0, "id": "ABC123"}]});
+        let actual = serde_json::json!({"aircrafts": [{"altitude": 1200.0, "id": "ABC123"}]});
+        let mut out = Vec::new();
+        diff_values("root", &expected, &actual, &mut out);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].path, "root.aircrafts[0].altitude");
+    }
+}
@@ -1,25 +1,130 @@
-mod adsb;
+pub mod adsb;
+mod airspace;
+mod compliance;
+pub mod decoder;
+mod export;
+mod golden;
+pub mod geo;
+mod network;
+mod physics;
+#[cfg(feature = "protobuf")]
+pub mod proto;
+mod recent;
+mod receiver;
+mod recording;
+pub mod smoothing;
+mod stats;
+#[cfg(feature = "uat")]
+pub mod uat;
+mod vectors;
 
-use adsb::{AdsbEvent, AdsbSimulator, Aircraft};
+use adsb::{
+    AdsbEvent, AdsbSimulator, AdsbSource, Aircraft, AirportBeacon, CapabilityProfile,
+    ChannelImpairment, EmergencyKind, FlightPlan, MessageCadence, MockRanges, PhaseSpeedProfile,
+    ReceptionModel, Runway, ScheduleEntry, SpeedAltitudeCoupling, TrackPoint, WindLayer,
+};
+use airspace::{AirspaceMonitor, NoFlyZone};
+use compliance::ComplianceReport;
+use decoder::{BitOrder, MessageRepresentation};
+use golden::GoldenDiff;
+use network::{ClientStats, OutputFormat, OutputServer};
+use physics::PhysicsWarning;
+use recent::RecentEvents;
+use receiver::{Receiver, ReceiverNetwork, ReceiverStats};
+use recording::{Recorder, RecordingReport, Replay, ReplayBatch, ReplaySummary};
+use smoothing::TrackSmoothing;
+use stats::{
+    LatencySnapshot, LatencyStats, MessageHistogram, MessageTypeCount, SimulationCounters,
+    SimulationStatistics,
+};
+use vectors::VectorResult;
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, Mutex};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tauri::async_runtime::{self, JoinHandle};
 use tauri::{AppHandle, Emitter, State};
+use tokio::sync::watch;
+use tokio::time::{Interval, MissedTickBehavior};
 
 /// 模拟器状态
 struct SimulatorState {
     simulator: Arc<Mutex<AdsbSimulator>>,
     is_running: Arc<Mutex<bool>>,
+    is_paused: Arc<Mutex<bool>>, // 暂停时后台任务保持运行但不推进模拟
+    latency: Arc<LatencyStats>,
+    histogram: Arc<Mutex<MessageHistogram>>, // 会话内已发送消息的 DF/类型码分布
+    counters: Arc<SimulationCounters>, // 消息吞吐计数，每次启动时清零
+    receivers: Arc<Mutex<ReceiverNetwork>>,
+    outputs: Arc<Mutex<Vec<OutputServer>>>, // TCP 网络输出端口
+    recent: Arc<Mutex<RecentEvents>>, // 最近发送的批次
+    replay: Arc<Mutex<Option<Replay>>>, // 叠加在实时模拟上的录制回放
+    recorder: Arc<Mutex<Option<Recorder>>>, // 消息流录制，None 时未在录制
+    #[cfg(feature = "uat")]
+    uat: Arc<Mutex<uat::UatChannel>>, // 使用 978 MHz UAT 的飞机
+    worker: Mutex<Option<Worker>>, // 后台模拟任务，停止时回收
+    scenario: Mutex<Option<Vec<Aircraft>>>, // 导入的固定机队，设置后启动时不再生成随机飞机
+    file_replay: Mutex<Option<FileReplay>>, // 脱离实时模拟的文件回放线程
+    track_smoothing: Mutex<TrackSmoothing>, // 最近一次启动模拟时配置的外部航迹平滑方式
+}
+
+/// 后台模拟任务及其停止信号
+struct Worker {
+    task: JoinHandle<()>,
+    shutdown: watch::Sender<bool>, // 发送 true 后任务不再等待下一个 tick，立即退出
+}
+
+/// 文件回放线程及其停止标志
+struct FileReplay {
+    thread: thread::JoinHandle<()>,
+    stop: Arc<AtomicBool>, // 置位后线程在下一次检查时退出，不再发送剩余批次
+}
+
+impl FileReplay {
+    fn is_active(&self) -> bool {
+        !self.thread.is_finished()
+    }
 }
 
 /// 模拟配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SimulationConfig {
     pub center_lat: f64,
     pub center_lng: f64,
     pub aircraft_count: usize,
-    pub update_interval_ms: u64,
+    pub update_interval_ms: u64, // 模拟步进间隔，小于 MIN_EMIT_INTERVAL_MS 时见 emit_plan
+    pub display_rate_hz: Option<f64>, // 向前端发送的刷新率 (Hz)，None 时每步发送
+    pub seed: Option<u64>, // 随机数种子，缺省时基于系统时间
+    pub no_fly_zones: Vec<NoFlyZone>, // 禁飞区，进入时触发 airspace-violation 事件
+    pub initial_aircraft: Vec<Aircraft>, // 预设飞机，不足 aircraft_count 的部分由生成器补齐
+    pub speed_altitude_coupling: Option<SpeedAltitudeCoupling>, // 速度随高度变化的参数
+    pub message_cadence: MessageCadence, // 各类型消息的发送周期 (tick)
+    pub phase_speeds: PhaseSpeedProfile, // 各飞行阶段的速度范围
+    pub receivers: Vec<Receiver>, // 地面接收站，用于统计各站接收情况
+    pub acquisition_ticks: u32,  // 模拟器生成的新飞机的 GNSS 捕获时长 (tick)
+    pub impairment: ChannelImpairment, // 信道丢帧与比特错误
+    pub reception: Option<ReceptionModel>, // 按距离随机漏收并附加信号强度，None 时所有消息都被接收
+    pub airport_beacon: Option<AirportBeacon>, // 机场参考点信标，发送地面位置消息
+    pub wind_layers: Vec<WindLayer>, // 分层风表
+    pub semicircular_levels: bool, // 生成机队时按半圆规则分配巡航高度层
+    pub trail_points: usize, // 批次中每架飞机附带的最近航迹点数，0 表示不附带
+    pub integrity_labels: bool, // 批次中附带 NIC 的可读完整性等级
+    pub keepalive_interval_ms: Option<u64>, // 暂停期间向网络客户端发送心跳的间隔，None 时不发送
+    pub recent_window_s: u64, // 最近批次缓冲的保留时长 (s)
+    pub recent_max_batches: usize, // 最近批次缓冲的最大批次数
+    pub history_budget_points: usize, // 全体飞机航迹历史的总点数上限，超出时淘汰最旧的点
+    pub startup_ramp_s: u64, // 启动时机队逐步出现的时长 (模拟秒)，0 表示全部立即出现
+    pub min_altitude: f64, // 生成机队的初始高度范围 (ft)
+    pub max_altitude: f64,
+    pub min_speed: f64, // 生成机队的初始速度范围 (kts)，启用速度-高度耦合时不使用
+    pub max_speed: f64,
+    pub max_radius_deg: Option<f64>, // 活动半径 (度)，飞出的飞机由边缘新飞入的飞机替换，None 时不限制
+    pub track_smoothing: TrackSmoothing, // 回放/叠加的外部航迹的平滑方式，模拟飞机不经过平滑
+    #[cfg(feature = "uat")]
+    pub uat_aircraft: Vec<String>, // 改用 978 MHz UAT 发送的飞机 ICAO
 }
 
 impl Default for SimulationConfig {
@@ -29,16 +134,306 @@ impl Default for SimulationConfig {
             center_lng: 114.0579,
             aircraft_count: 12,
             update_interval_ms: 1000,
+            display_rate_hz: None,
+            seed: None,
+            no_fly_zones: Vec::new(),
+            initial_aircraft: Vec::new(),
+            speed_altitude_coupling: None,
+            message_cadence: MessageCadence::default(),
+            phase_speeds: PhaseSpeedProfile::default(),
+            receivers: Vec::new(),
+            acquisition_ticks: adsb::DEFAULT_ACQUISITION_TICKS,
+            impairment: ChannelImpairment::default(),
+            reception: None,
+            airport_beacon: None,
+            wind_layers: Vec::new(),
+            semicircular_levels: false,
+            trail_points: 0,
+            integrity_labels: true,
+            keepalive_interval_ms: Some(5000),
+            recent_window_s: recent::DEFAULT_RECENT_WINDOW_S,
+            recent_max_batches: recent::DEFAULT_RECENT_MAX_BATCHES,
+            history_budget_points: adsb::DEFAULT_HISTORY_BUDGET_POINTS,
+            startup_ramp_s: 0,
+            min_altitude: MockRanges::default().min_altitude,
+            max_altitude: MockRanges::default().max_altitude,
+            min_speed: MockRanges::default().min_speed,
+            max_speed: MockRanges::default().max_speed,
+            max_radius_deg: None,
+            track_smoothing: TrackSmoothing::None,
+            #[cfg(feature = "uat")]
+            uat_aircraft: Vec::new(),
         }
     }
 }
 
 /// ADS-B 消息批量事件
 #[derive(Clone, Serialize)]
-struct AdsbBatchEvent {
-    messages: Vec<AdsbEvent>,
-    aircrafts: Vec<Aircraft>,
-    timestamp: u64,
+pub struct AdsbBatchEvent {
+    pub messages: Vec<AdsbEvent>,
+    pub aircrafts: Vec<Aircraft>,
+    pub colors: HashMap<String, String>, // 每架飞机的显示颜色，按 ICAO 索引
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub trails: HashMap<String, Vec<TrackPoint>>, // 每架飞机最近的航迹点（按时间先后），未启用时为空
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub integrity: HashMap<String, String>, // 每架飞机 NIC 对应的完整性等级（如 "<0.1 NM"），未启用时为空
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub predicted: HashMap<String, TrackPoint>, // 每架飞机在下一次发送时刻的外推位置，供前端插值
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub external_tracks: HashMap<String, TrackPoint>, // 回放航迹解码并平滑后的最新位置，按 ICAO 索引
+    pub tick: u64,      // 批次序号，每次发送加 1
+    pub timestamp: u64, // 批次生成时的系统时间 (Unix 毫秒)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replay_offset_ms: Option<u64>, // 文件回放批次相对录制首帧的时间 (ms)，实时批次为 None
+}
+
+/// 事件发送的最小间隔 (ms)，即最高 20 Hz
+///
+/// 请求的步进间隔小于该值时，后台任务不会更快地发送，而是在每次发送前
+/// 连续执行多个模拟步进，保持模拟速率不变，同时避免空转和淹没前端/网络。
+const MIN_EMIT_INTERVAL_MS: u64 = 50;
+
+/// 停止模拟时等待后台任务退出的最长时间
+const WORKER_JOIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 通知后台任务停止，并在 `timeout` 内等待其退出、回收句柄；超时则保留句柄并返回错误
+///
+/// `timeout` 为零时只检查上一次的任务是否已退出，不发送停止信号。
+fn join_worker(worker: &Mutex<Option<Worker>>, timeout: Duration) -> Result<(), String> {
+    let mut slot = worker.lock().map_err(|e| e.to_string())?;
+    let Some(worker) = slot.take() else {
+        return Ok(());
+    };
+    if !timeout.is_zero() {
+        // 任务已退出时接收端已释放，发送失败可忽略
+        let _ = worker.shutdown.send(true);
+    }
+    let deadline = Instant::now() + timeout;
+    while !worker.task.inner().is_finished() {
+        if Instant::now() >= deadline {
+            *slot = Some(worker);
+            return Err("Previous simulation task is still running".to_string());
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    if async_runtime::block_on(worker.task).is_err() {
+        eprintln!("[Rust] Simulation task panicked");
+    }
+    Ok(())
+}
+
+/// 暂停时不推进模拟，网络输出保持连接并按 `keepalive` 间隔发送心跳；返回是否处于暂停
+fn paused_tick(
+    is_paused: &Mutex<bool>,
+    outputs: &Mutex<Vec<OutputServer>>,
+    keepalive: Option<Duration>,
+    last_keepalive: &mut Instant,
+) -> bool {
+    if !*is_paused.lock().unwrap_or_else(PoisonError::into_inner) {
+        return false;
+    }
+    if keepalive.is_some_and(|every| last_keepalive.elapsed() >= every) {
+        if let Ok(outputs) = outputs.lock() {
+            for server in outputs.iter() {
+                server.send_keepalive();
+            }
+        }
+        *last_keepalive = Instant::now();
+    }
+    true
+}
+
+/// 等待下一个 tick；收到停止信号或停止信号的发送端已释放时立即返回 false
+async fn next_tick(ticker: &mut Interval, shutdown: &mut watch::Receiver<bool>) -> bool {
+    if *shutdown.borrow() {
+        return false;
+    }
+    tokio::select! {
+        _ = ticker.tick() => true,
+        _ = shutdown.changed() => false,
+    }
+}
+
+/// 将运行标志置为停止并清除中毒标记，使前端看到的状态与已退出的后台任务一致
+fn mark_stopped(is_running: &Mutex<bool>) {
+    *is_running.lock().unwrap_or_else(PoisonError::into_inner) = false;
+    is_running.clear_poison();
+}
+
+/// 后台任务是否应继续运行；运行标志的锁中毒时视为停止
+fn should_keep_running(is_running: &Mutex<bool>) -> bool {
+    match is_running.lock() {
+        Ok(running) => *running,
+        Err(poisoned) => {
+            eprintln!("[Rust] Running flag poisoned, stopping simulation task");
+            *poisoned.into_inner() = false;
+            is_running.clear_poison();
+            false
+        }
+    }
+}
+
+/// 获取模拟器锁；其他线程持锁时 panic 导致中毒的，模拟状态可能不完整，
+/// 此时记录日志并标记停止，由调用方退出后台任务
+fn lock_simulator<'a>(
+    simulator: &'a Mutex<AdsbSimulator>,
+    is_running: &Mutex<bool>,
+) -> Option<MutexGuard<'a, AdsbSimulator>> {
+    match simulator.lock() {
+        Ok(sim) => Some(sim),
+        Err(_) => {
+            eprintln!("[Rust] Simulator state poisoned, stopping simulation task");
+            simulator.clear_poison();
+            mark_stopped(is_running);
+            None
+        }
+    }
+}
+
+/// 根据请求的步进间隔和显示刷新率计算 (实际发送间隔 ms, 每次发送的模拟步数)
+///
+/// 模拟按步进间隔推进，前端只收到每个显示周期内的最后状态；
+/// 发送间隔总是步进间隔的整数倍，且不小于 MIN_EMIT_INTERVAL_MS。
+fn emit_plan(requested_interval_ms: u64, display_rate_hz: Option<f64>) -> (u64, u32) {
+    let requested = requested_interval_ms.max(1);
+    let display_interval = display_rate_hz
+        .filter(|hz| *hz > 0.0)
+        .map_or(0, |hz| (1000.0 / hz).round() as u64);
+    if requested >= display_interval.max(MIN_EMIT_INTERVAL_MS) {
+        return (requested, 1);
+    }
+
+    let steps = ((display_interval as f64 / requested as f64).round() as u64)
+        .max(MIN_EMIT_INTERVAL_MS.div_ceil(requested));
+    (requested * steps, steps as u32)
+}
+
+/// 后台任务的发送节拍：首个 tick 立即触发，处理耗时超过间隔时顺延，不连续补发
+fn emit_ticker(interval_ms: u64) -> Interval {
+    let mut ticker = tokio::time::interval(Duration::from_millis(interval_ms));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    ticker
+}
+
+/// 推进 `steps` 步模拟并生成批次事件，`trail_points` 为每架飞机附带的最近航迹点数
+///
+/// 下一批次同样推进 `steps` 步，因此按 `steps` 外推各飞机在下一次发送时的位置。
+fn build_batch(
+    simulator: &mut AdsbSimulator,
+    steps: u32,
+    tick: u64,
+    trail_points: usize,
+) -> AdsbBatchEvent {
+    for _ in 0..steps {
+        simulator.update_positions();
+    }
+    let messages = simulator.generate_all_messages();
+    let aircrafts = simulator.get_aircrafts().clone();
+    let history = simulator.get_history();
+    let trails = if trail_points == 0 {
+        HashMap::new()
+    } else {
+        aircrafts
+            .iter()
+            .filter_map(|a| {
+                let track = history.get(&a.id)?;
+                let recent = track.iter().skip(track.len().saturating_sub(trail_points));
+                Some((a.id.clone(), recent.copied().collect()))
+            })
+            .collect()
+    };
+    let messages = simulator.apply_reception(messages);
+    AdsbBatchEvent {
+        messages: simulator.apply_impairment(messages),
+        colors: aircrafts
+            .iter()
+            .map(|a| (a.id.clone(), export::icao_color(&a.id)))
+            .collect(),
+        trails,
+        integrity: HashMap::new(),
+        predicted: simulator.predict_positions(steps).into_iter().collect(),
+        external_tracks: HashMap::new(),
+        aircrafts,
+        tick,
+        timestamp: adsb::unix_time_ms(),
+        replay_offset_ms: None,
+    }
+}
+
+/// 当前时刻的批次快照：只读取飞机状态与配色，不生成消息，
+/// 因此不会改变 CPR 奇偶帧交替，也不会消耗接收/损伤模型的随机数
+#[cfg(feature = "protobuf")]
+fn snapshot_batch(simulator: &AdsbSimulator) -> AdsbBatchEvent {
+    let aircrafts = simulator.get_aircrafts().clone();
+    AdsbBatchEvent {
+        messages: Vec::new(),
+        colors: aircrafts
+            .iter()
+            .map(|a| (a.id.clone(), export::icao_color(&a.id)))
+            .collect(),
+        trails: HashMap::new(),
+        integrity: HashMap::new(),
+        predicted: HashMap::new(),
+        external_tracks: HashMap::new(),
+        aircrafts,
+        tick: 0,
+        timestamp: adsb::unix_time_ms(),
+        replay_offset_ms: None,
+    }
+}
+
+/// 各飞机 NIC 对应的可读完整性等级，按 ICAO 索引
+fn integrity_labels(aircrafts: &[Aircraft]) -> HashMap<String, String> {
+    aircrafts
+        .iter()
+        .map(|a| (a.id.clone(), physics::integrity_label(a.nic)))
+        .collect()
+}
+
+/// 按配置创建模拟器并生成初始机队
+fn build_simulator(config: &SimulationConfig) -> Result<AdsbSimulator, String> {
+    let mut simulator = match config.seed {
+        Some(seed) => AdsbSimulator::with_seed(config.center_lat, config.center_lng, seed),
+        None => AdsbSimulator::new(config.center_lat, config.center_lng),
+    };
+    simulator.set_speed_coupling(config.speed_altitude_coupling);
+    simulator.set_cadence(config.message_cadence);
+    simulator.set_phase_speeds(config.phase_speeds);
+    simulator.set_semicircular_levels(config.semicircular_levels);
+    simulator.set_acquisition_ticks(config.acquisition_ticks);
+    simulator.set_impairment(config.impairment);
+    simulator.set_reception(config.reception)?;
+    simulator.set_airport_beacon(config.airport_beacon.clone())?;
+    simulator.set_wind_layers(config.wind_layers.clone())?;
+    simulator.set_tick_interval_ms(config.update_interval_ms);
+    simulator.set_history_budget(config.history_budget_points);
+    simulator.set_mock_ranges(MockRanges {
+        min_altitude: config.min_altitude,
+        max_altitude: config.max_altitude,
+        min_speed: config.min_speed,
+        max_speed: config.max_speed,
+    })?;
+    simulator.set_max_radius_deg(config.max_radius_deg)?;
+
+    let remaining = config
+        .aircraft_count
+        .saturating_sub(config.initial_aircraft.len());
+    simulator.generate_mock_aircrafts(remaining)?;
+
+    // 预设飞机优先：与其 ICAO 冲突的生成飞机会被替换
+    simulator.retain_aircrafts(|generated| {
+        !config
+            .initial_aircraft
+            .iter()
+            .any(|a| a.id.eq_ignore_ascii_case(&generated.id))
+    });
+    for aircraft in &config.initial_aircraft {
+        simulator.add_aircraft(aircraft.clone())?;
+    }
+    // 每个 tick 推进 1 模拟秒
+    simulator.stagger_fleet(config.startup_ramp_s);
+
+    Ok(simulator)
 }
 
 /// 启动模拟
@@ -49,6 +444,15 @@ fn start_simulation(
     config: Option<SimulationConfig>,
 ) -> Result<String, String> {
     let config = config.unwrap_or_default();
+    // 已导入场景时以导入的机队替换预设与随机生成的飞机
+    let config = match state.scenario.lock().map_err(|e| e.to_string())?.clone() {
+        Some(fleet) => SimulationConfig {
+            aircraft_count: fleet.len(),
+            initial_aircraft: fleet,
+            ..config
+        },
+        None => config,
+    };
     
     // 检查是否已在运行
     {
@@ -56,63 +460,195 @@ fn start_simulation(
         if *is_running {
             return Err("Simulation already running".to_string());
         }
+        if file_replay_active(&state.file_replay)? {
+            return Err("Stop the file replay before starting the simulation".to_string());
+        }
     }
+    // 上一次的后台任务尚未退出时拒绝启动，避免两个任务同时发送批次
+    join_worker(&state.worker, Duration::ZERO)?;
+    config.track_smoothing.validate()?;
 
     // 初始化模拟器
     {
+        let new_simulator = build_simulator(&config)?;
         let mut simulator = state.simulator.lock().map_err(|e| e.to_string())?;
-        *simulator = AdsbSimulator::new(config.center_lat, config.center_lng);
-        simulator.generate_mock_aircrafts(config.aircraft_count);
+        *simulator = new_simulator;
     }
 
     // 设置运行状态
     {
         let mut is_running = state.is_running.lock().map_err(|e| e.to_string())?;
         *is_running = true;
+        *state.is_paused.lock().map_err(|e| e.to_string())? = false;
+    }
+    state.latency.reset();
+    state.histogram.lock().map_err(|e| e.to_string())?.reset();
+    state.counters.reset();
+    {
+        let mut receivers = state.receivers.lock().map_err(|e| e.to_string())?;
+        *receivers = ReceiverNetwork::new(config.receivers.clone());
+    }
+    {
+        let mut recent = state.recent.lock().map_err(|e| e.to_string())?;
+        *recent = RecentEvents::new(config.recent_window_s, config.recent_max_batches);
     }
+    *state.track_smoothing.lock().map_err(|e| e.to_string())? = config.track_smoothing;
 
-    // 克隆状态用于线程
+    // 克隆状态用于后台任务
     let simulator = Arc::clone(&state.simulator);
     let is_running = Arc::clone(&state.is_running);
-    let interval = config.update_interval_ms;
+    let is_paused = Arc::clone(&state.is_paused);
+    let trail_points = config.trail_points;
+    let with_integrity = config.integrity_labels;
+    let keepalive = config.keepalive_interval_ms.map(Duration::from_millis);
+    let latency = Arc::clone(&state.latency);
+    let histogram = Arc::clone(&state.histogram);
+    let counters = Arc::clone(&state.counters);
+    let receivers = Arc::clone(&state.receivers);
+    let outputs = Arc::clone(&state.outputs);
+    let recent = Arc::clone(&state.recent);
+    let replay = Arc::clone(&state.replay);
+    let recorder = Arc::clone(&state.recorder);
+    #[cfg(feature = "uat")]
+    let uat = {
+        let channel = Arc::clone(&state.uat);
+        channel
+            .lock()
+            .map_err(|e| e.to_string())?
+            .set_aircraft(&config.uat_aircraft);
+        channel
+    };
+    let (interval, steps_per_emit) = emit_plan(config.update_interval_ms, config.display_rate_hz);
+    if steps_per_emit > 1 {
+        println!(
+            "[Rust] Stepping every {}ms, emitting every {}ms with {} steps per batch",
+            config.update_interval_ms, interval, steps_per_emit
+        );
+    }
+    let mut airspace = AirspaceMonitor::new(config.no_fly_zones.clone());
 
-    // 启动后台线程
-    thread::spawn(move || {
+    // 启动后台任务：按发送间隔唤醒，停止信号到达时不必等满当前间隔
+    let (shutdown, mut stop_signal) = watch::channel(false);
+    let task = async_runtime::spawn(async move {
         let mut tick = 0u64;
-        
-        loop {
+        let mut last_keepalive = Instant::now();
+        let mut ticker = emit_ticker(interval);
+
+        while next_tick(&mut ticker, &mut stop_signal).await {
             // 检查是否应该停止
-            {
-                let running = is_running.lock().unwrap();
-                if !*running {
-                    break;
-                }
+            if !should_keep_running(&is_running) {
+                break;
+            }
+
+            if paused_tick(&is_paused, &outputs, keepalive, &mut last_keepalive) {
+                continue;
             }
 
             // 更新飞机位置并生成消息
-            let (messages, aircrafts) = {
-                let mut sim = simulator.lock().unwrap();
-                sim.update_positions();
-                (sim.generate_all_messages(), sim.get_aircrafts().clone())
+            let (mut event, completed) = {
+                let Some(mut sim) = lock_simulator(&simulator, &is_running) else {
+                    break;
+                };
+                let event = build_batch(&mut sim, steps_per_emit, tick, trail_points);
+                (event, sim.take_completed_flights())
             };
+            if with_integrity {
+                event.integrity = integrity_labels(&event.aircrafts);
+            }
 
-            // 发送事件到前端
-            let event = AdsbBatchEvent {
-                messages,
-                aircrafts,
-                timestamp: tick * interval,
-            };
+            // 混合模式：并入录制回放的背景流量（每步对应 1 s 录制时间）
+            if let Ok(mut overlay) = replay.lock() {
+                if let Some(active) = overlay.as_mut() {
+                    let collisions = active.merge_into(&mut event, steps_per_emit as u64 * 1000);
+                    if !collisions.is_empty() {
+                        if let Err(e) = app.emit("replay-collision", &collisions) {
+                            eprintln!("[Rust] Failed to emit replay collision: {}", e);
+                        }
+                    }
+                    if active.is_finished() {
+                        println!("[Rust] Replay overlay finished");
+                        *overlay = None;
+                    }
+                }
+            }
+            let produced = Instant::now();
+
+            // 已落地的航班
+            for completion in &completed {
+                if let Err(e) = app.emit("flight-completed", completion) {
+                    eprintln!("[Rust] Failed to emit flight completion: {}", e);
+                }
+            }
+
+            // UAT 飞机的消息改由 978 MHz 通道发送
+            #[cfg(feature = "uat")]
+            let uat_messages = uat
+                .lock()
+                .map(|channel| channel.split(&mut event))
+                .unwrap_or_default();
+            #[cfg(feature = "uat")]
+            if !uat_messages.is_empty() {
+                if let Err(e) = app.emit("uat-batch", &uat_messages) {
+                    eprintln!("[Rust] Failed to emit UAT batch: {}", e);
+                }
+            }
+
+            if let Ok(mut histogram) = histogram.lock() {
+                histogram.record(&event.messages);
+            }
+            counters.record(&event.messages, event.aircrafts.len(), steps_per_emit as u64);
+
+            // 各接收站接收统计
+            if let Ok(mut receivers) = receivers.lock() {
+                receivers.observe(&event.messages, &event.aircrafts);
+            }
+
+            // 网络输出：只写入各客户端缓冲区，不等待发送
+            if let Ok(outputs) = outputs.lock() {
+                for server in outputs.iter() {
+                    #[cfg(feature = "uat")]
+                    if server.format() == OutputFormat::Uat {
+                        server.publish(&uat_messages);
+                        continue;
+                    }
+                    server.publish_aircraft(&event.aircrafts, &event.messages);
+                    server.publish(&event.messages);
+                    server.publish_batch(&event);
+                }
+            }
+
+            // 消息流录制：与批次时间戳一致，写入失败时停止录制
+            if let Ok(mut active) = recorder.lock() {
+                if let Some(writer) = active.as_mut() {
+                    if let Err(e) = writer.record(event.timestamp, &event.messages) {
+                        eprintln!("[Rust] Recording stopped: {}", e);
+                        *active = None;
+                    }
+                }
+            }
+
+            // 禁飞区检查
+            for violation in airspace.check(&event.aircrafts) {
+                if let Err(e) = app.emit("airspace-violation", &violation) {
+                    eprintln!("[Rust] Failed to emit violation: {}", e);
+                }
+            }
 
-            if let Err(e) = app.emit("adsb-batch", &event) {
+            // 发送事件到前端，并记录从数据产生到发送完成的延迟
+            if let Err(e) = latency.time(produced, || app.emit("adsb-batch", &event)) {
                 eprintln!("[Rust] Failed to emit event: {}", e);
             }
 
-            tick += 1;
-            thread::sleep(Duration::from_millis(interval));
+            if let Ok(mut recent) = recent.lock() {
+                recent.push(event);
+            }
+
+            tick = tick.saturating_add(1);
         }
 
-        println!("[Rust] Simulation thread stopped");
+        println!("[Rust] Simulation task stopped");
     });
+    *state.worker.lock().map_err(|e| e.to_string())? = Some(Worker { task, shutdown });
 
     Ok("Simulation started".to_string())
 }
@@ -120,11 +656,132 @@ fn start_simulation(
 /// 停止模拟
 #[tauri::command]
 fn stop_simulation(state: State<SimulatorState>) -> Result<String, String> {
-    let mut is_running = state.is_running.lock().map_err(|e| e.to_string())?;
-    *is_running = false;
+    *state.is_running.lock().map_err(|e| e.to_string())? = false;
+    *state.is_paused.lock().map_err(|e| e.to_string())? = false;
+    join_worker(&state.worker, WORKER_JOIN_TIMEOUT)?;
+    stop_outputs(&state.outputs)?;
     Ok("Simulation stopped".to_string())
 }
 
+/// 关闭全部网络输出端口并断开客户端
+///
+/// 各格式的端口都只转发模拟数据，模拟停止后不再有数据来源，统一随模拟一同关闭。
+fn stop_outputs(outputs: &Mutex<Vec<OutputServer>>) -> Result<(), String> {
+    for server in outputs.lock().map_err(|e| e.to_string())?.drain(..) {
+        server.stop();
+    }
+    Ok(())
+}
+
+/// 启动 TCP 网络输出端口，`buffer_frames` 为每个客户端缓冲的最大帧数；
+/// 默认只监听本机，`external` 为 true 时对外部网络开放
+#[tauri::command]
+fn start_output_server(
+    state: State<SimulatorState>,
+    format: OutputFormat,
+    port: u16,
+    buffer_frames: Option<usize>,
+    external: Option<bool>,
+) -> Result<String, String> {
+    let mut outputs = state.outputs.lock().map_err(|e| e.to_string())?;
+    if port != 0 && outputs.iter().any(|server| server.port() == port) {
+        return Err(format!("Port {} already in use by an output server", port));
+    }
+    let capacity = buffer_frames.unwrap_or(network::DEFAULT_CLIENT_BUFFER_FRAMES);
+    let server = OutputServer::start(format, port, capacity, external.unwrap_or(false))?;
+    let message = format!("{:?} output listening on port {}", server.format(), server.port());
+    outputs.push(server);
+    Ok(message)
+}
+
+/// 启动 Beast 二进制输出端口，供 readsb 等解码器以 `--net-connector` 接入；
+/// 使用 `stop_output_server` 停止，端口也随 `stop_simulation` 关闭
+#[tauri::command]
+fn start_beast_server(
+    state: State<SimulatorState>,
+    port: u16,
+    external: Option<bool>,
+) -> Result<String, String> {
+    start_output_server(state, OutputFormat::Beast, port, None, external)
+}
+
+/// 启动 SBS-1 BaseStation CSV 输出端口（Virtual Radar Server 等使用）；
+/// 使用 `stop_output_server` 停止，端口也随 `stop_simulation` 关闭
+#[tauri::command]
+fn start_sbs_server(
+    state: State<SimulatorState>,
+    port: u16,
+    external: Option<bool>,
+) -> Result<String, String> {
+    start_output_server(state, OutputFormat::Sbs, port, None, external)
+}
+
+/// 启动 WebSocket 输出端口，每个批次以 JSON 文本消息推送给所有订阅者；
+/// 发送缓冲区积压的订阅者会被断开，端口随 `stop_simulation` 关闭
+#[tauri::command]
+fn start_ws_server(
+    state: State<SimulatorState>,
+    port: u16,
+    external: Option<bool>,
+) -> Result<String, String> {
+    let capacity = Some(network::DEFAULT_WS_BUFFER_BATCHES);
+    start_output_server(state, OutputFormat::WebSocket, port, capacity, external)
+}
+
+/// 停止指定端口的网络输出并断开其客户端
+#[tauri::command]
+fn stop_output_server(state: State<SimulatorState>, port: u16) -> Result<String, String> {
+    let mut outputs = state.outputs.lock().map_err(|e| e.to_string())?;
+    let index = outputs
+        .iter()
+        .position(|server| server.port() == port)
+        .ok_or_else(|| format!("No output server on port {}", port))?;
+    outputs.remove(index).stop();
+    Ok(format!("Output server on port {} stopped", port))
+}
+
+/// 获取最近 `seconds` 秒内发送的批次，供后加入的前端补齐数据
+#[tauri::command]
+fn get_recent_events(
+    state: State<SimulatorState>,
+    seconds: f64,
+) -> Result<Vec<AdsbBatchEvent>, String> {
+    let recent = state.recent.lock().map_err(|e| e.to_string())?;
+    Ok(recent.recent(seconds))
+}
+
+/// 暂停模拟：停止推进和发送，网络客户端保持连接
+#[tauri::command]
+fn pause_simulation(state: State<SimulatorState>) -> Result<String, String> {
+    if !*state.is_running.lock().map_err(|e| e.to_string())? {
+        return Err("Simulation not running".to_string());
+    }
+    *state.is_paused.lock().map_err(|e| e.to_string())? = true;
+    Ok("Simulation paused".to_string())
+}
+
+/// 从暂停处继续模拟
+#[tauri::command]
+fn resume_simulation(state: State<SimulatorState>) -> Result<String, String> {
+    if !*state.is_running.lock().map_err(|e| e.to_string())? {
+        return Err("Simulation not running".to_string());
+    }
+    *state.is_paused.lock().map_err(|e| e.to_string())? = false;
+    Ok("Simulation resumed".to_string())
+}
+
+/// 在指定距离处可被接收的最低飞机高度 (ft)，`receiver_altitude` 缺省为 0
+#[tauri::command]
+fn min_receivable_altitude(range_nm: f64, receiver_altitude: Option<f64>) -> Result<f64, String> {
+    if !range_nm.is_finite() || range_nm < 0.0 {
+        return Err(format!("Invalid range: {}", range_nm));
+    }
+    Ok(geo::min_receivable_altitude_ft(
+        range_nm,
+        receiver_altitude.unwrap_or(0.0),
+    ))
+}
+
 /// 获取当前飞机数据
 #[tauri::command]
 fn get_aircrafts(state: State<SimulatorState>) -> Result<Vec<Aircraft>, String> {
@@ -132,11 +789,707 @@ fn get_aircrafts(state: State<SimulatorState>) -> Result<Vec<Aircraft>, String>
     Ok(simulator.get_aircrafts().clone())
 }
 
-/// 检查模拟状态
+/// 按 ICAO 地址获取单架飞机，供详情面板刷新，不存在时返回错误
+#[tauri::command]
+fn get_aircraft(state: State<SimulatorState>, icao: String) -> Result<Aircraft, String> {
+    let simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    simulator
+        .get_aircraft(&icao)
+        .cloned()
+        .ok_or_else(|| format!("Aircraft {} not found", icao))
+}
+
+/// 运行中添加一架飞机，ICAO 地址重复时返回错误，成功时返回飞机总数
+#[tauri::command]
+fn add_aircraft(state: State<SimulatorState>, aircraft: Aircraft) -> Result<usize, String> {
+    let mut simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    let id = aircraft.id.clone();
+    let total = simulator.add_aircraft(aircraft)?;
+    println!("[Rust] Added aircraft {} ({} total)", id, total);
+    Ok(total)
+}
+
+/// 从 JSON 文件导入固定机队，之后启动的模拟使用该机队而不是随机生成，返回飞机数
+///
+/// 任一飞机校验失败时拒绝整个文件，已导入的场景保持不变。
+#[tauri::command]
+fn load_scenario(state: State<SimulatorState>, path: String) -> Result<usize, String> {
+    if *state.is_running.lock().map_err(|e| e.to_string())? {
+        return Err("Stop the simulation before loading a scenario".to_string());
+    }
+    let fleet = adsb::read_aircrafts_json(&path)?;
+    let count = fleet.len();
+    *state.scenario.lock().map_err(|e| e.to_string())? = Some(fleet);
+    println!("[Rust] Loaded scenario {} with {} aircraft", path, count);
+    Ok(count)
+}
+
+/// 将当前机队保存为 JSON 场景快照，可用 `load_scenario` 重新加载，返回飞机数
+///
+/// 只保存飞机状态，航路与进近不包含在快照中。
+#[tauri::command]
+fn save_scenario(state: State<SimulatorState>, path: String) -> Result<usize, String> {
+    let simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    let json = simulator.export_aircrafts_to_json()?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    let count = simulator.get_aircrafts().len();
+    println!("[Rust] Saved {} aircraft to scenario {}", count, path);
+    Ok(count)
+}
+
+/// 清除导入的场景，之后启动的模拟恢复随机生成机队
+#[tauri::command]
+fn clear_scenario(state: State<SimulatorState>) -> Result<(), String> {
+    *state.scenario.lock().map_err(|e| e.to_string())? = None;
+    Ok(())
+}
+
+/// 运行中按 ICAO 地址移除一架飞机，下一个 tick 起不再发送其消息，返回剩余飞机数
+#[tauri::command]
+fn remove_aircraft(state: State<SimulatorState>, icao: String) -> Result<usize, String> {
+    let mut simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    if !simulator.remove_aircraft(&icao) {
+        return Err(format!("Aircraft {} not found", icao));
+    }
+    let total = simulator.get_aircrafts().len();
+    println!("[Rust] Removed aircraft {} ({} remaining)", icao, total);
+    Ok(total)
+}
+
+/// 外推各飞机 `steps` 个 tick (缺省 1) 后的位置，按 ICAO 索引
+#[tauri::command]
+fn predict_positions(
+    state: State<SimulatorState>,
+    steps: Option<u32>,
+) -> Result<BTreeMap<String, TrackPoint>, String> {
+    let simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    Ok(simulator.predict_positions(steps.unwrap_or(1)))
+}
+
+/// 获取单架飞机各类型消息的发送计划
+#[tauri::command]
+fn get_schedule(state: State<SimulatorState>, id: String) -> Result<Vec<ScheduleEntry>, String> {
+    let simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    simulator
+        .get_schedule(&id)
+        .ok_or_else(|| format!("Aircraft {} not found", id))
+}
+
+/// 为指定飞机分配航路点飞行计划，航路飞完后按计划盘旋、循环或落地
+#[tauri::command]
+fn assign_route(state: State<SimulatorState>, icao: String, plan: FlightPlan) -> Result<(), String> {
+    let mut simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    simulator.assign_route(&icao, plan)
+}
+
+/// 设置飞机的紧急状态 (劫机、无线电失效、一般紧急)，`kind` 为空时解除
+#[tauri::command]
+fn set_emergency(
+    state: State<SimulatorState>,
+    icao: String,
+    kind: Option<EmergencyKind>,
+) -> Result<(), String> {
+    let mut simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    simulator.set_emergency(&icao, kind)
+}
+
+/// 创建一个沿大圆航线飞行的航班（含爬升与下降剖面）
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+fn create_flight(
+    state: State<SimulatorState>,
+    callsign: String,
+    from_lat: f64,
+    from_lng: f64,
+    to_lat: f64,
+    to_lng: f64,
+    cruise_alt: f64,
+    speed: f64,
+) -> Result<Aircraft, String> {
+    let mut simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    simulator.create_flight(
+        &callsign,
+        (from_lat, from_lng),
+        (to_lat, to_lng),
+        cruise_alt,
+        speed,
+    )
+}
+
+/// 在定位点生成垂直堆叠的等待航线机群
+#[tauri::command]
+fn create_holding_stack(
+    state: State<SimulatorState>,
+    fix_lat: f64,
+    fix_lng: f64,
+    count: usize,
+    base_altitude: f64,
+    separation_ft: f64,
+) -> Result<Vec<Aircraft>, String> {
+    let mut simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    simulator.create_holding_stack((fix_lat, fix_lng), count, base_altitude, separation_ft)
+}
+
+/// 引导飞机沿 3° 下滑道在指定跑道着陆
+#[tauri::command]
+fn land_aircraft(
+    state: State<SimulatorState>,
+    id: String,
+    runway_lat: f64,
+    runway_lng: f64,
+    runway_heading: f64,
+) -> Result<(), String> {
+    let mut simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    simulator.land_aircraft(
+        &id,
+        Runway {
+            lat: runway_lat,
+            lng: runway_lng,
+            heading: runway_heading,
+        },
+    )
+}
+
+/// 生成两条平行跑道的同时离场序列
+#[tauri::command]
+fn create_parallel_departures(
+    state: State<SimulatorState>,
+    runway_a: Runway,
+    runway_b: Runway,
+    count: usize,
+    interval_s: u32,
+) -> Result<Vec<Aircraft>, String> {
+    let mut simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    simulator.create_parallel_departures(runway_a, runway_b, count, interval_s)
+}
+
+/// 设置单架飞机的设备能力档位
+#[tauri::command]
+fn set_capability_profile(
+    state: State<SimulatorState>,
+    id: String,
+    profile: CapabilityProfile,
+) -> Result<String, String> {
+    let mut simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    if simulator.set_capability_profile(&id, profile) {
+        Ok(format!("Aircraft {} set to {:?}", id, profile))
+    } else {
+        Err(format!("Aircraft {} not found", id))
+    }
+}
+
+/// 添加地址欺骗发射源：以目标飞机的 ICAO 在偏移 (纬度, 经度) 位置发送消息
+#[tauri::command]
+fn add_spoofer(
+    state: State<SimulatorState>,
+    target_id: String,
+    offset: (f64, f64),
+) -> Result<String, String> {
+    let mut simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    simulator.add_spoofer(&target_id, offset)?;
+    Ok(format!("Spoofer added for {}", target_id))
+}
+
+/// 移除所有地址欺骗发射源
+#[tauri::command]
+fn clear_spoofers(state: State<SimulatorState>) -> Result<String, String> {
+    let mut simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    simulator.clear_spoofers();
+    Ok("Spoofers cleared".to_string())
+}
+
+/// 获取当前随机数种子
+#[tauri::command]
+fn get_seed(state: State<SimulatorState>) -> Result<u64, String> {
+    let simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    Ok(simulator.get_seed())
+}
+
+/// 设置随机数种子（重置内部随机数生成器）
+#[tauri::command]
+fn set_seed(state: State<SimulatorState>, seed: u64) -> Result<String, String> {
+    let mut simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    simulator.set_seed(seed);
+    Ok(format!("Seed set to {}", seed))
+}
+
+/// 设置单架飞机的监视数据来源 (ADS-B / TIS-B / ADS-R)
+#[tauri::command]
+fn set_aircraft_source(
+    state: State<SimulatorState>,
+    id: String,
+    source: AdsbSource,
+) -> Result<String, String> {
+    let mut simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    if simulator.set_source(&id, source) {
+        Ok(format!("Aircraft {} source set to {:?}", id, source))
+    } else {
+        Err(format!("Aircraft {} not found", id))
+    }
+}
+
+/// 设置分层风表（运行中立即生效）
+#[tauri::command]
+fn set_wind_layers(state: State<SimulatorState>, layers: Vec<WindLayer>) -> Result<String, String> {
+    let mut simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    let count = layers.len();
+    simulator.set_wind_layers(layers)?;
+    Ok(format!("Wind model set with {} layers", count))
+}
+
+/// 设置全高度统一的风（替换分层风表，运行中立即生效）
+#[tauri::command]
+fn set_wind(state: State<SimulatorState>, speed_kts: f64, direction_deg: f64) -> Result<(), String> {
+    let mut simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    simulator.set_wind(speed_kts, direction_deg)
+}
+
+/// 获取当前场景的 GeoJSON 字符串
+#[tauri::command]
+fn get_geojson(state: State<SimulatorState>) -> Result<String, String> {
+    let simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    Ok(export::aircrafts_to_geojson(simulator.get_aircrafts()).to_string())
+}
+
+/// 以对齐文本表格输出当前机队（调试用）
+#[tauri::command]
+fn dump_fleet_table(state: State<SimulatorState>) -> Result<String, String> {
+    let simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    Ok(export::fleet_table(simulator.get_aircrafts()))
+}
+
+/// 导出当前场景为 GeoJSON 文件
+#[tauri::command]
+fn export_geojson(state: State<SimulatorState>, path: String) -> Result<String, String> {
+    let simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    export::export_geojson(simulator.get_aircrafts(), &path)?;
+    Ok(format!("Exported {} aircraft to {}", simulator.get_aircrafts().len(), path))
+}
+
+/// 导出记录的航迹为 CZML 文件（CesiumJS 三维回放）
+#[tauri::command]
+fn export_czml(state: State<SimulatorState>, path: String) -> Result<String, String> {
+    let simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    export::export_czml(&simulator, &path)?;
+    Ok(format!("Exported {} tracks to {}", simulator.get_history().len(), path))
+}
+
+/// 按 readsb/tar1090 历史格式导出记录的航迹，`dir` 下每架飞机一个 gzip 航迹文件
+#[tauri::command]
+fn export_tar1090_traces(state: State<SimulatorState>, dir: String) -> Result<String, String> {
+    let simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    let count = export::export_tar1090_traces(&simulator, &dir)?;
+    Ok(format!("Exported {} traces to {}", count, dir))
+}
+
+/// 将单条消息导出为逐字段讲解的 HTML 页面，`bit_order` 缺省为最高位在前
+#[tauri::command]
+fn export_message_explainer(
+    hex: String,
+    path: String,
+    bit_order: Option<BitOrder>,
+) -> Result<String, String> {
+    export::export_message_explainer(&hex, &path, bit_order.unwrap_or_default())?;
+    Ok(format!("Exported explainer for {} to {}", hex.trim(), path))
+}
+
+/// 获取消息的十六进制、字节数组和比特串表示，并标出各字段起始位
+#[tauri::command]
+fn get_message_representation(
+    hex: String,
+    bit_order: Option<BitOrder>,
+) -> Result<MessageRepresentation, String> {
+    decoder::message_representation(&hex, bit_order.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+/// 由任意 DF 与十六进制比特构造消息，校验字段按 CRC 或 AP 模式计算（用于模糊测试）
+#[tauri::command]
+fn assemble_raw(
+    df: u8,
+    payload_hex: String,
+    use_ap: bool,
+    address: Option<String>,
+) -> Result<String, String> {
+    let payload = u128::from_str_radix(payload_hex.trim(), 16).map_err(|e| e.to_string())?;
+    let ap_address = if use_ap {
+        let address = address.ok_or("AP mode requires an address")?;
+        Some(u32::from_str_radix(address.trim(), 16).map_err(|e| e.to_string())?)
+    } else {
+        None
+    };
+    adsb::assemble_raw(df, payload, ap_address)
+}
+
+/// 模拟 Mode S 询问 (UF4/5/20/21)，返回指定飞机的应答报文，无 Mode S 应答机时返回 None
+#[tauri::command]
+fn interrogate(
+    state: State<SimulatorState>,
+    icao: String,
+    uf: u8,
+) -> Result<Option<String>, String> {
+    let simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    simulator.interrogate(&icao, uf)
+}
+
+/// 立即发送一组 DF11 全呼应答（用于测试接收端的目标捕获），返回发送的报文
+#[tauri::command]
+fn emit_all_call_burst(state: State<SimulatorState>) -> Result<Vec<AdsbEvent>, String> {
+    let burst = state
+        .simulator
+        .lock()
+        .map_err(|e| e.to_string())?
+        .all_call_burst();
+    let outputs = state.outputs.lock().map_err(|e| e.to_string())?;
+    for server in outputs.iter() {
+        // UAT 端口只输出 978 MHz 报文
+        #[cfg(feature = "uat")]
+        if server.format() == OutputFormat::Uat {
+            continue;
+        }
+        server.publish(&burst);
+    }
+    println!("[Rust] Emitted {} all-call replies", burst.len());
+    Ok(burst)
+}
+
+/// 估算当前信道损伤下可完成全局 CPR 解码的飞机比例
+#[tauri::command]
+fn analyze_cpr_robustness(state: State<SimulatorState>, samples: usize) -> Result<f64, String> {
+    let simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    Ok(simulator.analyze_cpr_robustness(samples))
+}
+
+/// 按配置运行指定步数并保存为金标准文件（回归测试用）
+#[tauri::command]
+fn capture_golden(config: SimulationConfig, ticks: u32, path: String) -> Result<String, String> {
+    let record = golden::save_golden(&config, ticks, &path)?;
+    Ok(format!(
+        "Captured {} messages after {} ticks to {}",
+        record.messages.len(),
+        ticks,
+        path
+    ))
+}
+
+/// 重新运行金标准文件中的配置，报告与记录输出的差异
+#[tauri::command]
+fn compare_to_golden(path: String) -> Result<GoldenDiff, String> {
+    golden::compare_to_golden(&path)
+}
+
+/// 开始将发送的消息流录制到 AVR 文本文件，每行带序号和 Unix ms 时间戳
+#[tauri::command]
+fn start_recording(state: State<SimulatorState>, path: String) -> Result<String, String> {
+    let mut recorder = state.recorder.lock().map_err(|e| e.to_string())?;
+    if recorder.is_some() {
+        return Err("Recording already in progress".to_string());
+    }
+    *recorder = Some(Recorder::create(&path)?);
+    Ok(format!("Recording to {}", path))
+}
+
+/// 停止录制，返回录制的消息数
+#[tauri::command]
+fn stop_recording(state: State<SimulatorState>) -> Result<u64, String> {
+    let recorder = state.recorder.lock().map_err(|e| e.to_string())?.take();
+    recorder
+        .ok_or_else(|| "No recording in progress".to_string())?
+        .finish()
+}
+
+/// 检查录制文件的完整性（格式、CRC、序号连续性）
 #[tauri::command]
-fn get_simulation_status(state: State<SimulatorState>) -> Result<bool, String> {
+fn validate_recording(path: String) -> Result<RecordingReport, String> {
+    recording::validate_recording(&path)
+}
+
+/// 将录制文件作为背景流量叠加到实时模拟上回放，返回可回放的消息数
+///
+/// 回放与实时飞机 ICAO 冲突时发送 replay-collision 事件。`smoothing` 缺省时
+/// 使用模拟配置中的 `track_smoothing`。
+#[tauri::command]
+fn start_replay_overlay(
+    state: State<SimulatorState>,
+    path: String,
+    smoothing: Option<TrackSmoothing>,
+) -> Result<usize, String> {
+    let mut replay = Replay::load(&path)?;
+    replay.set_smoothing(configured_smoothing(&state, smoothing)?)?;
+    let frames = replay.len();
+    *state.replay.lock().map_err(|e| e.to_string())? = Some(replay);
+    println!("[Rust] Replay overlay started: {} ({} messages)", path, frames);
+    Ok(frames)
+}
+
+/// 本次回放使用的外部航迹平滑方式：未指定时沿用模拟配置
+fn configured_smoothing(
+    state: &SimulatorState,
+    smoothing: Option<TrackSmoothing>,
+) -> Result<TrackSmoothing, String> {
+    match smoothing {
+        Some(smoothing) => Ok(smoothing),
+        None => Ok(*state.track_smoothing.lock().map_err(|e| e.to_string())?),
+    }
+}
+
+/// 文件回放等待下一批次期间检查停止标志的间隔
+const REPLAY_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// 文件回放允许的倍速范围
+const REPLAY_SPEED_RANGE: std::ops::RangeInclusive<f64> = 0.01..=1000.0;
+
+/// 脱离实时模拟，按录制的时间间隔将录制文件经 adsb-batch 事件重新发送
+///
+/// `speed` 为回放倍速 (2.0 为两倍速)，`smoothing` 缺省时使用模拟配置中的
+/// `track_smoothing`。跳过的损坏行数随返回值给出，
+/// 完整回放结束时再以 replay-finished 事件报告一次；使用 `stop_replay_file` 提前停止。
+/// 回放期间不能启动模拟，模拟运行时也不能开始回放。
+#[tauri::command]
+fn replay_file(
+    app: AppHandle,
+    state: State<SimulatorState>,
+    path: String,
+    speed: f64,
+    smoothing: Option<TrackSmoothing>,
+) -> Result<ReplaySummary, String> {
+    if !REPLAY_SPEED_RANGE.contains(&speed) {
+        return Err(format!(
+            "Replay speed {} must be within {}-{}",
+            speed,
+            REPLAY_SPEED_RANGE.start(),
+            REPLAY_SPEED_RANGE.end()
+        ));
+    }
+    // 持有运行标志直到回放线程登记完成，避免与 start_simulation 同时启动
     let is_running = state.is_running.lock().map_err(|e| e.to_string())?;
-    Ok(*is_running)
+    if *is_running {
+        return Err("Stop the simulation before replaying a file".to_string());
+    }
+    let mut slot = state.file_replay.lock().map_err(|e| e.to_string())?;
+    if slot.as_ref().is_some_and(FileReplay::is_active) {
+        return Err("A file replay is already running".to_string());
+    }
+    let mut replay = Replay::load(&path)?;
+    replay.set_smoothing(configured_smoothing(&state, smoothing)?)?;
+    let summary = replay.summary();
+    let batches = replay.into_batches();
+    println!(
+        "[Rust] Replaying {} at {}x: {} messages, {} lines skipped",
+        path, speed, summary.messages, summary.skipped
+    );
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let stopped = Arc::clone(&stop);
+    let thread = thread::spawn(move || {
+        let finished = run_file_replay(batches, speed, &stopped, |batch| {
+            if let Err(e) = app.emit("adsb-batch", &batch) {
+                eprintln!("[Rust] Failed to emit replay batch: {}", e);
+            }
+        });
+        if !finished {
+            println!("[Rust] Replay stopped");
+            return;
+        }
+        println!("[Rust] Replay finished");
+        if let Err(e) = app.emit("replay-finished", summary) {
+            eprintln!("[Rust] Failed to emit replay summary: {}", e);
+        }
+    });
+    *slot = Some(FileReplay { thread, stop });
+    drop(is_running);
+    Ok(summary)
+}
+
+/// 停止文件回放并等待回放线程退出；没有进行中的回放时直接返回
+#[tauri::command]
+fn stop_replay_file(state: State<SimulatorState>) -> Result<(), String> {
+    let replay = state.file_replay.lock().map_err(|e| e.to_string())?.take();
+    if let Some(replay) = replay {
+        replay.stop.store(true, Ordering::Relaxed);
+        if replay.thread.join().is_err() {
+            eprintln!("[Rust] Replay thread panicked");
+        }
+    }
+    Ok(())
+}
+
+/// 文件回放线程是否仍在运行
+fn file_replay_active(slot: &Mutex<Option<FileReplay>>) -> Result<bool, String> {
+    let slot = slot.lock().map_err(|e| e.to_string())?;
+    Ok(slot.as_ref().is_some_and(FileReplay::is_active))
+}
+
+/// 按录制时间偏移逐批调用 `emit`；`stop` 置位后立即返回 false，全部发送完返回 true
+///
+/// 发送时刻超出 `Instant` 可表示的范围时视为永远不到，只等待停止标志。
+fn run_file_replay(
+    batches: Vec<ReplayBatch>,
+    speed: f64,
+    stop: &AtomicBool,
+    mut emit: impl FnMut(AdsbBatchEvent),
+) -> bool {
+    let started = Instant::now();
+    for batch in batches {
+        let delay = Duration::try_from_secs_f64(batch.offset_ms as f64 / 1000.0 / speed)
+            .unwrap_or(Duration::MAX);
+        let due = started.checked_add(delay);
+        loop {
+            if stop.load(Ordering::Relaxed) {
+                return false;
+            }
+            let now = Instant::now();
+            if due.is_some_and(|due| now >= due) {
+                break;
+            }
+            let wait = due.map_or(REPLAY_POLL_INTERVAL, |due| due - now);
+            thread::sleep(wait.min(REPLAY_POLL_INTERVAL));
+        }
+        emit(recording::replay_batch(batch));
+    }
+    true
+}
+
+/// 停止叠加回放
+#[tauri::command]
+fn stop_replay_overlay(state: State<SimulatorState>) -> Result<(), String> {
+    *state.replay.lock().map_err(|e| e.to_string())? = None;
+    Ok(())
+}
+
+/// 接收统计
+#[derive(Debug, Clone, Serialize)]
+pub struct ReceiverReport {
+    pub stations: Vec<ReceiverStats>, // 各地面接收站的接收情况
+    pub clients: Vec<ClientStats>,    // 各网络输出客户端的发送与丢弃帧数
+}
+
+/// 获取接收统计：各接收站的接收情况与各网络输出客户端的发送/丢弃计数
+#[tauri::command]
+fn get_receiver_stats(state: State<SimulatorState>) -> Result<ReceiverReport, String> {
+    let receivers = state.receivers.lock().map_err(|e| e.to_string())?;
+    let outputs = state.outputs.lock().map_err(|e| e.to_string())?;
+    Ok(receiver_report(&receivers, &outputs))
+}
+
+/// 汇总接收站统计与网络输出客户端统计
+fn receiver_report(receivers: &ReceiverNetwork, outputs: &[OutputServer]) -> ReceiverReport {
+    ReceiverReport {
+        stations: receivers.stats(),
+        clients: outputs.iter().flat_map(|s| s.client_stats()).collect(),
+    }
+}
+
+/// 用内置的真实报文测试向量校验解码器
+#[tauri::command]
+fn run_test_vectors() -> Vec<VectorResult> {
+    vectors::run_test_vectors()
+}
+
+/// 计算指定高度下各接收站均未覆盖的网格点 (纬度, 经度)，`grid_resolution` 为网格间距 (°)
+#[tauri::command]
+fn coverage_gaps(
+    state: State<SimulatorState>,
+    altitude_ft: f64,
+    grid_resolution: f64,
+) -> Result<Vec<(f64, f64)>, String> {
+    let receivers = state.receivers.lock().map_err(|e| e.to_string())?;
+    receivers.coverage_gaps(altitude_ft, grid_resolution)
+}
+
+/// 以 protobuf 编码返回当前时刻的批次快照（不推进模拟）
+#[cfg(feature = "protobuf")]
+#[tauri::command]
+fn get_batch_protobuf(state: State<SimulatorState>) -> Result<Vec<u8>, String> {
+    let simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    Ok(proto::encode_batch(&snapshot_batch(&simulator)))
+}
+
+/// 未启用 `protobuf` feature 时的占位命令
+#[cfg(not(feature = "protobuf"))]
+#[tauri::command]
+fn get_batch_protobuf(_state: State<SimulatorState>) -> Result<Vec<u8>, String> {
+    Err("Built without the `protobuf` feature".to_string())
+}
+
+/// 设置改用 978 MHz UAT 发送的飞机
+#[cfg(feature = "uat")]
+#[tauri::command]
+fn set_uat_aircraft(state: State<SimulatorState>, icaos: Vec<String>) -> Result<(), String> {
+    let mut channel = state.uat.lock().map_err(|e| e.to_string())?;
+    channel.set_aircraft(&icaos);
+    Ok(())
+}
+
+/// 未启用 `uat` feature 时的占位命令
+#[cfg(not(feature = "uat"))]
+#[tauri::command]
+fn set_uat_aircraft(_state: State<SimulatorState>, _icaos: Vec<String>) -> Result<(), String> {
+    Err("Built without the `uat` feature".to_string())
+}
+
+/// 检查当前机队状态的物理合理性
+#[tauri::command]
+fn validate_physics(state: State<SimulatorState>) -> Result<Vec<PhysicsWarning>, String> {
+    let simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    Ok(physics::validate_physics(
+        simulator.get_aircrafts(),
+        simulator.get_history(),
+    ))
+}
+
+/// 获取本次会话已发送消息按 (DF, 类型码) 的分布
+#[tauri::command]
+fn get_message_type_histogram(
+    state: State<SimulatorState>,
+) -> Result<Vec<MessageTypeCount>, String> {
+    let histogram = state.histogram.lock().map_err(|e| e.to_string())?;
+    Ok(histogram.entries())
+}
+
+/// 获取本次启动以来的消息吞吐统计：消息总数、各类型消息数、飞机数与 tick 数
+#[tauri::command]
+fn get_statistics(state: State<SimulatorState>) -> Result<SimulationStatistics, String> {
+    Ok(state.counters.snapshot())
+}
+
+/// 按 ADS-B Out 强制要求检查当前机队的符合情况
+#[tauri::command]
+fn compliance_report(state: State<SimulatorState>) -> Result<ComplianceReport, String> {
+    let simulator = state.simulator.lock().map_err(|e| e.to_string())?;
+    Ok(compliance::compliance_report(simulator.get_aircrafts()))
+}
+
+/// 获取发送延迟统计
+#[tauri::command]
+fn get_latency_stats(state: State<SimulatorState>) -> Result<LatencySnapshot, String> {
+    Ok(state.latency.snapshot())
+}
+
+/// 模拟运行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SimulationStatus {
+    Stopped,
+    Running,
+    Paused, // 后台任务仍在运行，但不推进模拟
+}
+
+impl SimulationStatus {
+    fn from_flags(running: bool, paused: bool) -> Self {
+        match (running, paused) {
+            (false, _) => SimulationStatus::Stopped,
+            (true, false) => SimulationStatus::Running,
+            (true, true) => SimulationStatus::Paused,
+        }
+    }
+}
+
+/// 检查模拟状态
+#[tauri::command]
+fn get_simulation_status(state: State<SimulatorState>) -> Result<SimulationStatus, String> {
+    let is_running = *state.is_running.lock().map_err(|e| e.to_string())?;
+    let is_paused = *state.is_paused.lock().map_err(|e| e.to_string())?;
+    Ok(SimulationStatus::from_flags(is_running, is_paused))
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -146,13 +1499,680 @@ pub fn run() {
         .manage(SimulatorState {
             simulator: Arc::new(Mutex::new(AdsbSimulator::new(22.5431, 114.0579))),
             is_running: Arc::new(Mutex::new(false)),
+            is_paused: Arc::new(Mutex::new(false)),
+            latency: Arc::new(LatencyStats::default()),
+            histogram: Arc::new(Mutex::new(MessageHistogram::default())),
+            counters: Arc::new(SimulationCounters::default()),
+            receivers: Arc::new(Mutex::new(ReceiverNetwork::default())),
+            outputs: Arc::new(Mutex::new(Vec::new())),
+            recent: Arc::new(Mutex::new(RecentEvents::default())),
+            replay: Arc::new(Mutex::new(None)),
+            recorder: Arc::new(Mutex::new(None)),
+            #[cfg(feature = "uat")]
+            uat: Arc::new(Mutex::new(uat::UatChannel::default())),
+            worker: Mutex::new(None),
+            scenario: Mutex::new(None),
+            file_replay: Mutex::new(None),
+            track_smoothing: Mutex::new(TrackSmoothing::None),
         })
         .invoke_handler(tauri::generate_handler![
             start_simulation,
             stop_simulation,
+            pause_simulation,
+            resume_simulation,
             get_aircrafts,
+            get_aircraft,
+            add_aircraft,
+            remove_aircraft,
+            load_scenario,
+            save_scenario,
+            clear_scenario,
+            predict_positions,
+            get_recent_events,
+            start_output_server,
+            start_beast_server,
+            start_sbs_server,
+            start_ws_server,
+            stop_output_server,
             get_simulation_status,
+            get_geojson,
+            dump_fleet_table,
+            export_geojson,
+            export_czml,
+            export_tar1090_traces,
+            export_message_explainer,
+            get_message_representation,
+            assemble_raw,
+            interrogate,
+            emit_all_call_burst,
+            get_latency_stats,
+            get_message_type_histogram,
+            get_statistics,
+            validate_physics,
+            compliance_report,
+            get_batch_protobuf,
+            set_uat_aircraft,
+            get_receiver_stats,
+            coverage_gaps,
+            run_test_vectors,
+            min_receivable_altitude,
+            analyze_cpr_robustness,
+            validate_recording,
+            start_recording,
+            stop_recording,
+            start_replay_overlay,
+            stop_replay_overlay,
+            replay_file,
+            stop_replay_file,
+            capture_golden,
+            compare_to_golden,
+            add_spoofer,
+            clear_spoofers,
+            get_schedule,
+            set_aircraft_source,
+            set_wind_layers,
+            set_wind,
+            create_flight,
+            assign_route,
+            set_emergency,
+            create_holding_stack,
+            land_aircraft,
+            create_parallel_departures,
+            set_capability_profile,
+            get_seed,
+            set_seed,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_timestamp_tracks_wall_clock() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 2);
+        sim.generate_mock_aircrafts(2).unwrap();
+
+        let first = build_batch(&mut sim, 1, 0, 0);
+        thread::sleep(Duration::from_millis(100));
+        let second = build_batch(&mut sim, 1, 1, 0);
+        // 时间戳差值为实际经过的时间（不小于睡眠间隔，另含步进与调度耗时）
+        let elapsed = second.timestamp - first.timestamp;
+        assert!((100..600).contains(&elapsed), "{} ms", elapsed);
+        assert_eq!((first.tick, second.tick), (0, 1));
+    }
+
+    #[test]
+    fn test_display_rate_throttles_emits() {
+        // 30 Hz 模拟、5 Hz 显示：按发送节拍驱动 1 s
+        let (emit_interval, steps) = emit_plan(1000 / 30, Some(5.0));
+        assert_eq!((emit_interval, steps), (198, 6));
+        let mut fleet = AdsbSimulator::with_seed(22.5431, 114.0579, 3);
+        fleet.generate_mock_aircrafts(2).unwrap();
+        let simulator = Arc::new(Mutex::new(fleet));
+        let emitted = Arc::new(Mutex::new(0u64));
+        let (sim, count) = (Arc::clone(&simulator), Arc::clone(&emitted));
+        let (shutdown, mut stop_signal) = watch::channel(false);
+        let task = async_runtime::spawn(async move {
+            let mut ticker = emit_ticker(emit_interval);
+            while next_tick(&mut ticker, &mut stop_signal).await {
+                let mut count = count.lock().unwrap();
+                build_batch(&mut sim.lock().unwrap(), steps, *count, 0);
+                *count += 1;
+            }
+        });
+        let worker = Mutex::new(Some(Worker { task, shutdown }));
+        thread::sleep(Duration::from_millis(1000));
+        join_worker(&worker, WORKER_JOIN_TIMEOUT).unwrap();
+
+        // 首个批次立即发送，此后约每 200 ms 一次
+        let emitted = *emitted.lock().unwrap();
+        assert!((5..=7).contains(&emitted), "{} batches in 1 s", emitted);
+        // 模拟本身仍按 30 Hz 步进：每个批次包含 6 步
+        let simulator = simulator.lock().unwrap();
+        let track = simulator.get_history().values().next().unwrap();
+        assert_eq!(track.len() as u64, emitted * steps as u64 + 1);
+
+        // 显示刷新率高于模拟速率时每步发送
+        assert_eq!(emit_plan(1000, Some(5.0)), (1000, 1));
+    }
+
+    #[test]
+    fn test_small_interval_respects_emit_floor() {
+        assert_eq!(emit_plan(1000, None), (1000, 1));
+        assert_eq!(emit_plan(MIN_EMIT_INTERVAL_MS, None), (MIN_EMIT_INTERVAL_MS, 1));
+
+        for requested in [0, 1, 3, 7, 49] {
+            let (emit_interval, steps) = emit_plan(requested, None);
+            assert!(emit_interval >= MIN_EMIT_INTERVAL_MS);
+            // 每次发送包含的步数保持请求的模拟速率
+            assert_eq!(emit_interval, requested.max(1) * steps as u64);
+        }
+        assert_eq!(emit_plan(1, None), (50, 50));
+
+        // 一次批次推进多步
+        let mut single = AdsbSimulator::with_seed(22.5431, 114.0579, 2);
+        let mut batched = AdsbSimulator::with_seed(22.5431, 114.0579, 2);
+        single.generate_mock_aircrafts(3).unwrap();
+        batched.generate_mock_aircrafts(3).unwrap();
+        for tick in 0..4 {
+            build_batch(&mut single, 1, tick, 0);
+        }
+        let event = build_batch(&mut batched, 4, 0, 0);
+        assert_eq!(&event.aircrafts, single.get_aircrafts());
+    }
+
+    fn sample_aircraft(id: &str, callsign: &str) -> Aircraft {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 1);
+        sim.generate_mock_aircrafts(1).unwrap();
+        Aircraft {
+            id: id.to_string(),
+            callsign: callsign.to_string(),
+            ..sim.get_aircrafts()[0].clone()
+        }
+    }
+
+    #[test]
+    fn test_initial_aircraft_in_fleet() {
+        let config = SimulationConfig {
+            aircraft_count: 5,
+            seed: Some(3),
+            initial_aircraft: vec![
+                sample_aircraft("ABC123", "TEST1"),
+                sample_aircraft("ABC124", "TEST2"),
+            ],
+            ..SimulationConfig::default()
+        };
+
+        let sim = build_simulator(&config).unwrap();
+        let fleet = sim.get_aircrafts();
+        assert_eq!(fleet.len(), 5);
+        assert!(fleet.iter().any(|a| a.id == "ABC123" && a.callsign == "TEST1"));
+        assert!(fleet.iter().any(|a| a.id == "ABC124" && a.callsign == "TEST2"));
+
+        // 仅使用预设飞机
+        let only = SimulationConfig {
+            aircraft_count: 0,
+            ..config.clone()
+        };
+        assert_eq!(build_simulator(&only).unwrap().get_aircrafts().len(), 2);
+
+        // 非法字段与重复 ICAO 均被拒绝
+        let mut invalid = config.clone();
+        invalid.initial_aircraft[0].lat = 123.0;
+        assert!(build_simulator(&invalid).is_err());
+        let mut duplicate = config;
+        duplicate.initial_aircraft[1].id = "abc123".to_string();
+        assert!(build_simulator(&duplicate).is_err());
+    }
+
+    #[test]
+    fn test_config_altitude_and_speed_ranges() {
+        let config = SimulationConfig {
+            aircraft_count: 40,
+            seed: Some(272),
+            min_altitude: 500.0,
+            max_altitude: 3000.0,
+            min_speed: 90.0,
+            max_speed: 140.0,
+            ..SimulationConfig::default()
+        };
+        let mut sim = build_simulator(&config).unwrap();
+        let within = |sim: &AdsbSimulator| {
+            sim.get_aircrafts()
+                .iter()
+                .all(|a| (500.0..=3000.0).contains(&a.altitude))
+        };
+        assert!(within(&sim));
+        assert!(sim
+            .get_aircrafts()
+            .iter()
+            .all(|a| (90.0..=140.0).contains(&a.speed)));
+        // 爬升中的飞机也不会越过最高高度
+        for _ in 0..300 {
+            sim.update_positions();
+        }
+        assert!(within(&sim));
+
+        let inverted = SimulationConfig {
+            min_altitude: 3000.0,
+            max_altitude: 3000.0,
+            ..config.clone()
+        };
+        assert!(build_simulator(&inverted).is_err());
+        let inverted = SimulationConfig {
+            min_speed: 200.0,
+            ..config
+        };
+        assert!(build_simulator(&inverted).is_err());
+    }
+
+    #[test]
+    fn test_added_aircraft_in_next_batch() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 61);
+        sim.generate_mock_aircrafts(3).unwrap();
+        build_batch(&mut sim, 1, 0, 0);
+
+        assert_eq!(sim.add_aircraft(sample_aircraft("ABC123", "TEST1")), Ok(4));
+        assert!(sim.add_aircraft(sample_aircraft("abc123", "TEST2")).is_err());
+        let event = build_batch(&mut sim, 1, 1, 0);
+        assert!(event.aircrafts.iter().any(|a| a.id == "ABC123"));
+        assert!(event.messages.iter().any(|m| m.aircraft_id == "ABC123"));
+    }
+
+    #[test]
+    fn test_startup_ramp_populates_gradually() {
+        let config = SimulationConfig {
+            aircraft_count: 20,
+            seed: Some(63),
+            startup_ramp_s: 60,
+            ..SimulationConfig::default()
+        };
+        let mut sim = build_simulator(&config).unwrap();
+        assert_eq!(sim.get_aircrafts().len(), 1);
+
+        for _ in 0..30 {
+            sim.update_positions();
+        }
+        let midpoint = sim.get_aircrafts().len();
+        assert!((9..=12).contains(&midpoint), "{} aircraft at midpoint", midpoint);
+
+        for _ in 30..60 {
+            sim.update_positions();
+        }
+        assert_eq!(sim.get_aircrafts().len(), 20);
+    }
+
+    #[test]
+    fn test_poisoned_lock_stops_thread() {
+        let simulator = Arc::new(Mutex::new(AdsbSimulator::with_seed(22.5431, 114.0579, 7)));
+        let is_running = Arc::new(Mutex::new(true));
+
+        // 其他线程持有模拟器锁时 panic，锁进入中毒状态
+        let poisoner = Arc::clone(&simulator);
+        let _ = thread::spawn(move || {
+            let _guard = poisoner.lock().unwrap();
+            panic!("poison simulator");
+        })
+        .join();
+        assert!(simulator.is_poisoned());
+
+        let (sim, running) = (Arc::clone(&simulator), Arc::clone(&is_running));
+        let worker = thread::spawn(move || {
+            let mut ticks = 0;
+            while should_keep_running(&running) {
+                let Some(mut sim) = lock_simulator(&sim, &running) else {
+                    break;
+                };
+                build_batch(&mut sim, 1, ticks, 0);
+                ticks += 1;
+            }
+            ticks
+        });
+        assert_eq!(worker.join().unwrap(), 0);
+        assert!(!*is_running.lock().unwrap());
+        assert!(!simulator.is_poisoned());
+
+        // 运行标志本身中毒时同样停止
+        *is_running.lock().unwrap() = true;
+        let poisoner = Arc::clone(&is_running);
+        let _ = thread::spawn(move || {
+            let _guard = poisoner.lock().unwrap();
+            panic!("poison running flag");
+        })
+        .join();
+        assert!(!should_keep_running(&is_running));
+        assert!(!*is_running.lock().unwrap());
+    }
+
+    #[test]
+    fn test_rapid_restart_single_worker() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let worker = Mutex::new(None);
+        let running = Arc::new(Mutex::new(false));
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let start = || -> Result<(), String> {
+            join_worker(&worker, Duration::ZERO)?;
+            *running.lock().unwrap() = true;
+            let (running, active, peak) =
+                (Arc::clone(&running), Arc::clone(&active), Arc::clone(&peak));
+            let (shutdown, mut stop_signal) = watch::channel(false);
+            let task = async_runtime::spawn(async move {
+                peak.fetch_max(active.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                let mut ticker = tokio::time::interval(Duration::from_millis(1));
+                while next_tick(&mut ticker, &mut stop_signal).await && should_keep_running(&running)
+                {
+                }
+                active.fetch_sub(1, Ordering::SeqCst);
+            });
+            *worker.lock().unwrap() = Some(Worker { task, shutdown });
+            Ok(())
+        };
+        let stop = || {
+            *running.lock().unwrap() = false;
+            join_worker(&worker, WORKER_JOIN_TIMEOUT)
+        };
+
+        for _ in 0..10 {
+            start().unwrap();
+            stop().unwrap();
+            assert_eq!(active.load(Ordering::SeqCst), 0);
+        }
+        assert_eq!(peak.load(Ordering::SeqCst), 1);
+
+        // 未回收的任务仍在运行时拒绝再次启动
+        start().unwrap();
+        assert!(start().is_err());
+        stop().unwrap();
+        assert_eq!(peak.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_stop_cancels_within_tick() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        // 10 s 的发送间隔：停止信号应立即唤醒任务，而不是等到下一个 tick
+        let worker = Mutex::new(None);
+        let ticks = Arc::new(AtomicU64::new(0));
+        let counted = Arc::clone(&ticks);
+        let (shutdown, mut stop_signal) = watch::channel(false);
+        let task = async_runtime::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(10));
+            while next_tick(&mut ticker, &mut stop_signal).await {
+                counted.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        *worker.lock().unwrap() = Some(Worker { task, shutdown });
+
+        // 首个 tick 立即触发
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while ticks.load(Ordering::SeqCst) == 0 {
+            assert!(Instant::now() < deadline, "first tick did not fire");
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        let started = Instant::now();
+        join_worker(&worker, WORKER_JOIN_TIMEOUT).unwrap();
+        assert!(started.elapsed() < Duration::from_millis(500), "{:?}", started.elapsed());
+        assert_eq!(ticks.load(Ordering::SeqCst), 1);
+        assert!(worker.lock().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_stop_outputs_closes_every_port() {
+        use std::net::TcpStream;
+
+        let outputs = Mutex::new(Vec::new());
+        for format in [OutputFormat::Avr, OutputFormat::Beast, OutputFormat::Sbs] {
+            outputs
+                .lock()
+                .unwrap()
+                .push(OutputServer::start(format, 0, 8, false).unwrap());
+        }
+        let ports: Vec<u16> = outputs.lock().unwrap().iter().map(|s| s.port()).collect();
+        for &port in &ports {
+            TcpStream::connect(("127.0.0.1", port)).unwrap();
+        }
+
+        // 停止后监听端口释放，新连接被拒绝
+        stop_outputs(&outputs).unwrap();
+        assert!(outputs.lock().unwrap().is_empty());
+        let deadline = Instant::now() + Duration::from_secs(2);
+        for port in ports {
+            while TcpStream::connect(("127.0.0.1", port)).is_ok() {
+                assert!(Instant::now() < deadline, "port {} still open", port);
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+    }
+
+    #[test]
+    fn test_paused_loop_sends_keepalive() {
+        use std::io::{BufRead, BufReader};
+        use std::net::TcpStream;
+
+        let outputs = Arc::new(Mutex::new(Vec::new()));
+        let mut readers = Vec::new();
+        for format in [OutputFormat::Avr, OutputFormat::Sbs] {
+            let server = OutputServer::start(format, 0, 16, false).unwrap();
+            let client = TcpStream::connect(("127.0.0.1", server.port())).unwrap();
+            // 客户端在 1 s 无数据时视为断线
+            client
+                .set_read_timeout(Some(Duration::from_secs(1)))
+                .unwrap();
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while server.client_stats().is_empty() {
+                assert!(Instant::now() < deadline, "client did not connect");
+                thread::sleep(Duration::from_millis(10));
+            }
+            readers.push(BufReader::new(client));
+            outputs.lock().unwrap().push(server);
+        }
+
+        // 以 20 ms 间隔驱动暂停中的任务循环，每 300 ms 发送一次心跳
+        let is_paused = Arc::new(Mutex::new(true));
+        let (paused, servers) = (Arc::clone(&is_paused), Arc::clone(&outputs));
+        let (shutdown, mut stop_signal) = watch::channel(false);
+        let task = async_runtime::spawn(async move {
+            let mut last_keepalive = Instant::now();
+            let mut ticker = tokio::time::interval(Duration::from_millis(20));
+            let every = Some(Duration::from_millis(300));
+            while next_tick(&mut ticker, &mut stop_signal).await {
+                paused_tick(&paused, &servers, every, &mut last_keepalive);
+            }
+        });
+        let worker = Mutex::new(Some(Worker { task, shutdown }));
+
+        // 暂停期间客户端持续收到心跳而不超时：AVR 为空 Mode A/C 帧，SBS 为空行
+        for _ in 0..3 {
+            for (reader, expected) in readers.iter_mut().zip(["*0000;\n", "\n"]) {
+                let mut line = String::new();
+                reader.read_line(&mut line).expect("client timed out");
+                assert_eq!(line, expected);
+            }
+        }
+        join_worker(&worker, WORKER_JOIN_TIMEOUT).unwrap();
+        assert!(outputs
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|server| server.client_stats().len() == 1));
+
+        // 恢复后不再处于暂停分支
+        *is_paused.lock().unwrap() = false;
+        let mut last_keepalive = Instant::now();
+        let paused = paused_tick(&is_paused, &outputs, None, &mut last_keepalive);
+        assert!(!paused);
+    }
+
+    #[test]
+    fn test_receiver_report_includes_output_clients() {
+        use std::net::TcpStream;
+
+        let server = OutputServer::start(OutputFormat::Avr, 0, 8, false).unwrap();
+        let _client = TcpStream::connect(("127.0.0.1", server.port())).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while server.client_stats().is_empty() {
+            assert!(Instant::now() < deadline, "client did not connect");
+            thread::sleep(Duration::from_millis(10));
+        }
+        let receivers = ReceiverNetwork::new(vec![Receiver {
+            name: "VHHH".to_string(),
+            lat: 22.31,
+            lng: 113.92,
+            altitude: 0.0,
+            range_nm: 200.0,
+        }]);
+
+        let report = serde_json::to_value(receiver_report(&receivers, &[server])).unwrap();
+        assert_eq!(report["stations"][0]["name"], "VHHH");
+        assert_eq!(report["clients"].as_array().unwrap().len(), 1);
+        assert_eq!(report["clients"][0]["frames_dropped"], 0);
+    }
+
+    #[test]
+    fn test_file_replay_stops_on_request() {
+        let event = |icao: &str| AdsbEvent {
+            hex_message: "8D40621D58C382D690C8AC2863A7".to_string(),
+            aircraft_id: icao.to_string(),
+            message_type: adsb::MessageType::Position,
+            rssi: None,
+            spoofed: false,
+        };
+        let batch = |offset_ms: u64, icao: &str| ReplayBatch {
+            offset_ms,
+            messages: vec![event(icao)],
+            tracks: HashMap::new(),
+        };
+        let batches = vec![
+            batch(0, "40621D"),
+            batch(100, "485020"),
+            batch(60_000, "4840D6"),
+        ];
+        let stop = Arc::new(AtomicBool::new(false));
+        let emitted = Arc::new(Mutex::new(Vec::new()));
+        let (stopped, sink) = (Arc::clone(&stop), Arc::clone(&emitted));
+        let replay = thread::spawn(move || {
+            run_file_replay(batches, 1.0, &stopped, |batch| {
+                let icao = batch.messages[0].aircraft_id.clone();
+                sink.lock().unwrap().push(icao);
+            })
+        });
+
+        // 第三批要 60 s 后才发送：停止标志置位后线程应很快退出
+        thread::sleep(Duration::from_millis(300));
+        let requested = Instant::now();
+        stop.store(true, Ordering::Relaxed);
+        assert!(!replay.join().unwrap());
+        assert!(requested.elapsed() < Duration::from_millis(500));
+        assert_eq!(*emitted.lock().unwrap(), vec!["40621D", "485020"]);
+
+        // 未停止时全部发送完毕
+        let stop = AtomicBool::new(false);
+        let mut count = 0;
+        let batches = vec![batch(0, "40621D"), batch(10, "485020")];
+        assert!(run_file_replay(batches, 2.0, &stop, |_| count += 1));
+        assert_eq!(count, 2);
+
+        // 极小倍速下发送时刻溢出，不 panic，仍可停止
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+        let batches = vec![batch(0, "40621D"), batch(1000, "485020")];
+        let replay = thread::spawn(move || run_file_replay(batches, 1e-300, &stopped, |_| {}));
+        thread::sleep(Duration::from_millis(100));
+        stop.store(true, Ordering::Relaxed);
+        assert!(!replay.join().unwrap());
+        assert!(!REPLAY_SPEED_RANGE.contains(&1e-300));
+    }
+
+    #[test]
+    fn test_simulation_status_three_states() {
+        assert_eq!(SimulationStatus::from_flags(false, false), SimulationStatus::Stopped);
+        assert_eq!(SimulationStatus::from_flags(false, true), SimulationStatus::Stopped);
+        assert_eq!(SimulationStatus::from_flags(true, false), SimulationStatus::Running);
+        assert_eq!(SimulationStatus::from_flags(true, true), SimulationStatus::Paused);
+        assert_eq!(
+            serde_json::to_string(&SimulationStatus::Paused).unwrap(),
+            "\"paused\""
+        );
+    }
+
+    #[test]
+    fn test_predicted_matches_next_batch() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 64);
+        sim.generate_mock_aircrafts(6).unwrap();
+        let event = build_batch(&mut sim, 1, 0, 0);
+        assert_eq!(event.predicted.len(), 6);
+
+        // 速度恒定时，外推位置与下一批次的实际位置一致
+        let next = build_batch(&mut sim, 1, 1, 0);
+        for aircraft in &next.aircrafts {
+            let predicted = &event.predicted[&aircraft.id];
+            assert!((predicted.lat - aircraft.lat).abs() < 1e-12);
+            assert!((predicted.lng - aircraft.lng).abs() < 1e-12);
+            if aircraft.vertical_rate != 0.0 {
+                assert!((predicted.altitude - aircraft.altitude).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[cfg(feature = "protobuf")]
+    #[test]
+    fn test_snapshot_does_not_advance_pipeline() {
+        let mut plain = AdsbSimulator::with_seed(22.5431, 114.0579, 65);
+        let mut probed = AdsbSimulator::with_seed(22.5431, 114.0579, 65);
+        for sim in [&mut plain, &mut probed] {
+            sim.generate_mock_aircrafts(5).unwrap();
+            sim.set_impairment(ChannelImpairment {
+                dropout_rate: 0.3,
+                bit_error_rate: 0.001,
+            });
+            build_batch(sim, 1, 0, 0);
+        }
+
+        // 中途取快照不影响后续批次的消息与飞机状态
+        let snapshot = snapshot_batch(&probed);
+        assert!(snapshot.messages.is_empty());
+        assert_eq!(snapshot.aircrafts.len(), 5);
+        assert_eq!(snapshot.colors.len(), 5);
+        for tick in 1..4 {
+            let expected = build_batch(&mut plain, 1, tick, 0);
+            let actual = build_batch(&mut probed, 1, tick, 0);
+            assert_eq!(
+                serde_json::to_value(&expected.messages).unwrap(),
+                serde_json::to_value(&actual.messages).unwrap()
+            );
+            assert_eq!(
+                serde_json::to_value(&expected.aircrafts).unwrap(),
+                serde_json::to_value(&actual.aircrafts).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_latency_populated_after_ticks() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 1);
+        sim.generate_mock_aircrafts(4).unwrap();
+        let latency = LatencyStats::default();
+
+        for tick in 0..5 {
+            let event = build_batch(&mut sim, 1, tick, 0);
+            let produced = Instant::now();
+            let json = latency.time(produced, || serde_json::to_string(&event).unwrap());
+            assert!(!json.is_empty());
+        }
+
+        let snapshot = latency.snapshot();
+        assert_eq!(snapshot.samples, 5);
+        assert!(snapshot.mean_us >= 0.0);
+        assert_eq!(snapshot.buckets.iter().map(|b| b.count).sum::<u64>(), 5);
+    }
+
+    #[test]
+    fn test_batch_trails_recent_points() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 46);
+        sim.generate_mock_aircrafts(3).unwrap();
+        let mut event = build_batch(&mut sim, 1, 0, 4);
+        // 生成时记录 1 个点，加上 1 步共 2 个点
+        assert!(event.trails.values().all(|trail| trail.len() == 2));
+
+        for tick in 1..6 {
+            event = build_batch(&mut sim, 1, tick, 4);
+        }
+        assert_eq!(event.trails.len(), 3);
+        for aircraft in &event.aircrafts {
+            let trail = &event.trails[&aircraft.id];
+            assert_eq!(trail.len(), 4);
+            assert!(trail.windows(2).all(|pair| pair[0].time < pair[1].time));
+            let last = trail.last().unwrap();
+            assert_eq!((last.lat, last.lng), (aircraft.lat, aircraft.lng));
+        }
+
+        let json = serde_json::to_value(build_batch(&mut sim, 1, 6, 0)).unwrap();
+        assert!(json.get("trails").is_none());
+    }
+}
@@ -0,0 +1,858 @@
+//! TCP 网络输出：每个客户端拥有独立的有界发送缓冲区
+//!
+//! 发布消息只向各客户端缓冲区追加数据，由每个客户端各自的写线程发送，
+//! 慢速客户端缓冲区满时丢弃最旧的数据（WebSocket 客户端则直接断开），
+//! 不会阻塞模拟线程或其他客户端。
+
+use crate::adsb::{unix_time_ms, AdsbEvent, Aircraft};
+use crate::export::civil_from_unix;
+use crate::AdsbBatchEvent;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 每个客户端缓冲的最大帧数
+pub const DEFAULT_CLIENT_BUFFER_FRAMES: usize = 1024;
+/// 每个 WebSocket 客户端缓冲的最大批次数，超过时断开该客户端
+pub const DEFAULT_WS_BUFFER_BATCHES: usize = 32;
+/// 等待 WebSocket 握手请求的超时
+const WS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+/// 握手请求的最大长度 (字节)
+const WS_MAX_HANDSHAKE_BYTES: usize = 8192;
+/// RFC 6455 握手中拼接在 Sec-WebSocket-Key 之后的固定 GUID
+const WS_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+/// 监听线程轮询新连接的间隔
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// 写线程等待新数据的超时，超时后检查服务器是否已停止
+const WRITER_WAIT_TIMEOUT: Duration = Duration::from_millis(200);
+/// Beast 帧起始/转义字节
+const BEAST_ESCAPE: u8 = 0x1A;
+/// Beast 帧中的信号强度（模拟数据固定值）
+const BEAST_SIGNAL_LEVEL: u8 = 0xFF;
+
+/// 网络输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    /// AVR 原始格式 `*HEX;`，每条消息一行（dump1090 端口 30002）
+    Avr,
+    /// UAT 原始格式 `-HEX;`，每条消息一行（dump978 端口 30978），仅发送 UAT 通道的消息
+    #[cfg(feature = "uat")]
+    Uat,
+    /// Beast 二进制格式（dump1090/readsb 端口 30005）
+    Beast,
+    /// SBS-1 BaseStation CSV（端口 30003），按飞机状态而非原始消息输出
+    Sbs,
+    /// WebSocket (RFC 6455)，每个批次作为一条 JSON 文本消息发送，供浏览器仪表盘订阅
+    #[serde(rename = "websocket")]
+    WebSocket,
+}
+
+impl OutputFormat {
+    /// 将一条消息编码为发送帧
+    fn encode(&self, event: &AdsbEvent) -> Vec<u8> {
+        match self {
+            OutputFormat::Avr => format!("*{};\n", event.hex_message).into_bytes(),
+            #[cfg(feature = "uat")]
+            OutputFormat::Uat => format!("-{};\n", event.hex_message).into_bytes(),
+            OutputFormat::Beast => beast_frame(&event.hex_message),
+            // SBS 行由 publish_aircraft 生成，WebSocket 批次由 publish_batch 生成
+            OutputFormat::Sbs | OutputFormat::WebSocket => Vec::new(),
+        }
+    }
+
+    /// 无数据时发送的心跳帧，避免客户端因长时间静默而断开
+    fn keepalive(&self) -> &'static [u8] {
+        match self {
+            // 与 dump1090 相同，使用空 Mode A/C 帧
+            OutputFormat::Avr => b"*0000;\n",
+            // dump978 没有心跳帧
+            #[cfg(feature = "uat")]
+            OutputFormat::Uat => b"",
+            // 全零的 Mode A/C 帧：类型 '1'、6 字节时间戳、信号强度和 2 字节数据
+            OutputFormat::Beast => b"\x1a1\0\0\0\0\0\0\0\0\0",
+            // BaseStation 协议没有心跳消息；发送空行，VRS 等按行解析的客户端会直接跳过
+            OutputFormat::Sbs => b"\n",
+            // 空载荷的 Ping 帧，浏览器自动回复 Pong
+            OutputFormat::WebSocket => b"\x89\x00",
+        }
+    }
+}
+
+/// 编码一帧 Beast 消息：0x1A、类型（'2' 短消息 / '3' 长消息）、6 字节 12 MHz 时间戳、
+/// 1 字节信号强度和消息字节，其中出现的 0x1A 需重复一次作为转义
+fn beast_frame(hex: &str) -> Vec<u8> {
+    let bytes: Vec<u8> = (0..hex.len() / 2)
+        .filter_map(|i| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok())
+        .collect();
+    let kind = if bytes.len() == 7 { b'2' } else { b'3' };
+    let ticks = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| (d.as_nanos() * 12 / 1000) as u64);
+
+    let mut frame = vec![BEAST_ESCAPE, kind];
+    let timestamp = ticks.to_be_bytes();
+    for &byte in timestamp[2..]
+        .iter()
+        .chain([BEAST_SIGNAL_LEVEL].iter())
+        .chain(&bytes)
+    {
+        frame.push(byte);
+        if byte == BEAST_ESCAPE {
+            frame.push(BEAST_ESCAPE);
+        }
+    }
+    frame
+}
+
+/// 生成一架飞机的 SBS-1 行，每行 22 个字段：空中为 MSG,3 (位置) 和 MSG,4 (速度)，
+/// 地面为 MSG,2 (地面位置，含地速和航迹)
+fn sbs_rows(aircraft: &Aircraft, unix_ms: u64) -> String {
+    let (year, month, day, hour, minute, second) = civil_from_unix(unix_ms / 1000);
+    let date = format!("{:04}/{:02}/{:02}", year, month, day);
+    let time = format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        hour,
+        minute,
+        second,
+        unix_ms % 1000
+    );
+    // SBS 中标志位以 -1 表示真
+    let flag = |set: bool| if set { "-1" } else { "0" };
+    let prefix = |kind: u8| {
+        format!(
+            "MSG,{},1,1,{},1,{},{},{},{},{}",
+            kind,
+            aircraft.id,
+            date,
+            time,
+            date,
+            time,
+            aircraft.callsign.trim()
+        )
+    };
+    let flags = format!(
+        ",{:04o},0,{},{},{}",
+        aircraft.squawk,
+        flag(aircraft.emergency_state().is_some()),
+        flag(aircraft.spi),
+        flag(aircraft.on_ground)
+    );
+    if aircraft.on_ground {
+        return format!(
+            "{},{:.0},{:.0},{:.1},{:.5},{:.5},{}\n",
+            prefix(2),
+            aircraft.altitude,
+            aircraft.ground_speed,
+            aircraft.track,
+            aircraft.lat,
+            aircraft.lng,
+            flags
+        );
+    }
+    format!(
+        "{},{:.0},,,{:.5},{:.5},{}\n{},,{:.0},{:.1},,,{:.0}{}\n",
+        prefix(3),
+        aircraft.altitude,
+        aircraft.lat,
+        aircraft.lng,
+        flags,
+        prefix(4),
+        aircraft.ground_speed,
+        aircraft.track,
+        aircraft.vertical_rate,
+        flags
+    )
+}
+
+/// 单个客户端的发送统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientStats {
+    pub format: OutputFormat,
+    pub port: u16,
+    pub peer: String,
+    pub frames_sent: u64,
+    pub frames_dropped: u64, // 因缓冲区满被丢弃的帧数
+    pub queued: usize,
+}
+
+/// 客户端的有界发送队列
+struct ClientQueue {
+    peer: String,
+    stream: TcpStream, // 用于停止时关闭连接，唤醒阻塞中的写操作
+    capacity: usize,
+    disconnect_when_full: bool, // 缓冲区满时断开客户端而不是丢弃最旧的帧
+    frames: Mutex<VecDeque<Arc<[u8]>>>,
+    ready: Condvar,
+    sent: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl ClientQueue {
+    /// 追加一帧，缓冲区满时丢弃最旧的帧，或断开连接（由写线程随后移除客户端）
+    fn push(&self, frame: Arc<[u8]>) {
+        let mut frames = self.frames.lock().unwrap();
+        if frames.len() >= self.capacity {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            if self.disconnect_when_full {
+                frames.clear();
+                let _ = self.stream.shutdown(Shutdown::Both);
+                return;
+            }
+            frames.pop_front();
+        }
+        frames.push_back(frame);
+        self.ready.notify_one();
+    }
+
+    /// 等待并取出下一帧，超时返回 None
+    fn pop(&self, timeout: Duration) -> Option<Arc<[u8]>> {
+        let frames = self.frames.lock().unwrap();
+        let (mut frames, _) = self
+            .ready
+            .wait_timeout_while(frames, timeout, |frames| frames.is_empty())
+            .unwrap();
+        frames.pop_front()
+    }
+}
+
+/// 一个 TCP 输出端口
+pub struct OutputServer {
+    format: OutputFormat,
+    addr: SocketAddr,
+    running: Arc<AtomicBool>,
+    clients: Arc<Mutex<Vec<Arc<ClientQueue>>>>,
+}
+
+impl OutputServer {
+    /// 在 `port` 上启动监听（0 表示由系统分配），`capacity` 为每个客户端缓冲的帧数
+    ///
+    /// 默认只监听本机回环地址；`external` 为 true 时监听所有网卡，供局域网内的其他设备接入。
+    pub fn start(
+        format: OutputFormat,
+        port: u16,
+        capacity: usize,
+        external: bool,
+    ) -> Result<Self, String> {
+        if capacity == 0 {
+            return Err("Client buffer capacity must be positive".to_string());
+        }
+        let host = if external {
+            Ipv4Addr::UNSPECIFIED
+        } else {
+            Ipv4Addr::LOCALHOST
+        };
+        let listener = TcpListener::bind((host, port))
+            .map_err(|e| format!("Failed to bind port {}: {}", port, e))?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        let addr = listener.local_addr().map_err(|e| e.to_string())?;
+
+        let server = OutputServer {
+            format,
+            addr,
+            running: Arc::new(AtomicBool::new(true)),
+            clients: Arc::new(Mutex::new(Vec::new())),
+        };
+        let running = Arc::clone(&server.running);
+        let clients = Arc::clone(&server.clients);
+        thread::spawn(move || accept_loop(listener, format, running, clients, capacity));
+        println!("[Rust] {:?} output listening on {}", format, addr);
+        Ok(server)
+    }
+
+    pub fn format(&self) -> OutputFormat {
+        self.format
+    }
+
+    pub fn port(&self) -> u16 {
+        self.addr.port()
+    }
+
+    /// 将消息编码后追加到所有客户端的缓冲区，不等待发送完成
+    pub fn publish(&self, messages: &[AdsbEvent]) {
+        let clients = self.clients.lock().unwrap();
+        let per_message = !matches!(self.format, OutputFormat::Sbs | OutputFormat::WebSocket);
+        if clients.is_empty() || !per_message {
+            return;
+        }
+        for message in messages {
+            let frame: Arc<[u8]> = self.format.encode(message).into();
+            for client in clients.iter() {
+                client.push(Arc::clone(&frame));
+            }
+        }
+    }
+
+    /// 将飞机的当前状态编码为 SBS 行后追加到客户端缓冲区，仅 SBS 端口有效
+    ///
+    /// 只输出本批次中仍有消息 (经接收与信道损伤后) 的飞机，与原始消息端口看到的数据一致。
+    pub fn publish_aircraft(&self, aircrafts: &[Aircraft], messages: &[AdsbEvent]) {
+        if self.format != OutputFormat::Sbs {
+            return;
+        }
+        let clients = self.clients.lock().unwrap();
+        if clients.is_empty() {
+            return;
+        }
+        let now_ms = unix_time_ms();
+        let received = |aircraft: &&Aircraft| {
+            messages
+                .iter()
+                .any(|m| m.aircraft_id.eq_ignore_ascii_case(&aircraft.id))
+        };
+        for aircraft in aircrafts.iter().filter(received) {
+            let frame: Arc<[u8]> = sbs_rows(aircraft, now_ms).into_bytes().into();
+            for client in clients.iter() {
+                client.push(Arc::clone(&frame));
+            }
+        }
+    }
+
+    /// 将批次事件序列化为 JSON 文本帧追加到客户端缓冲区，仅 WebSocket 端口有效
+    pub fn publish_batch(&self, batch: &AdsbBatchEvent) {
+        if self.format != OutputFormat::WebSocket {
+            return;
+        }
+        let clients = self.clients.lock().unwrap();
+        if clients.is_empty() {
+            return;
+        }
+        let json = match serde_json::to_vec(batch) {
+            Ok(json) => json,
+            Err(e) => {
+                eprintln!("[Rust] Failed to serialize WebSocket batch: {}", e);
+                return;
+            }
+        };
+        let frame: Arc<[u8]> = websocket_text_frame(&json).into();
+        for client in clients.iter() {
+            client.push(Arc::clone(&frame));
+        }
+    }
+
+    /// 向所有客户端发送一次心跳（暂停期间保持连接）
+    pub fn send_keepalive(&self) {
+        let keepalive = self.format.keepalive();
+        if keepalive.is_empty() {
+            return;
+        }
+        let frame: Arc<[u8]> = keepalive.into();
+        for client in self.clients.lock().unwrap().iter() {
+            client.push(Arc::clone(&frame));
+        }
+    }
+
+    /// 各已连接客户端的发送统计
+    pub fn client_stats(&self) -> Vec<ClientStats> {
+        self.clients
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|client| ClientStats {
+                format: self.format,
+                port: self.port(),
+                peer: client.peer.clone(),
+                frames_sent: client.sent.load(Ordering::Relaxed),
+                frames_dropped: client.dropped.load(Ordering::Relaxed),
+                queued: client.frames.lock().unwrap().len(),
+            })
+            .collect()
+    }
+
+    /// 停止监听并断开所有客户端
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::Relaxed);
+        for client in self.clients.lock().unwrap().drain(..) {
+            let _ = client.stream.shutdown(Shutdown::Both);
+            client.ready.notify_all();
+        }
+    }
+
+    #[cfg(test)]
+    fn publish_frame(&self, frame: &[u8]) {
+        let frame: Arc<[u8]> = frame.into();
+        for client in self.clients.lock().unwrap().iter() {
+            client.push(Arc::clone(&frame));
+        }
+    }
+}
+
+impl Drop for OutputServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// 接受新连接，每个客户端启动独立的写线程
+fn accept_loop(
+    listener: TcpListener,
+    format: OutputFormat,
+    running: Arc<AtomicBool>,
+    clients: Arc<Mutex<Vec<Arc<ClientQueue>>>>,
+    capacity: usize,
+) {
+    while running.load(Ordering::Relaxed) {
+        let (stream, peer) = match listener.accept() {
+            Ok(accepted) => accepted,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                thread::sleep(ACCEPT_POLL_INTERVAL);
+                continue;
+            }
+            Err(e) => {
+                eprintln!("[Rust] Failed to accept output client: {}", e);
+                thread::sleep(ACCEPT_POLL_INTERVAL);
+                continue;
+            }
+        };
+        let control = match stream
+            .set_nonblocking(false)
+            .and_then(|_| stream.try_clone())
+        {
+            Ok(control) => control,
+            Err(e) => {
+                eprintln!("[Rust] Failed to set up output client {}: {}", peer, e);
+                continue;
+            }
+        };
+        let _ = stream.set_nodelay(true);
+
+        // 新连接（包括断线重连）从实时数据流开始，不补发历史数据
+        let client = Arc::new(ClientQueue {
+            peer: peer.to_string(),
+            stream: control,
+            capacity,
+            // 落后的 WebSocket 仪表盘只会收到越来越旧的快照，直接断开让其重连
+            disconnect_when_full: format == OutputFormat::WebSocket,
+            frames: Mutex::new(VecDeque::new()),
+            ready: Condvar::new(),
+            sent: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        });
+
+        let running = Arc::clone(&running);
+        let clients = Arc::clone(&clients);
+        thread::spawn(move || {
+            // 握手在客户端线程中完成，不阻塞接受其他连接
+            if format == OutputFormat::WebSocket {
+                if let Err(e) = websocket_handshake(&stream) {
+                    eprintln!("[Rust] WebSocket handshake with {} failed: {}", peer, e);
+                    return;
+                }
+            }
+            clients.lock().unwrap().push(Arc::clone(&client));
+            println!("[Rust] Output client connected: {}", peer);
+            write_loop(stream, &client, &running);
+            clients
+                .lock()
+                .unwrap()
+                .retain(|other| !Arc::ptr_eq(other, &client));
+            println!("[Rust] Output client disconnected: {}", client.peer);
+        });
+    }
+}
+
+/// 持续发送客户端缓冲区中的数据，直到连接断开或服务器停止
+fn write_loop(mut stream: TcpStream, client: &ClientQueue, running: &AtomicBool) {
+    while running.load(Ordering::Relaxed) {
+        let Some(frame) = client.pop(WRITER_WAIT_TIMEOUT) else {
+            continue;
+        };
+        if stream.write_all(&frame).is_err() {
+            break;
+        }
+        client.sent.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// 完成 WebSocket 握手：读取 HTTP 升级请求并以 101 响应
+fn websocket_handshake(mut stream: &TcpStream) -> Result<(), String> {
+    stream
+        .set_read_timeout(Some(WS_HANDSHAKE_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.ends_with(b"\r\n\r\n") {
+        let n = stream.read(&mut buf).map_err(|e| e.to_string())?;
+        if n == 0 {
+            return Err("connection closed during handshake".to_string());
+        }
+        request.extend_from_slice(&buf[..n]);
+        if request.len() > WS_MAX_HANDSHAKE_BYTES {
+            return Err("handshake request too large".to_string());
+        }
+    }
+    let request = String::from_utf8_lossy(&request);
+    let key = request
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("Sec-WebSocket-Key"))
+        .map(|(_, value)| value.trim())
+        .ok_or("missing Sec-WebSocket-Key header")?;
+    let accept = base64(&sha1(format!("{}{}", key, WS_GUID).as_bytes()));
+    let response = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept
+    );
+    stream
+        .write_all(response.as_bytes())
+        .map_err(|e| e.to_string())?;
+    stream.set_read_timeout(None).map_err(|e| e.to_string())
+}
+
+/// 封装为 WebSocket 文本帧：FIN 置位、不分片，服务器发出的帧不加掩码
+fn websocket_text_frame(payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x81);
+    match payload.len() {
+        len @ 0..=125 => frame.push(len as u8),
+        len @ 126..=0xFFFF => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// SHA-1 摘要，仅用于计算握手响应的 Sec-WebSocket-Accept
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (word, bytes) in w.iter_mut().zip(block.chunks(4)) {
+            *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (i, word) in w.into_iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let next = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            (e, d, c, b, a) = (d, c, b.rotate_left(30), a, next);
+        }
+        for (h, value) in state.iter_mut().zip([a, b, c, d, e]) {
+            *h = h.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (bytes, word) in digest.chunks_mut(4).zip(state) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+/// 标准 Base64 编码（带 `=` 填充）
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let byte = |i: usize| chunk.get(i).copied().unwrap_or(0) as u32;
+        let bits = byte(0) << 16 | byte(1) << 8 | byte(2);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(bits >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::time::Instant;
+
+    /// 等待服务器登记指定数量的客户端
+    fn wait_for_clients(server: &OutputServer, count: usize) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while server.client_stats().len() < count {
+            assert!(Instant::now() < deadline, "clients did not connect");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_slow_client_does_not_stall_fast_client() {
+        const FRAME_BYTES: usize = 256 * 1024;
+        const FRAMES: usize = 100;
+
+        let server = OutputServer::start(OutputFormat::Avr, 0, 8, false).unwrap();
+        let addr = ("127.0.0.1", server.port());
+        // 慢速客户端只连接不读取
+        let _slow = TcpStream::connect(addr).unwrap();
+        wait_for_clients(&server, 1);
+        let mut fast = TcpStream::connect(addr).unwrap();
+        let fast_peer = fast.local_addr().unwrap().to_string();
+        wait_for_clients(&server, 2);
+
+        let reader = thread::spawn(move || {
+            let mut buf = vec![0u8; FRAME_BYTES];
+            let mut total = 0;
+            while total < FRAME_BYTES * FRAMES {
+                match fast.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => total += n,
+                }
+            }
+            total
+        });
+
+        // 发布不应被慢速客户端阻塞
+        let started = Instant::now();
+        let frame = vec![b'A'; FRAME_BYTES];
+        for _ in 0..FRAMES {
+            server.publish_frame(&frame);
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // 快速客户端收到全部数据，慢速客户端落后并丢弃旧数据
+        assert_eq!(reader.join().unwrap(), FRAME_BYTES * FRAMES);
+        let stats = server.client_stats();
+        let (fast, slow): (Vec<_>, Vec<_>) = stats.iter().partition(|s| s.peer == fast_peer);
+        assert_eq!(fast[0].frames_dropped, 0);
+        assert!(slow[0].frames_dropped > 0);
+        assert!(slow[0].frames_sent < FRAMES as u64);
+    }
+
+    #[test]
+    fn test_binds_loopback_unless_external() {
+        let local = OutputServer::start(OutputFormat::Avr, 0, 8, false).unwrap();
+        assert!(local.addr.ip().is_loopback());
+        let external = OutputServer::start(OutputFormat::Avr, 0, 8, true).unwrap();
+        assert!(external.addr.ip().is_unspecified());
+        TcpStream::connect(("127.0.0.1", external.port())).unwrap();
+    }
+
+    #[test]
+    fn test_beast_frame_over_tcp() {
+        let server = OutputServer::start(OutputFormat::Beast, 0, 16, false).unwrap();
+        let mut client = TcpStream::connect(("127.0.0.1", server.port())).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        wait_for_clients(&server, 1);
+
+        server.publish(&[AdsbEvent {
+            hex_message: "8D40621D58C382D690C8AC2863A7".to_string(),
+            aircraft_id: "40621D".to_string(),
+            message_type: crate::adsb::MessageType::Position,
+            rssi: None,
+            spoofed: false,
+        }]);
+
+        // 读取一帧并去掉转义：类型、6 字节时间戳、信号强度和 14 字节消息
+        let mut header = [0u8; 2];
+        client.read_exact(&mut header).unwrap();
+        assert_eq!(header, [BEAST_ESCAPE, b'3']);
+        let mut payload = Vec::new();
+        let mut byte = [0u8; 1];
+        while payload.len() < 6 + 1 + 14 {
+            client.read_exact(&mut byte).unwrap();
+            if byte[0] == BEAST_ESCAPE {
+                client.read_exact(&mut byte).unwrap();
+                assert_eq!(byte[0], BEAST_ESCAPE);
+            }
+            payload.push(byte[0]);
+        }
+        assert_eq!(payload[6], BEAST_SIGNAL_LEVEL);
+        assert_eq!(
+            &payload[7..],
+            [0x8D, 0x40, 0x62, 0x1D, 0x58, 0xC3, 0x82, 0xD6, 0x90, 0xC8, 0xAC, 0x28, 0x63, 0xA7]
+        );
+    }
+
+    #[test]
+    fn test_sbs_rows_over_tcp() {
+        let server = OutputServer::start(OutputFormat::Sbs, 0, 16, false).unwrap();
+        let client = TcpStream::connect(("127.0.0.1", server.port())).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        wait_for_clients(&server, 1);
+
+        let mut sim = crate::adsb::AdsbSimulator::with_seed(22.5431, 114.0579, 7);
+        sim.generate_mock_aircrafts(1).unwrap();
+        let mut aircraft = sim.get_aircrafts()[0].clone();
+        aircraft.callsign = "CCA1234".to_string();
+        let messages = sim.generate_all_messages();
+        server.publish_aircraft(&[aircraft.clone()], &messages);
+
+        let mut reader = std::io::BufReader::new(client);
+        let mut line = String::new();
+        std::io::BufRead::read_line(&mut reader, &mut line).unwrap();
+        let fields: Vec<&str> = line.trim_end_matches('\n').split(',').collect();
+        assert_eq!(fields.len(), 22);
+        assert_eq!(&fields[..2], ["MSG", "3"]);
+        assert_eq!(fields[4], aircraft.id);
+        assert_eq!(fields[10], "CCA1234");
+        assert_eq!(fields[11], format!("{:.0}", aircraft.altitude));
+        assert!((fields[14].parse::<f64>().unwrap() - aircraft.lat).abs() < 1e-4);
+        // 日期 YYYY/MM/DD，时间 HH:MM:SS.mmm
+        assert_eq!(fields[6].len(), 10);
+        assert_eq!(fields[7].len(), 12);
+
+        line.clear();
+        std::io::BufRead::read_line(&mut reader, &mut line).unwrap();
+        let fields: Vec<&str> = line.trim_end_matches('\n').split(',').collect();
+        assert_eq!(fields.len(), 22);
+        assert_eq!(fields[1], "4");
+        assert_eq!(fields[12], format!("{:.0}", aircraft.speed));
+    }
+
+    #[test]
+    fn test_sbs_rows_follow_received_messages() {
+        let server = OutputServer::start(OutputFormat::Sbs, 0, 16, false).unwrap();
+        let client = TcpStream::connect(("127.0.0.1", server.port())).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        wait_for_clients(&server, 1);
+
+        let mut sim = crate::adsb::AdsbSimulator::with_seed(22.5431, 114.0579, 8);
+        sim.generate_mock_aircrafts(2).unwrap();
+        let mut aircrafts = sim.get_aircrafts().clone();
+        aircrafts[1].on_ground = true;
+        aircrafts[1].altitude = 0.0;
+        let messages = sim.generate_all_messages();
+        let of = |id: &str| -> Vec<AdsbEvent> {
+            let own = messages.iter().filter(|m| m.aircraft_id == id);
+            own.cloned().collect()
+        };
+
+        // 只有地面飞机的消息被接收：仅输出其 MSG,2 地面位置行
+        server.publish_aircraft(&aircrafts, &of(&aircrafts[1].id));
+        // 随后只有空中飞机的消息被接收：输出 MSG,3 与 MSG,4
+        server.publish_aircraft(&aircrafts, &of(&aircrafts[0].id));
+
+        let mut reader = std::io::BufReader::new(client);
+        let mut rows = Vec::new();
+        for _ in 0..3 {
+            let mut line = String::new();
+            std::io::BufRead::read_line(&mut reader, &mut line).unwrap();
+            let fields: Vec<String> = line.trim_end().split(',').map(String::from).collect();
+            assert_eq!(fields.len(), 22);
+            rows.push((fields[1].clone(), fields[4].clone(), fields[21].clone()));
+        }
+        let ground = (aircrafts[1].id.clone(), "-1".to_string());
+        let airborne = (aircrafts[0].id.clone(), "0".to_string());
+        assert_eq!(
+            rows,
+            vec![
+                ("2".to_string(), ground.0, ground.1),
+                ("3".to_string(), airborne.0.clone(), airborne.1.clone()),
+                ("4".to_string(), airborne.0, airborne.1),
+            ]
+        );
+    }
+
+    /// 以 RFC 6455 示例密钥完成 WebSocket 握手
+    fn connect_websocket(server: &OutputServer) -> TcpStream {
+        let mut client = TcpStream::connect(("127.0.0.1", server.port())).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        client
+            .write_all(
+                b"GET / HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\n\
+                  Connection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+                  Sec-WebSocket-Version: 13\r\n\r\n",
+            )
+            .unwrap();
+        let mut response = Vec::new();
+        let mut byte = [0u8; 1];
+        while !response.ends_with(b"\r\n\r\n") {
+            client.read_exact(&mut byte).unwrap();
+            response.push(byte[0]);
+        }
+        let response = String::from_utf8(response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 101"));
+        assert!(response.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n"));
+        client
+    }
+
+    #[test]
+    fn test_websocket_json_batches() {
+        let server = OutputServer::start(OutputFormat::WebSocket, 0, 4, false).unwrap();
+        let mut first = connect_websocket(&server);
+        let mut second = connect_websocket(&server);
+        wait_for_clients(&server, 2);
+
+        let mut sim = crate::adsb::AdsbSimulator::with_seed(22.5431, 114.0579, 8);
+        sim.generate_mock_aircrafts(3).unwrap();
+        server.publish_batch(&crate::build_batch(&mut sim, 1, 0, 0));
+
+        // 每个订阅者都收到一条未掩码的文本帧，载荷为完整的批次 JSON
+        for client in [&mut first, &mut second] {
+            let mut header = [0u8; 4];
+            client.read_exact(&mut header).unwrap();
+            assert_eq!(&header[..2], [0x81, 126]);
+            let mut payload = vec![0u8; u16::from_be_bytes([header[2], header[3]]) as usize];
+            client.read_exact(&mut payload).unwrap();
+            let batch: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+            assert_eq!(batch["aircrafts"].as_array().unwrap().len(), 3);
+            assert!(!batch["messages"].as_array().unwrap().is_empty());
+            assert_eq!(batch["tick"], 0);
+        }
+    }
+
+    #[test]
+    fn test_slow_websocket_client_disconnected() {
+        let server = OutputServer::start(OutputFormat::WebSocket, 0, 4, false).unwrap();
+        // 只握手不读取的客户端
+        let _slow = connect_websocket(&server);
+        wait_for_clients(&server, 1);
+
+        // 发送缓冲区积压后断开该客户端，发布不被阻塞
+        let started = Instant::now();
+        let frame = websocket_text_frame(&vec![b'A'; 256 * 1024]);
+        while !server.client_stats().is_empty() {
+            assert!(started.elapsed() < Duration::from_secs(5));
+            server.publish_frame(&frame);
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_sha1_base64() {
+        let hex =
+            |digest: [u8; 20]| -> String { digest.iter().map(|b| format!("{:02x}", b)).collect() };
+        assert_eq!(hex(sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        let abc = hex(sha1(b"abc"));
+        assert_eq!(abc, "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+    }
+}
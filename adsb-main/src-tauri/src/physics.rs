@@ -0,0 +1,196 @@
+use crate::adsb::{Aircraft, AircraftCategory, TrackPoint};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+/// 高于该高度 (ft) 视为空中
+const AIRBORNE_ALTITUDE_FT: f64 = 1000.0;
+/// 地面状态下的最大合理速度 (kts)
+const MAX_SURFACE_SPEED_KTS: f64 = 200.0;
+
+/// NIC 1-11 对应的水平包容半径 Rc (nm)，米制上限按 1 nm = 1852 m 换算
+const CONTAINMENT_RADIUS_NM: [f64; 11] = [
+    20.0,
+    8.0,
+    4.0,
+    2.0,
+    1.0,
+    0.6,
+    0.2,
+    0.1,
+    75.0 / 1852.0,
+    25.0 / 1852.0,
+    7.5 / 1852.0,
+];
+
+/// 某类飞机的性能包线
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PerformanceEnvelope {
+    pub max_speed: f64,          // 最大合理地速 (kts)
+    pub min_airborne_speed: f64, // 空中飞行的最低速度 (kts)，低于该值视为失速
+    pub max_vertical_rate: f64,  // 最大合理垂直速率 (ft/min)
+}
+
+/// 发射机类别对应的性能包线，轻型飞机与旋翼机远比运输类飞机慢，高性能飞机远比其快
+pub fn envelope(category: AircraftCategory) -> PerformanceEnvelope {
+    let (max_speed, min_airborne_speed, max_vertical_rate) = match category {
+        AircraftCategory::Light => (250.0, 50.0, 2000.0),
+        AircraftCategory::Small => (500.0, 90.0, 4000.0),
+        AircraftCategory::Large | AircraftCategory::HighVortexLarge => (650.0, 100.0, 6000.0),
+        AircraftCategory::Heavy => (650.0, 120.0, 5000.0),
+        AircraftCategory::HighPerformance => (1500.0, 120.0, 30000.0),
+        // 旋翼机可以悬停
+        AircraftCategory::Rotorcraft => (200.0, 0.0, 3000.0),
+    };
+    PerformanceEnvelope {
+        max_speed,
+        min_airborne_speed,
+        max_vertical_rate,
+    }
+}
+
+/// 物理合理性检查发现的问题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhysicsWarning {
+    pub aircraft_id: String,
+    pub callsign: String,
+    pub reason: String,
+}
+
+/// NIC 对应的包容半径 Rc (nm)，NIC 0 表示未知，超出 0-11 时返回 None
+pub fn containment_radius_nm(nic: u8) -> Option<f64> {
+    match nic {
+        0 => Some(f64::INFINITY),
+        1..=11 => Some(CONTAINMENT_RADIUS_NM[nic as usize - 1]),
+        _ => None,
+    }
+}
+
+/// NIC 对应的可读完整性等级，如 NIC 8 为 "<0.1 NM"；小于 0.1 nm 的半径以米表示
+pub fn integrity_label(nic: u8) -> String {
+    match containment_radius_nm(nic) {
+        Some(rc) if rc.is_infinite() => "Unknown".to_string(),
+        Some(rc) if rc >= 0.1 => format!("<{} NM", rc),
+        Some(rc) => format!("<{} m", (rc * 1852.0 * 10.0).round() / 10.0),
+        None => "Invalid".to_string(),
+    }
+}
+
+/// 检查机队状态的物理合理性：速度包线、垂直速率、高度/速度匹配与 NIC
+///
+/// 速度与垂直速率按各飞机发射机类别的性能包线 ([`envelope`]) 检查。
+/// 垂直速率由航迹历史最近两个点估算，历史不足时跳过该项。
+pub fn validate_physics(
+    aircrafts: &[Aircraft],
+    history: &BTreeMap<String, VecDeque<TrackPoint>>,
+) -> Vec<PhysicsWarning> {
+    let mut warnings = Vec::new();
+    for aircraft in aircrafts {
+        let limits = envelope(aircraft.category);
+        let mut warn = |reason: String| {
+            warnings.push(PhysicsWarning {
+                aircraft_id: aircraft.id.clone(),
+                callsign: aircraft.callsign.clone(),
+                reason,
+            })
+        };
+
+        if aircraft.speed > limits.max_speed {
+            warn(format!(
+                "speed {:.0} kts exceeds {:?} envelope of {:.0} kts",
+                aircraft.speed, aircraft.category, limits.max_speed
+            ));
+        }
+        let airborne = aircraft.altitude > AIRBORNE_ALTITUDE_FT;
+        if airborne && aircraft.speed < limits.min_airborne_speed {
+            warn(format!(
+                "speed {:.0} kts below stall at {:.0} ft",
+                aircraft.speed, aircraft.altitude
+            ));
+        }
+        if aircraft.altitude <= 0.0 && aircraft.speed > MAX_SURFACE_SPEED_KTS {
+            warn(format!("speed {:.0} kts on surface", aircraft.speed));
+        }
+        if containment_radius_nm(aircraft.nic).is_none() {
+            warn(format!(
+                "NIC {} has no defined containment radius",
+                aircraft.nic
+            ));
+        }
+
+        if let Some(track) = history.get(&aircraft.id).filter(|t| t.len() >= 2) {
+            let (previous, last) = (&track[track.len() - 2], &track[track.len() - 1]);
+            let dt = last.time - previous.time;
+            if dt > 0.0 {
+                let rate = (last.altitude - previous.altitude) / dt * 60.0;
+                if rate.abs() > limits.max_vertical_rate {
+                    warn(format!(
+                        "vertical rate {:.0} ft/min exceeds {:.0} ft/min",
+                        rate, limits.max_vertical_rate
+                    ));
+                }
+            }
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adsb::AdsbSimulator;
+
+    #[test]
+    fn test_excessive_speed_flagged() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 36);
+        sim.generate_mock_aircrafts(3).unwrap();
+        sim.update_positions();
+        sim.update_positions();
+        assert!(validate_physics(sim.get_aircrafts(), sim.get_history()).is_empty());
+
+        let mut aircrafts = sim.get_aircrafts().clone();
+        aircrafts[1].speed = 3000.0;
+        let warnings = validate_physics(&aircrafts, sim.get_history());
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].aircraft_id, aircrafts[1].id);
+        assert!(warnings[0].reason.contains("3000 kts"));
+
+        assert_eq!(containment_radius_nm(8), Some(0.1));
+        assert_eq!(containment_radius_nm(12), None);
+    }
+
+    #[test]
+    fn test_envelope_depends_on_category() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 36);
+        sim.generate_mock_aircrafts(1).unwrap();
+        let mut aircraft = sim.get_aircrafts()[0].clone();
+        aircraft.altitude = 3000.0;
+        aircraft.speed = 300.0;
+
+        // 300 kts 对运输类飞机正常，对轻型飞机超出包线
+        aircraft.category = AircraftCategory::Large;
+        let history = BTreeMap::new();
+        assert!(validate_physics(&[aircraft.clone()], &history).is_empty());
+        aircraft.category = AircraftCategory::Light;
+        let warnings = validate_physics(&[aircraft.clone()], &history);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].reason.contains("Light envelope of 250 kts"));
+
+        // 旋翼机低速飞行不视为失速
+        aircraft.category = AircraftCategory::Rotorcraft;
+        aircraft.speed = 40.0;
+        assert!(validate_physics(&[aircraft.clone()], &history).is_empty());
+        aircraft.category = AircraftCategory::Large;
+        assert_eq!(validate_physics(&[aircraft], &history).len(), 1);
+    }
+
+    #[test]
+    fn test_integrity_label() {
+        assert_eq!(integrity_label(8), "<0.1 NM");
+        assert_eq!(integrity_label(1), "<20 NM");
+        assert_eq!(integrity_label(6), "<0.6 NM");
+        assert_eq!(integrity_label(9), "<75 m");
+        assert_eq!(integrity_label(11), "<7.5 m");
+        assert_eq!(integrity_label(0), "Unknown");
+        assert_eq!(integrity_label(12), "Invalid");
+    }
+}
@@ -0,0 +1,392 @@
+//! 批次事件的 protobuf 编解码（schema 见 `proto/adsb.proto`）
+//!
+//! 消息结构简单，直接按 protobuf 线格式手写编解码，不引入代码生成依赖。
+
+use crate::adsb::{
+    AdsbEvent, AdsbSource, Aircraft, AircraftCategory, CapabilityClass, CapabilityProfile,
+    EmergencyKind, MessageType,
+};
+use crate::AdsbBatchEvent;
+use std::collections::HashMap;
+
+/// 线格式类型：varint
+const WIRE_VARINT: u8 = 0;
+/// 线格式类型：64 位定长
+const WIRE_FIXED64: u8 = 1;
+/// 线格式类型：长度前缀
+const WIRE_LEN: u8 = 2;
+/// 线格式类型：32 位定长
+const WIRE_FIXED32: u8 = 5;
+
+/// 消息类型与 proto 枚举值的对应关系
+const MESSAGE_TYPES: [(MessageType, u64); 9] = [
+    (MessageType::Identification, 1),
+    (MessageType::SurfacePosition, 2),
+    (MessageType::Position, 3),
+    (MessageType::Velocity, 4),
+    (MessageType::Status, 5),
+    (MessageType::TargetState, 6),
+    (MessageType::OperationalStatus, 7),
+    (MessageType::Other, 8),
+    (MessageType::AllCall, 9),
+];
+
+/// 将批次事件编码为 protobuf 字节
+pub fn encode_batch(batch: &AdsbBatchEvent) -> Vec<u8> {
+    let mut out = Vec::new();
+    for message in &batch.messages {
+        write_message(&mut out, 1, &encode_event(message));
+    }
+    for aircraft in &batch.aircrafts {
+        write_message(&mut out, 2, &encode_aircraft(aircraft));
+    }
+    // map 字段按 key 排序输出，保证同一批次编码结果稳定
+    let mut colors: Vec<_> = batch.colors.iter().collect();
+    colors.sort();
+    for (icao, color) in colors {
+        let mut entry = Vec::new();
+        write_string(&mut entry, 1, icao);
+        write_string(&mut entry, 2, color);
+        write_message(&mut out, 3, &entry);
+    }
+    write_varint_field(&mut out, 4, batch.timestamp);
+    write_varint_field(&mut out, 5, batch.tick);
+    out
+}
+
+/// 从 protobuf 字节解码批次事件
+pub fn decode_batch(bytes: &[u8]) -> Result<AdsbBatchEvent, String> {
+    let mut batch = AdsbBatchEvent {
+        messages: Vec::new(),
+        aircrafts: Vec::new(),
+        colors: HashMap::new(),
+        trails: HashMap::new(),
+        integrity: HashMap::new(),
+        predicted: HashMap::new(),
+        external_tracks: HashMap::new(),
+        tick: 0,
+        timestamp: 0,
+        replay_offset_ms: None,
+    };
+    let mut reader = Reader::new(bytes);
+    while let Some((field, wire)) = reader.key()? {
+        match (field, wire) {
+            (1, WIRE_LEN) => batch.messages.push(decode_event(reader.bytes()?)?),
+            (2, WIRE_LEN) => batch.aircrafts.push(decode_aircraft(reader.bytes()?)?),
+            (3, WIRE_LEN) => {
+                let (icao, color) = decode_color(reader.bytes()?)?;
+                batch.colors.insert(icao, color);
+            }
+            (4, WIRE_VARINT) => batch.timestamp = reader.varint()?,
+            (5, WIRE_VARINT) => batch.tick = reader.varint()?,
+            _ => reader.skip(wire)?,
+        }
+    }
+    Ok(batch)
+}
+
+fn encode_event(event: &AdsbEvent) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_string(&mut out, 1, &event.hex_message);
+    write_string(&mut out, 2, &event.aircraft_id);
+    let code = MESSAGE_TYPES
+        .iter()
+        .find(|(t, _)| *t == event.message_type)
+        .map_or(0, |&(_, code)| code);
+    write_varint_field(&mut out, 3, code);
+    if let Some(rssi) = event.rssi {
+        write_double(&mut out, 4, rssi);
+    }
+    out
+}
+
+fn decode_event(bytes: &[u8]) -> Result<AdsbEvent, String> {
+    let mut event = AdsbEvent {
+        hex_message: String::new(),
+        aircraft_id: String::new(),
+        message_type: MessageType::Other,
+        rssi: None,
+        spoofed: false,
+    };
+    let mut reader = Reader::new(bytes);
+    while let Some((field, wire)) = reader.key()? {
+        match (field, wire) {
+            (1, WIRE_LEN) => event.hex_message = reader.string()?,
+            (2, WIRE_LEN) => event.aircraft_id = reader.string()?,
+            (3, WIRE_VARINT) => {
+                let code = reader.varint()?;
+                event.message_type = MESSAGE_TYPES
+                    .iter()
+                    .find(|&&(_, c)| c == code)
+                    .map_or(MessageType::Other, |&(t, _)| t);
+            }
+            (4, WIRE_FIXED64) => event.rssi = Some(reader.double()?),
+            _ => reader.skip(wire)?,
+        }
+    }
+    Ok(event)
+}
+
+fn encode_aircraft(aircraft: &Aircraft) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_string(&mut out, 1, &aircraft.id);
+    write_string(&mut out, 2, &aircraft.callsign);
+    write_double(&mut out, 3, aircraft.lat);
+    write_double(&mut out, 4, aircraft.lng);
+    write_double(&mut out, 5, aircraft.altitude);
+    write_double(&mut out, 6, aircraft.speed);
+    write_double(&mut out, 7, aircraft.heading);
+    write_varint_field(&mut out, 8, aircraft.nic as u64);
+    write_varint_field(&mut out, 9, aircraft.on_ground as u64);
+    write_varint_field(&mut out, 10, aircraft.version as u64);
+    write_varint_field(&mut out, 11, aircraft.nacp as u64);
+    write_varint_field(&mut out, 12, aircraft.sil as u64);
+    write_double(&mut out, 13, aircraft.vertical_rate);
+    write_varint_field(&mut out, 14, aircraft.spi as u64);
+    write_varint_field(&mut out, 15, aircraft.squawk as u64);
+    let emergency = aircraft.emergency.map_or(0, |kind| kind.code());
+    write_varint_field(&mut out, 16, emergency as u64);
+    write_double(&mut out, 17, aircraft.track);
+    write_double(&mut out, 18, aircraft.ground_speed);
+    write_varint_field(&mut out, 19, aircraft.nacv as u64);
+    write_varint_field(&mut out, 20, aircraft.category.code() as u64);
+    out
+}
+
+fn decode_aircraft(bytes: &[u8]) -> Result<Aircraft, String> {
+    let mut aircraft = Aircraft {
+        id: String::new(),
+        callsign: String::new(),
+        lat: 0.0,
+        lng: 0.0,
+        altitude: 0.0,
+        speed: 0.0,
+        heading: 0.0,
+        nic: 0,
+        vertical_rate: 0.0,
+        capability: CapabilityProfile::default(),
+        source: AdsbSource::default(),
+        phase: None,
+        target_altitude: None,
+        on_ground: false,
+        version: 0,
+        nacp: 0,
+        nacv: 0,
+        sil: 0,
+        spi: false,
+        capability_class: CapabilityClass::default(),
+        category: AircraftCategory::default(),
+        squawk: 0,
+        emergency: None,
+        track: 0.0,
+        ground_speed: 0.0,
+    };
+    let mut reader = Reader::new(bytes);
+    while let Some((field, wire)) = reader.key()? {
+        match (field, wire) {
+            (1, WIRE_LEN) => aircraft.id = reader.string()?,
+            (2, WIRE_LEN) => aircraft.callsign = reader.string()?,
+            (3, WIRE_FIXED64) => aircraft.lat = reader.double()?,
+            (4, WIRE_FIXED64) => aircraft.lng = reader.double()?,
+            (5, WIRE_FIXED64) => aircraft.altitude = reader.double()?,
+            (6, WIRE_FIXED64) => aircraft.speed = reader.double()?,
+            (7, WIRE_FIXED64) => aircraft.heading = reader.double()?,
+            (8, WIRE_VARINT) => aircraft.nic = small_uint(reader.varint()?)?,
+            (9, WIRE_VARINT) => aircraft.on_ground = reader.varint()? != 0,
+            (10, WIRE_VARINT) => aircraft.version = small_uint(reader.varint()?)?,
+            (11, WIRE_VARINT) => aircraft.nacp = small_uint(reader.varint()?)?,
+            (12, WIRE_VARINT) => aircraft.sil = small_uint(reader.varint()?)?,
+            (13, WIRE_FIXED64) => aircraft.vertical_rate = reader.double()?,
+            (14, WIRE_VARINT) => aircraft.spi = reader.varint()? != 0,
+            (15, WIRE_VARINT) => {
+                aircraft.squawk = u16::try_from(reader.varint()?).map_err(|e| e.to_string())?
+            }
+            (16, WIRE_VARINT) => {
+                aircraft.emergency = EmergencyKind::from_code(small_uint(reader.varint()?)?)
+            }
+            (17, WIRE_FIXED64) => aircraft.track = reader.double()?,
+            (18, WIRE_FIXED64) => aircraft.ground_speed = reader.double()?,
+            (19, WIRE_VARINT) => aircraft.nacv = small_uint(reader.varint()?)?,
+            (20, WIRE_VARINT) => {
+                let code = small_uint(reader.varint()?)?;
+                aircraft.category = AircraftCategory::from_code(code).unwrap_or_default();
+            }
+            _ => reader.skip(wire)?,
+        }
+    }
+    Ok(aircraft)
+}
+
+fn small_uint(value: u64) -> Result<u8, String> {
+    u8::try_from(value).map_err(|e| e.to_string())
+}
+
+fn decode_color(bytes: &[u8]) -> Result<(String, String), String> {
+    let (mut icao, mut color) = (String::new(), String::new());
+    let mut reader = Reader::new(bytes);
+    while let Some((field, wire)) = reader.key()? {
+        match (field, wire) {
+            (1, WIRE_LEN) => icao = reader.string()?,
+            (2, WIRE_LEN) => color = reader.string()?,
+            _ => reader.skip(wire)?,
+        }
+    }
+    Ok((icao, color))
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7F) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn write_key(out: &mut Vec<u8>, field: u32, wire: u8) {
+    write_varint(out, ((field as u64) << 3) | wire as u64);
+}
+
+// proto3 标量字段取默认值时不输出
+
+fn write_varint_field(out: &mut Vec<u8>, field: u32, value: u64) {
+    if value != 0 {
+        write_key(out, field, WIRE_VARINT);
+        write_varint(out, value);
+    }
+}
+
+fn write_double(out: &mut Vec<u8>, field: u32, value: f64) {
+    if value.to_bits() != 0 {
+        write_key(out, field, WIRE_FIXED64);
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+fn write_string(out: &mut Vec<u8>, field: u32, value: &str) {
+    if !value.is_empty() {
+        write_message(out, field, value.as_bytes());
+    }
+}
+
+fn write_message(out: &mut Vec<u8>, field: u32, payload: &[u8]) {
+    write_key(out, field, WIRE_LEN);
+    write_varint(out, payload.len() as u64);
+    out.extend_from_slice(payload);
+}
+
+/// protobuf 线格式读取器
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    /// 读取下一个字段的 (编号, 线格式类型)，到达末尾时返回 None
+    fn key(&mut self) -> Result<Option<(u32, u8)>, String> {
+        if self.pos >= self.bytes.len() {
+            return Ok(None);
+        }
+        let key = self.varint()?;
+        let field = u32::try_from(key >> 3).map_err(|e| e.to_string())?;
+        Ok(Some((field, (key & 0x7) as u8)))
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self.bytes.get(self.pos).ok_or("Truncated varint")?;
+            self.pos += 1;
+            value |= ((byte & 0x7F) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("Varint too long".to_string())
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or("Truncated field")?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], String> {
+        let len = self.varint()?;
+        self.take(usize::try_from(len).map_err(|e| e.to_string())?)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        String::from_utf8(self.bytes()?.to_vec()).map_err(|e| e.to_string())
+    }
+
+    fn double(&mut self) -> Result<f64, String> {
+        let raw: [u8; 8] = self.take(8)?.try_into().map_err(|_| "Truncated double")?;
+        Ok(f64::from_le_bytes(raw))
+    }
+
+    /// 跳过未知字段
+    fn skip(&mut self, wire: u8) -> Result<(), String> {
+        match wire {
+            WIRE_VARINT => self.varint().map(|_| ()),
+            WIRE_FIXED64 => self.take(8).map(|_| ()),
+            WIRE_LEN => self.bytes().map(|_| ()),
+            WIRE_FIXED32 => self.take(4).map(|_| ()),
+            _ => Err(format!("Unsupported wire type {}", wire)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adsb::AdsbSimulator;
+    use crate::build_batch;
+
+    #[test]
+    fn test_batch_protobuf_roundtrip() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 35);
+        sim.generate_mock_aircrafts(5).unwrap();
+        let batch = build_batch(&mut sim, 3, 3, 0);
+        assert!(!batch.messages.is_empty());
+
+        let bytes = encode_batch(&batch);
+        let json = serde_json::to_vec(&batch).unwrap();
+        assert!(bytes.len() < json.len());
+
+        let decoded = decode_batch(&bytes).unwrap();
+        assert_eq!(decoded.timestamp, batch.timestamp);
+        assert_eq!(decoded.tick, batch.tick);
+        assert_eq!(decoded.colors, batch.colors);
+        assert_eq!(decoded.messages.len(), batch.messages.len());
+        for (a, b) in decoded.messages.iter().zip(&batch.messages) {
+            assert_eq!(a.hex_message, b.hex_message);
+            assert_eq!(a.aircraft_id, b.aircraft_id);
+            assert_eq!(a.message_type, b.message_type);
+        }
+        assert_eq!(decoded.aircrafts.len(), batch.aircrafts.len());
+        for (a, b) in decoded.aircrafts.iter().zip(&batch.aircrafts) {
+            assert_eq!((&a.id, &a.callsign, a.nic), (&b.id, &b.callsign, b.nic));
+            assert_eq!(
+                (a.version, a.nacp, a.nacv, a.sil),
+                (b.version, b.nacp, b.nacv, b.sil)
+            );
+            assert_eq!(a.spi, b.spi);
+            assert_eq!((a.squawk, a.emergency), (b.squawk, b.emergency));
+            assert_eq!(a.category, b.category);
+            assert_eq!((a.lat, a.lng, a.altitude), (b.lat, b.lng, b.altitude));
+            assert_eq!((a.speed, a.heading), (b.speed, b.heading));
+            assert_eq!((a.ground_speed, a.track), (b.ground_speed, b.track));
+        }
+
+        assert!(decode_batch(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
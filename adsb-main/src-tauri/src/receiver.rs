@@ -0,0 +1,252 @@
+use crate::adsb::{AdsbEvent, Aircraft};
+use crate::geo;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// 覆盖盲区分析的最大采样点数，防止分辨率过细时耗尽内存
+const MAX_COVERAGE_SAMPLES: usize = 1_000_000;
+
+/// 地面接收站
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Receiver {
+    pub name: String,
+    pub lat: f64,
+    pub lng: f64,
+    #[serde(default)]
+    pub altitude: f64, // 天线高度 (ft)
+    pub range_nm: f64, // 最大接收距离 (nm)
+}
+
+impl Receiver {
+    /// 判断能否收到该飞机的信号，可接收时返回距离 (nm)
+    ///
+    /// 需同时满足接收距离和无线电视距两个条件。
+    pub fn hears(&self, aircraft: &Aircraft) -> Option<f64> {
+        self.covers(aircraft.lat, aircraft.lng, aircraft.altitude)
+    }
+
+    /// 判断该位置、高度 (ft) 是否在覆盖范围内，覆盖时返回距离 (nm)
+    pub fn covers(&self, lat: f64, lng: f64, altitude_ft: f64) -> Option<f64> {
+        let distance = geo::distance_nm(self.lat, self.lng, lat, lng);
+        (distance <= self.reach_nm(altitude_ft)).then_some(distance)
+    }
+
+    /// 指定飞机高度下的有效覆盖半径 (nm)，取接收距离与视距的较小值
+    fn reach_nm(&self, altitude_ft: f64) -> f64 {
+        self.range_nm
+            .min(geo::radar_horizon_nm(altitude_ft, self.altitude))
+    }
+}
+
+/// 单个接收站的接收统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiverStats {
+    pub name: String,
+    pub messages_received: u64,
+    pub messages_dropped: u64,
+    pub unique_aircraft: usize, // 收到过消息的飞机数
+    pub max_range_nm: f64,      // 收到消息的最远距离 (nm)
+    pub drop_rate: f64,         // 丢失比例 (0-1)
+}
+
+/// 单个接收站的累计计数
+#[derive(Debug)]
+struct ReceiverTally {
+    receiver: Receiver,
+    received: u64,
+    dropped: u64,
+    heard: HashSet<String>,
+    max_range_nm: f64,
+}
+
+/// 多接收站网络：按各站的覆盖范围统计每条消息的接收情况
+#[derive(Debug, Default)]
+pub struct ReceiverNetwork {
+    tallies: Vec<ReceiverTally>,
+}
+
+impl ReceiverNetwork {
+    pub fn new(receivers: Vec<Receiver>) -> Self {
+        ReceiverNetwork {
+            tallies: receivers
+                .into_iter()
+                .map(|receiver| ReceiverTally {
+                    receiver,
+                    received: 0,
+                    dropped: 0,
+                    heard: HashSet::new(),
+                    max_range_nm: 0.0,
+                })
+                .collect(),
+        }
+    }
+
+    /// 统计一批消息，消息发送者的位置从 `aircrafts` 中按 ICAO 查找
+    pub fn observe(&mut self, messages: &[AdsbEvent], aircrafts: &[Aircraft]) {
+        let by_id: HashMap<&str, &Aircraft> =
+            aircrafts.iter().map(|a| (a.id.as_str(), a)).collect();
+
+        for message in messages {
+            let Some(aircraft) = by_id.get(message.aircraft_id.as_str()) else {
+                continue;
+            };
+            for tally in &mut self.tallies {
+                match tally.receiver.hears(aircraft) {
+                    Some(distance) => {
+                        tally.received += 1;
+                        tally.heard.insert(aircraft.id.clone());
+                        tally.max_range_nm = tally.max_range_nm.max(distance);
+                    }
+                    None => tally.dropped += 1,
+                }
+            }
+        }
+    }
+
+    /// 在各站覆盖范围的外接矩形内按 `grid_resolution` (°) 采样，
+    /// 返回指定高度下没有任何接收站覆盖的点 (纬度, 经度)
+    pub fn coverage_gaps(
+        &self,
+        altitude_ft: f64,
+        grid_resolution: f64,
+    ) -> Result<Vec<(f64, f64)>, String> {
+        if !(grid_resolution.is_finite() && grid_resolution > 0.0) {
+            return Err(format!("Invalid grid resolution: {}", grid_resolution));
+        }
+        let receivers: Vec<&Receiver> = self.tallies.iter().map(|t| &t.receiver).collect();
+        if receivers.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // 外接矩形：1 nm = 1/60 度纬度，经度按所在纬度缩放
+        let (mut south, mut north) = (f64::INFINITY, f64::NEG_INFINITY);
+        let (mut west, mut east) = (f64::INFINITY, f64::NEG_INFINITY);
+        for receiver in &receivers {
+            let reach = receiver.reach_nm(altitude_ft);
+            let dlat = reach / 60.0;
+            let dlng = reach / (60.0 * receiver.lat.to_radians().cos().max(1e-6));
+            south = south.min(receiver.lat - dlat);
+            north = north.max(receiver.lat + dlat);
+            west = west.min(receiver.lng - dlng);
+            east = east.max(receiver.lng + dlng);
+        }
+
+        let rows = ((north - south) / grid_resolution).floor() as usize + 1;
+        let cols = ((east - west) / grid_resolution).floor() as usize + 1;
+        if rows.saturating_mul(cols) > MAX_COVERAGE_SAMPLES {
+            return Err(format!(
+                "Grid resolution {} yields {} x {} samples (max {})",
+                grid_resolution, rows, cols, MAX_COVERAGE_SAMPLES
+            ));
+        }
+
+        let mut gaps = Vec::new();
+        for row in 0..rows {
+            let lat = south + row as f64 * grid_resolution;
+            for col in 0..cols {
+                let lng = west + col as f64 * grid_resolution;
+                if receivers
+                    .iter()
+                    .all(|receiver| receiver.covers(lat, lng, altitude_ft).is_none())
+                {
+                    gaps.push((lat, lng));
+                }
+            }
+        }
+        Ok(gaps)
+    }
+
+    pub fn stats(&self) -> Vec<ReceiverStats> {
+        self.tallies
+            .iter()
+            .map(|tally| {
+                let total = tally.received + tally.dropped;
+                ReceiverStats {
+                    name: tally.receiver.name.clone(),
+                    messages_received: tally.received,
+                    messages_dropped: tally.dropped,
+                    unique_aircraft: tally.heard.len(),
+                    max_range_nm: tally.max_range_nm,
+                    drop_rate: if total > 0 {
+                        tally.dropped as f64 / total as f64
+                    } else {
+                        0.0
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adsb::AdsbSimulator;
+
+    fn receiver(name: &str, range_nm: f64) -> Receiver {
+        Receiver {
+            name: name.to_string(),
+            lat: 22.5431,
+            lng: 114.0579,
+            altitude: 100.0,
+            range_nm,
+        }
+    }
+
+    #[test]
+    fn test_short_range_hears_fewer_aircraft() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 5);
+        sim.generate_mock_aircrafts(20).unwrap();
+        let mut network =
+            ReceiverNetwork::new(vec![receiver("short", 20.0), receiver("long", 200.0)]);
+
+        for _ in 0..10 {
+            sim.update_positions();
+            let messages = sim.generate_all_messages();
+            network.observe(&messages, sim.get_aircrafts());
+        }
+
+        let stats = network.stats();
+        let (short, long) = (&stats[0], &stats[1]);
+        assert_eq!(long.unique_aircraft, 20);
+        assert_eq!(long.messages_dropped, 0);
+        assert!(short.unique_aircraft < long.unique_aircraft);
+        assert!(short.messages_received < long.messages_received);
+        assert!(short.drop_rate > 0.0);
+        assert!(short.max_range_nm <= 20.0);
+        assert!(long.max_range_nm > 20.0);
+    }
+
+    #[test]
+    fn test_coverage_gaps_between_receivers() {
+        let west = Receiver {
+            lng: 113.5,
+            ..receiver("west", 20.0)
+        };
+        let east = Receiver {
+            lng: 114.5,
+            ..receiver("east", 20.0)
+        };
+        let network = ReceiverNetwork::new(vec![west.clone(), east]);
+        let gaps = network.coverage_gaps(10000.0, 0.05).unwrap();
+        assert!(!gaps.is_empty());
+
+        // 两站相距约 55 nm，中点距各站约 28 nm，超出 20 nm 的接收距离
+        let near = |lat: f64, lng: f64| {
+            gaps.iter()
+                .any(|&(glat, glng)| (glat - lat).abs() < 0.03 && (glng - lng).abs() < 0.03)
+        };
+        assert!(near(22.5431, 114.0));
+        // 接收站所在位置被覆盖
+        assert!(!near(west.lat, west.lng));
+        assert!(gaps
+            .iter()
+            .all(|&(lat, lng)| west.covers(lat, lng, 10000.0).is_none()));
+
+        assert!(network.coverage_gaps(10000.0, 0.0).is_err());
+        assert!(ReceiverNetwork::default()
+            .coverage_gaps(10000.0, 0.1)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,106 @@
+use crate::AdsbBatchEvent;
+use std::collections::VecDeque;
+
+/// 缺省保留的时间窗口 (s)
+pub const DEFAULT_RECENT_WINDOW_S: u64 = 30;
+/// 缺省最多保留的批次数，限制内存占用
+pub const DEFAULT_RECENT_MAX_BATCHES: usize = 2000;
+
+/// 最近发送批次的环形缓冲区
+///
+/// 按批次时间戳保留最近 `window_ms` 内的批次，同时不超过 `max_batches` 个，
+/// 供后加入的前端补齐近期数据或短时回看。
+pub struct RecentEvents {
+    window_ms: u64,
+    max_batches: usize,
+    batches: VecDeque<AdsbBatchEvent>,
+}
+
+impl Default for RecentEvents {
+    fn default() -> Self {
+        Self::new(DEFAULT_RECENT_WINDOW_S, DEFAULT_RECENT_MAX_BATCHES)
+    }
+}
+
+impl RecentEvents {
+    pub fn new(window_s: u64, max_batches: usize) -> Self {
+        RecentEvents {
+            window_ms: window_s.saturating_mul(1000),
+            max_batches,
+            batches: VecDeque::new(),
+        }
+    }
+
+    /// 追加一个批次，并淘汰超出时间窗口或数量上限的旧批次
+    pub fn push(&mut self, batch: AdsbBatchEvent) {
+        let cutoff = batch.timestamp.saturating_sub(self.window_ms);
+        self.batches.push_back(batch);
+        while self
+            .batches
+            .front()
+            .is_some_and(|oldest| oldest.timestamp < cutoff)
+            || self.batches.len() > self.max_batches
+        {
+            self.batches.pop_front();
+        }
+    }
+
+    /// 最近 `seconds` 秒内（相对最新批次）的批次，按时间先后排列
+    pub fn recent(&self, seconds: f64) -> Vec<AdsbBatchEvent> {
+        let Some(latest) = self.batches.back() else {
+            return Vec::new();
+        };
+        let cutoff = latest
+            .timestamp
+            .saturating_sub((seconds.max(0.0) * 1000.0) as u64);
+        self.batches
+            .iter()
+            .filter(|batch| batch.timestamp >= cutoff)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn batch(timestamp: u64) -> AdsbBatchEvent {
+        AdsbBatchEvent {
+            messages: Vec::new(),
+            aircrafts: Vec::new(),
+            colors: HashMap::new(),
+            trails: HashMap::new(),
+            integrity: HashMap::new(),
+            predicted: HashMap::new(),
+            external_tracks: HashMap::new(),
+            tick: 0,
+            timestamp,
+            replay_offset_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_recent_window() {
+        let mut recent = RecentEvents::new(5, 100);
+        assert!(recent.recent(10.0).is_empty());
+        for tick in 0..10 {
+            recent.push(batch(tick * 1000));
+        }
+
+        let timestamps = |batches: Vec<AdsbBatchEvent>| -> Vec<u64> {
+            batches.iter().map(|b| b.timestamp).collect()
+        };
+        assert_eq!(timestamps(recent.recent(3.0)), vec![6000, 7000, 8000, 9000]);
+        // 超出保留窗口的批次已被淘汰
+        assert_eq!(timestamps(recent.recent(60.0)).first(), Some(&4000));
+
+        // 数量上限优先于时间窗口
+        let mut capped = RecentEvents::new(60, 3);
+        for tick in 0..10 {
+            capped.push(batch(tick * 1000));
+        }
+        assert_eq!(timestamps(capped.recent(60.0)), vec![7000, 8000, 9000]);
+    }
+}
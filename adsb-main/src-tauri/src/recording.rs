@@ -0,0 +1,682 @@
+use crate::adsb::{unix_time_ms, AdsbEvent, MessageType, TrackPoint};
+use crate::decoder::{self, DecodeError, MessageBody};
+use crate::smoothing::{TrackSmoother, TrackSmoothing};
+use crate::{export, AdsbBatchEvent};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+/// 录制时刷新到磁盘的间隔，异常退出时最多丢失这段时间内的消息
+const RECORDER_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 回放航迹做 CPR 全局解码时，偶帧与奇帧的最大录制时间间隔 (ms)
+const CPR_PAIR_TIMEOUT_MS: u64 = 10_000;
+
+/// 录制文件中的一行消息
+///
+/// 格式为带序号和时间戳的 AVR 文本：`<序号> <Unix ms> *<十六进制消息>;`，
+/// 也接受只有 `*<十六进制消息>;` 的标准 AVR 行（此时没有序号和时间戳）。
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedLine {
+    pub seq: Option<u64>,
+    pub time_ms: Option<u64>,
+    pub hex: String,
+}
+
+/// 解析一行录制数据
+pub fn parse_line(line: &str) -> Result<RecordedLine, String> {
+    let line = line.trim();
+    let (prefix, frame) = match line.find('*') {
+        Some(index) => line.split_at(index),
+        None => return Err("missing '*' frame marker".to_string()),
+    };
+    let hex = frame
+        .strip_prefix('*')
+        .and_then(|rest| rest.strip_suffix(';'))
+        .ok_or_else(|| "frame must be written as *<hex>;".to_string())?;
+
+    let fields: Vec<&str> = prefix.split_whitespace().collect();
+    let (seq, time_ms) = match fields.as_slice() {
+        [] => (None, None),
+        [seq, time] => (
+            Some(seq.parse().map_err(|_| format!("invalid sequence number '{}'", seq))?),
+            Some(time.parse().map_err(|_| format!("invalid timestamp '{}'", time))?),
+        ),
+        _ => return Err("expected '<seq> <time_ms> *<hex>;'".to_string()),
+    };
+
+    Ok(RecordedLine {
+        seq,
+        time_ms,
+        hex: hex.to_string(),
+    })
+}
+
+/// 损坏的行
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorruptLine {
+    pub line: usize, // 行号 (从 1 开始)
+    pub reason: String,
+}
+
+/// 序号不连续的位置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SequenceGap {
+    pub line: usize,
+    pub expected: u64,
+    pub found: u64,
+}
+
+/// 录制文件完整性报告
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordingReport {
+    pub total_lines: usize, // 非空行数
+    pub valid_messages: usize,
+    pub corrupt_lines: Vec<CorruptLine>,
+    pub gaps: Vec<SequenceGap>,
+    pub type_counts: BTreeMap<MessageType, usize>, // 有效消息按类型计数
+}
+
+/// 检查录制内容：格式、CRC、序号连续性，并统计各类型消息数量
+pub fn validate_recording_text(text: &str) -> RecordingReport {
+    let mut report = RecordingReport {
+        total_lines: 0,
+        valid_messages: 0,
+        corrupt_lines: Vec::new(),
+        gaps: Vec::new(),
+        type_counts: BTreeMap::new(),
+    };
+    let mut expected_seq: Option<u64> = None;
+
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        report.total_lines += 1;
+
+        let recorded = match parse_line(line) {
+            Ok(recorded) => recorded,
+            Err(reason) => {
+                report.corrupt_lines.push(CorruptLine { line: number, reason });
+                continue;
+            }
+        };
+
+        // 序号连续性与消息内容分别检查，损坏的消息仍占用序号
+        if let Some(seq) = recorded.seq {
+            if let Some(expected) = expected_seq.filter(|&expected| expected != seq) {
+                report.gaps.push(SequenceGap {
+                    line: number,
+                    expected,
+                    found: seq,
+                });
+            }
+            expected_seq = Some(seq + 1);
+        }
+
+        let checked = decoder::crc_syndrome(&recorded.hex).and_then(|syndrome| {
+            if syndrome != 0 {
+                return Err(DecodeError::CrcMismatch(syndrome));
+            }
+            decoder::decode_message(&recorded.hex)
+        });
+        match checked {
+            Ok(message) => {
+                report.valid_messages += 1;
+                let message_type = MessageType::from_downlink_format(message.df, message.type_code);
+                *report.type_counts.entry(message_type).or_insert(0) += 1;
+            }
+            Err(e) => report.corrupt_lines.push(CorruptLine {
+                line: number,
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    report
+}
+
+/// 读取并检查录制文件
+pub fn validate_recording(path: &str) -> Result<RecordingReport, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    Ok(validate_recording_text(&text))
+}
+
+/// 消息流录制器：按 `<序号> <Unix ms> *<hex>;` 格式写入，生成的文件可直接校验与回放
+pub struct Recorder {
+    writer: BufWriter<File>,
+    seq: u64, // 下一条消息的序号，即已写入的消息数
+    last_flush: Instant,
+}
+
+impl Recorder {
+    /// 创建录制文件，已存在时覆盖
+    pub fn create(path: &str) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+        Ok(Recorder {
+            writer: BufWriter::new(file),
+            seq: 0,
+            last_flush: Instant::now(),
+        })
+    }
+
+    /// 追加一批消息，距上次刷新超过 RECORDER_FLUSH_INTERVAL 时写入磁盘
+    pub fn record(&mut self, time_ms: u64, messages: &[AdsbEvent]) -> Result<(), String> {
+        for message in messages {
+            writeln!(
+                self.writer,
+                "{} {} *{};",
+                self.seq, time_ms, message.hex_message
+            )
+            .map_err(|e| e.to_string())?;
+            self.seq += 1;
+        }
+        if self.last_flush.elapsed() >= RECORDER_FLUSH_INTERVAL {
+            self.writer.flush().map_err(|e| e.to_string())?;
+            self.last_flush = Instant::now();
+        }
+        Ok(())
+    }
+
+    /// 写入剩余数据并关闭文件，返回录制的消息数
+    pub fn finish(mut self) -> Result<u64, String> {
+        self.writer.flush().map_err(|e| e.to_string())?;
+        Ok(self.seq)
+    }
+}
+
+/// 独立回放录制文件的统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplaySummary {
+    pub messages: usize, // 可回放的消息数
+    pub batches: usize,  // 按录制时间分组后的批次数
+    pub skipped: usize,  // 格式错误或校验失败而跳过的行数
+}
+
+/// 文件回放中同一录制时刻的一批消息
+#[derive(Debug, Clone)]
+pub struct ReplayBatch {
+    pub offset_ms: u64,                      // 相对录制首帧的时间 (ms)
+    pub messages: Vec<AdsbEvent>,            // 该时刻的录制消息
+    pub tracks: HashMap<String, TrackPoint>, // 本批次更新的航迹平滑位置，按 ICAO 索引
+}
+
+/// 由回放批次构造批次事件（没有实时飞机，只附带各 ICAO 的显示颜色与平滑航迹）
+///
+/// 与实时批次一样以发送时刻的系统时间为时间戳，录制时间偏移 (ms) 单独放在
+/// `replay_offset_ms` 中；没有模拟 tick。
+pub fn replay_batch(batch: ReplayBatch) -> AdsbBatchEvent {
+    let colors = batch
+        .messages
+        .iter()
+        .map(|m| (m.aircraft_id.clone(), export::icao_color(&m.aircraft_id)))
+        .collect();
+    AdsbBatchEvent {
+        messages: batch.messages,
+        aircrafts: Vec::new(),
+        colors,
+        trails: HashMap::new(),
+        integrity: HashMap::new(),
+        predicted: HashMap::new(),
+        external_tracks: batch.tracks,
+        tick: 0,
+        timestamp: unix_time_ms(),
+        replay_offset_ms: Some(batch.offset_ms),
+    }
+}
+
+/// 回放航迹最近一帧空中位置消息
+#[derive(Debug, Clone, Copy)]
+struct CprFrame {
+    offset_ms: u64, // 录制时间偏移
+    lat_cpr: u32,
+    lng_cpr: u32,
+}
+
+/// 录制回放：按录制时间戳逐批放出消息，可叠加在实时模拟的批次上
+///
+/// 只回放通过 CRC 校验的行；没有时间戳的行沿用上一行的时间。空中位置消息
+/// 按 ICAO 凑齐 CPR 偶帧/奇帧后解码，再经 `TrackSmoother` 平滑后随批次给出。
+pub struct Replay {
+    frames: Vec<(u64, AdsbEvent)>, // (相对首帧的时间 ms, 消息)，按时间排序
+    skipped: usize,                // 格式错误或校验失败而跳过的行数
+    next: usize,
+    elapsed_ms: u64,
+    reported: BTreeSet<String>, // 已报告过与实时飞机冲突的 ICAO
+    cpr: BTreeMap<String, [Option<CprFrame>; 2]>, // 各 ICAO 最近的 [偶帧, 奇帧]
+    smoother: TrackSmoother,
+}
+
+impl Replay {
+    /// 从录制内容创建回放，没有可回放的消息时返回错误
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let mut frames = Vec::new();
+        let mut skipped = 0;
+        let (mut start, mut last) = (None, 0u64);
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let Ok(recorded) = parse_line(line) else {
+                skipped += 1;
+                continue;
+            };
+            if decoder::crc_syndrome(&recorded.hex) != Ok(0) {
+                skipped += 1;
+                continue;
+            }
+            let Ok(message) = decoder::decode_message(&recorded.hex) else {
+                skipped += 1;
+                continue;
+            };
+            if let Some(time) = recorded.time_ms {
+                last = time;
+            }
+            let start = *start.get_or_insert(last);
+            frames.push((
+                last.saturating_sub(start),
+                AdsbEvent {
+                    hex_message: recorded.hex,
+                    aircraft_id: message.icao,
+                    message_type: MessageType::from_downlink_format(message.df, message.type_code),
+                    rssi: None,
+                    spoofed: false,
+                },
+            ));
+        }
+        if frames.is_empty() {
+            return Err("Recording contains no replayable messages".to_string());
+        }
+        frames.sort_by_key(|&(offset, _)| offset);
+        Ok(Replay {
+            frames,
+            skipped,
+            next: 0,
+            elapsed_ms: 0,
+            reported: BTreeSet::new(),
+            cpr: BTreeMap::new(),
+            smoother: TrackSmoother::default(),
+        })
+    }
+
+    /// 设置回放航迹的平滑方式，参数无效时返回错误
+    pub fn set_smoothing(&mut self, mode: TrackSmoothing) -> Result<(), String> {
+        self.smoother = TrackSmoother::new(mode)?;
+        Ok(())
+    }
+
+    /// 读取录制文件并创建回放
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text =
+            fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::from_text(&text)
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.frames.len()
+    }
+
+    /// 按录制时间分组，各批次附带该时刻更新的平滑航迹位置
+    pub fn into_batches(mut self) -> Vec<ReplayBatch> {
+        let mut batches: Vec<ReplayBatch> = Vec::new();
+        for (offset, event) in std::mem::take(&mut self.frames) {
+            let track = self.observe(offset, &event);
+            let batch = match batches.last_mut() {
+                Some(batch) if batch.offset_ms == offset => batch,
+                _ => {
+                    batches.push(ReplayBatch {
+                        offset_ms: offset,
+                        messages: Vec::new(),
+                        tracks: HashMap::new(),
+                    });
+                    batches.last_mut().unwrap()
+                }
+            };
+            if let Some(point) = track {
+                batch.tracks.insert(event.aircraft_id.clone(), point);
+            }
+            batch.messages.push(event);
+        }
+        batches
+    }
+
+    /// 回放时钟前进 `dt_ms`，返回这段时间内的录制消息在 `frames` 中的下标范围
+    fn advance(&mut self, dt_ms: u64) -> Range<usize> {
+        self.elapsed_ms = self.elapsed_ms.saturating_add(dt_ms);
+        let start = self.next;
+        self.next += self.frames[start..].partition_point(|&(offset, _)| offset < self.elapsed_ms);
+        start..self.next
+    }
+
+    /// 用一条录制消息更新航迹，空中位置消息凑齐 CPR 偶帧/奇帧后返回平滑位置
+    ///
+    /// 两帧录制时间相差超过 CPR_PAIR_TIMEOUT_MS 或跨越经度分区时无法解码；
+    /// 高度无效的位置消息不参与航迹。
+    fn observe(&mut self, offset_ms: u64, event: &AdsbEvent) -> Option<TrackPoint> {
+        let message = decoder::decode_message(&event.hex_message).ok()?;
+        let MessageBody::Position {
+            altitude: Some(altitude),
+            odd,
+            lat_cpr,
+            lng_cpr,
+        } = message.body
+        else {
+            return None;
+        };
+        let pair = self.cpr.entry(message.icao.clone()).or_default();
+        pair[odd as usize] = Some(CprFrame {
+            offset_ms,
+            lat_cpr,
+            lng_cpr,
+        });
+        let [Some(even), Some(odd_frame)] = *pair else {
+            return None;
+        };
+        if even.offset_ms.abs_diff(odd_frame.offset_ms) > CPR_PAIR_TIMEOUT_MS {
+            return None;
+        }
+        let (lat, lng) = decoder::cpr_decode_airborne(
+            (even.lat_cpr, even.lng_cpr),
+            (odd_frame.lat_cpr, odd_frame.lng_cpr),
+            odd,
+        )?;
+        let time = offset_ms as f64 / 1000.0;
+        let (lat, lng) = self.smoother.smooth(&message.icao, time, lat, lng);
+        Some(TrackPoint {
+            time,
+            lat,
+            lng,
+            altitude,
+        })
+    }
+
+    /// 回放统计：可回放的消息数、批次数和跳过的行数
+    pub fn summary(&self) -> ReplaySummary {
+        let mut offsets: Vec<u64> = self.frames.iter().map(|&(offset, _)| offset).collect();
+        offsets.dedup();
+        ReplaySummary {
+            messages: self.frames.len(),
+            batches: offsets.len(),
+            skipped: self.skipped,
+        }
+    }
+
+    /// 将 `dt_ms` 内的录制消息并入实时批次，返回新发现的 ICAO 冲突
+    ///
+    /// 录制飞机与实时飞机 ICAO 相同时仍照常合并，每个 ICAO 只报告一次；
+    /// 平滑航迹只对非实时飞机给出，放在批次的 `external_tracks` 中。
+    pub fn merge_into(&mut self, batch: &mut AdsbBatchEvent, dt_ms: u64) -> Vec<String> {
+        let mut collisions = Vec::new();
+        for index in self.advance(dt_ms) {
+            let (offset, event) = self.frames[index].clone();
+            let track = self.observe(offset, &event);
+            let icao = event.aircraft_id.to_uppercase();
+            let live = batch
+                .aircrafts
+                .iter()
+                .any(|a| a.id.eq_ignore_ascii_case(&icao));
+            if live && self.reported.insert(icao.clone()) {
+                collisions.push(icao.clone());
+            }
+            if !live {
+                batch
+                    .colors
+                    .entry(icao.clone())
+                    .or_insert_with(|| export::icao_color(&icao));
+                if let Some(point) = track {
+                    batch.external_tracks.insert(icao.clone(), point);
+                }
+            }
+            batch.messages.push(event);
+        }
+        collisions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_corrupted_recording_flagged() {
+        let position = "8D40621D58C382D690C8AC2863A7";
+        let velocity = "8D485020994409940838175B284F";
+        let mut flipped = position.to_string();
+        flipped.replace_range(10..11, "D");
+
+        let text = [
+            format!("0 1700000000000 *{};", position),
+            format!("1 1700000000500 *{};", velocity),
+            format!("2 1700000001000 *{};", flipped), // 第 3 行：CRC 错误
+            "3 1700000001500 *8D40621D58C3".to_string(), // 第 4 行：缺少结束符
+            String::new(),
+            format!("6 1700000002000 *{};", position), // 第 6 行：跳号
+            format!("*{};", velocity),
+        ]
+        .join("\n");
+
+        let report = validate_recording_text(&text);
+        assert_eq!(report.total_lines, 6);
+        assert_eq!(report.valid_messages, 4);
+        let corrupt: Vec<usize> = report.corrupt_lines.iter().map(|c| c.line).collect();
+        assert_eq!(corrupt, vec![3, 4]);
+        assert!(report.corrupt_lines[0].reason.contains("CRC"));
+        assert_eq!(
+            report.gaps,
+            vec![SequenceGap {
+                line: 6,
+                expected: 3, // 第 4 行无法解析，不计入序号
+                found: 6,
+            }]
+        );
+        assert_eq!(report.type_counts[&MessageType::Position], 2);
+        assert_eq!(report.type_counts[&MessageType::Velocity], 2);
+
+        let clean = validate_recording_text(&format!("0 1 *{};\n1 2 *{};", position, velocity));
+        assert!(clean.corrupt_lines.is_empty() && clean.gaps.is_empty());
+    }
+
+    #[test]
+    fn test_all_call_replies_typed_by_downlink_format() {
+        use crate::adsb::AdsbSimulator;
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 66);
+        sim.generate_mock_aircrafts(1).unwrap();
+        let all_call = AdsbSimulator::generate_all_call_reply(&sim.get_aircrafts()[0]);
+        let position = "8D40621D58C382D690C8AC2863A7";
+        let text = format!("0 1 *{};\n1 2 *{};", all_call, position);
+
+        // DF11 的类型码字段为 0，不能按扩展电文类型码归类
+        let report = validate_recording_text(&text);
+        assert_eq!(report.valid_messages, 2);
+        assert_eq!(report.type_counts[&MessageType::AllCall], 1);
+        assert_eq!(report.type_counts[&MessageType::Position], 1);
+        assert!(!report.type_counts.contains_key(&MessageType::Other));
+
+        let replay = Replay::from_text(&text).unwrap();
+        let types: Vec<MessageType> = replay.frames.iter().map(|(_, e)| e.message_type).collect();
+        assert_eq!(types, vec![MessageType::AllCall, MessageType::Position]);
+    }
+
+    #[test]
+    fn test_recorder_line_per_message() {
+        use crate::adsb::AdsbSimulator;
+
+        let path = std::env::temp_dir().join(format!("adsb_record_{}.avr", std::process::id()));
+        let path = path.to_str().unwrap();
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 66);
+        sim.generate_mock_aircrafts(4).unwrap();
+
+        let mut recorder = Recorder::create(path).unwrap();
+        let mut emitted = 0;
+        for tick in 0..5 {
+            sim.update_positions();
+            let messages = sim.generate_all_messages();
+            emitted += messages.len();
+            recorder
+                .record(1_700_000_000_000 + tick * 1000, &messages)
+                .unwrap();
+        }
+        assert_eq!(recorder.finish().unwrap(), emitted as u64);
+
+        let text = fs::read_to_string(path).unwrap();
+        assert_eq!(text.lines().count(), emitted);
+        let report = validate_recording_text(&text);
+        assert_eq!(report.valid_messages, emitted);
+        assert!(report.corrupt_lines.is_empty() && report.gaps.is_empty());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_replay_file_batches_valid_lines() {
+        let position = "8D40621D58C382D690C8AC2863A7";
+        let velocity = "8D485020994409940838175B284F";
+        let text = [
+            format!("0 1700000000000 *{};", position),
+            format!("1 1700000000000 *{};", velocity),
+            "garbage".to_string(),
+            format!("2 1700000001000 *{};", position),
+            "3 1700000001000 *8D40621D58C3".to_string(),
+            format!("4 1700000002000 *{};", velocity),
+        ]
+        .join("\n");
+
+        let replay = Replay::from_text(&text).unwrap();
+        let summary = replay.summary();
+        assert_eq!(
+            summary,
+            ReplaySummary {
+                messages: 4,
+                batches: 3,
+                skipped: 2,
+            }
+        );
+
+        let events: Vec<AdsbBatchEvent> = replay
+            .into_batches()
+            .into_iter()
+            .map(replay_batch)
+            .collect();
+        let offsets: Vec<Option<u64>> = events.iter().map(|e| e.replay_offset_ms).collect();
+        assert_eq!(offsets, vec![Some(0), Some(1000), Some(2000)]);
+        // 时间戳为构造批次时的系统时间
+        assert!(events.iter().all(|e| e.timestamp > 1_600_000_000_000));
+        let emitted: usize = events.iter().map(|e| e.messages.len()).sum();
+        assert_eq!(emitted, summary.messages);
+        assert!(events[0].colors.contains_key("485020"));
+    }
+
+    #[test]
+    fn test_replay_merged_with_live() {
+        use crate::adsb::AdsbSimulator;
+        use crate::build_batch;
+
+        let position = "8D40621D58C382D690C8AC2863A7"; // 40621D
+        let velocity = "8D485020994409940838175B284F"; // 485020
+        let text = [
+            format!("0 1700000000000 *{};", position),
+            format!("1 1700000000400 *{};", velocity),
+            "2 1700000000800 *8D40621D58C3".to_string(), // 损坏行被跳过
+            format!("3 1700000001500 *{};", position),
+        ]
+        .join("\n");
+        let mut replay = Replay::from_text(&text).unwrap();
+        assert_eq!(replay.len(), 3);
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 47);
+        sim.generate_mock_aircrafts(2).unwrap();
+        let mut clash = sim.get_aircrafts()[0].clone();
+        clash.id = "485020".to_string();
+        sim.add_aircraft(clash).unwrap();
+
+        let mut batch = build_batch(&mut sim, 1, 0, 0);
+        let live: Vec<String> = batch.aircrafts.iter().map(|a| a.id.clone()).collect();
+        let collisions = replay.merge_into(&mut batch, 1000);
+        assert_eq!(collisions, vec!["485020".to_string()]);
+
+        let ids: BTreeSet<&str> = batch
+            .messages
+            .iter()
+            .map(|m| m.aircraft_id.as_str())
+            .collect();
+        assert!(ids.contains("40621D"));
+        assert!(live.iter().all(|id| ids.contains(id.as_str())));
+        assert!(batch.colors.contains_key("40621D"));
+        assert!(!replay.is_finished());
+
+        // 第二批只放出 1.5 s 处的帧，冲突不重复报告
+        let mut batch = build_batch(&mut sim, 1, 1, 0);
+        assert!(replay.merge_into(&mut batch, 1000).is_empty());
+        let replayed = batch.messages.iter().filter(|m| m.hex_message == position);
+        assert_eq!(replayed.count(), 1);
+        assert!(replay.is_finished());
+
+        assert!(Replay::from_text("garbage").is_err());
+    }
+
+    #[test]
+    fn test_replay_tracks_smoothed() {
+        use crate::adsb::{AdsbSimulator, SimRng};
+
+        // 匀速直线航迹，位置叠加约 ±0.002° 的噪声后编码为交替的偶帧/奇帧
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 58);
+        sim.generate_mock_aircrafts(1).unwrap();
+        let mut aircraft = sim.get_aircrafts()[0].clone();
+        let icao = aircraft.id.clone();
+        let truth = |step: u64| (22.5 + step as f64 * 1e-3, 114.0 + step as f64 * 5e-4);
+        let mut rng = SimRng::new(58);
+        let mut lines = Vec::new();
+        for step in 0..120u64 {
+            let (lat, lng) = truth(step);
+            aircraft.lat = lat + (rng.next_f64() - 0.5) * 0.004;
+            aircraft.lng = lng + (rng.next_f64() - 0.5) * 0.004;
+            let hex = AdsbSimulator::generate_position_message(&aircraft, step % 2 == 1);
+            let time_ms = 1_700_000_000_000 + step * 1000;
+            lines.push(format!("{} {} *{};", step, time_ms, hex));
+        }
+        let text = lines.join("\n");
+
+        let track_error = |mode: TrackSmoothing| {
+            let mut replay = Replay::from_text(&text).unwrap();
+            replay.set_smoothing(mode).unwrap();
+            let batches = replay.into_batches();
+            // 首帧凑不齐偶帧/奇帧，不给出航迹
+            assert!(batches[0].tracks.is_empty());
+            batches
+                .iter()
+                .skip(20)
+                .map(|batch| {
+                    let point = batch.tracks[&icao];
+                    let (lat, lng) = truth(batch.offset_ms / 1000);
+                    (point.lat - lat).powi(2) + (point.lng - lng).powi(2)
+                })
+                .sum::<f64>()
+        };
+        let raw = track_error(TrackSmoothing::None);
+        let smoothed = track_error(TrackSmoothing::AlphaBeta {
+            alpha: 0.3,
+            beta: 0.05,
+        });
+        assert!(smoothed < raw * 0.6, "{} vs {}", smoothed, raw);
+
+        // 叠加回放同样在批次中给出平滑航迹
+        let mut overlay = Replay::from_text(&text).unwrap();
+        let invalid = TrackSmoothing::AlphaBeta {
+            alpha: 1.5,
+            beta: 0.1,
+        };
+        assert!(overlay.set_smoothing(invalid).is_err());
+        let mut batch = replay_batch(ReplayBatch {
+            offset_ms: 0,
+            messages: Vec::new(),
+            tracks: HashMap::new(),
+        });
+        overlay.merge_into(&mut batch, 2000);
+        assert_eq!(batch.external_tracks[&icao].time, 1.0);
+    }
+}
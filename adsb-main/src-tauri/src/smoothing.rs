@@ -0,0 +1,216 @@
+//! 外部航迹平滑：对真实数据源（hex/SBS）中带噪声的位置做 α-β 或卡尔曼滤波
+//!
+//! 模拟飞机的位置本身是连续的，不经过平滑；只有外部接入的航迹按 ICAO 各自滤波。
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 平滑方式
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TrackSmoothing {
+    /// 不平滑，直接使用原始位置
+    #[default]
+    None,
+    /// α-β 滤波：`alpha` 为位置修正增益，`beta` 为速度修正增益 (0-1)
+    AlphaBeta { alpha: f64, beta: f64 },
+    /// 匀速模型卡尔曼滤波：`process_noise` 为加速度噪声谱密度 (deg²/s³)，
+    /// `measurement_noise` 为位置测量方差 (deg²)
+    Kalman {
+        process_noise: f64,
+        measurement_noise: f64,
+    },
+}
+
+impl TrackSmoothing {
+    /// 校验滤波参数
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            TrackSmoothing::None => Ok(()),
+            TrackSmoothing::AlphaBeta { alpha, beta } => {
+                if !((0.0..=1.0).contains(&alpha) && (0.0..=1.0).contains(&beta)) {
+                    return Err(format!(
+                        "Alpha-beta gains {} / {} must be within 0-1",
+                        alpha, beta
+                    ));
+                }
+                Ok(())
+            }
+            TrackSmoothing::Kalman {
+                process_noise,
+                measurement_noise,
+            } => {
+                let valid = process_noise.is_finite()
+                    && process_noise >= 0.0
+                    && measurement_noise.is_finite()
+                    && measurement_noise > 0.0;
+                if !valid {
+                    return Err("Kalman noise parameters must be positive".to_string());
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// 单个坐标轴的匀速滤波状态
+#[derive(Debug, Clone, Copy)]
+struct AxisState {
+    position: f64,
+    velocity: f64,             // 每秒变化量
+    covariance: [[f64; 2]; 2], // 仅卡尔曼滤波使用
+}
+
+impl AxisState {
+    fn new(position: f64, measurement_noise: f64) -> Self {
+        AxisState {
+            position,
+            velocity: 0.0,
+            // 速度未知，初始方差取较大值
+            covariance: [[measurement_noise, 0.0], [0.0, 1.0]],
+        }
+    }
+
+    fn update(&mut self, mode: TrackSmoothing, measured: f64, dt: f64) {
+        match mode {
+            TrackSmoothing::None => self.position = measured,
+            TrackSmoothing::AlphaBeta { alpha, beta } => {
+                let predicted = self.position + self.velocity * dt;
+                let residual = measured - predicted;
+                self.position = predicted + alpha * residual;
+                self.velocity += beta * residual / dt;
+            }
+            TrackSmoothing::Kalman {
+                process_noise: q,
+                measurement_noise: r,
+            } => {
+                // 预测：x = F x，P = F P Fᵀ + Q
+                let [[p00, p01], [p10, p11]] = self.covariance;
+                let predicted = self.position + self.velocity * dt;
+                let p00 = p00 + dt * (p10 + p01) + dt * dt * p11 + q * dt.powi(3) / 3.0;
+                let p01 = p01 + dt * p11 + q * dt * dt / 2.0;
+                let p10 = p10 + dt * p11 + q * dt * dt / 2.0;
+                let p11 = p11 + q * dt;
+
+                // 更新：K = P Hᵀ / (H P Hᵀ + R)，H = [1, 0]
+                let s = p00 + r;
+                let (k0, k1) = (p00 / s, p10 / s);
+                let residual = measured - predicted;
+                self.position = predicted + k0 * residual;
+                self.velocity += k1 * residual;
+                self.covariance = [
+                    [(1.0 - k0) * p00, (1.0 - k0) * p01],
+                    [p10 - k1 * p00, p11 - k1 * p01],
+                ];
+            }
+        }
+    }
+}
+
+/// 单条航迹的滤波状态
+#[derive(Debug, Clone, Copy)]
+struct TrackState {
+    time: f64,
+    lat: AxisState,
+    lng: AxisState,
+}
+
+/// 按 ICAO 维护各外部航迹的滤波状态
+#[derive(Debug, Default)]
+pub struct TrackSmoother {
+    mode: TrackSmoothing,
+    tracks: BTreeMap<String, TrackState>,
+}
+
+impl TrackSmoother {
+    pub fn new(mode: TrackSmoothing) -> Result<Self, String> {
+        mode.validate()?;
+        Ok(TrackSmoother {
+            mode,
+            tracks: BTreeMap::new(),
+        })
+    }
+
+    /// 输入一个外部位置观测 (时间 s)，返回平滑后的 (纬度, 经度)
+    ///
+    /// 航迹的第一个点原样返回；时间不前进的观测视为重复数据，返回当前估计。
+    pub fn smooth(&mut self, icao: &str, time: f64, lat: f64, lng: f64) -> (f64, f64) {
+        let initial_noise = match self.mode {
+            TrackSmoothing::Kalman {
+                measurement_noise, ..
+            } => measurement_noise,
+            _ => 0.0,
+        };
+        let Some(track) = self.tracks.get_mut(icao) else {
+            self.tracks.insert(
+                icao.to_string(),
+                TrackState {
+                    time,
+                    lat: AxisState::new(lat, initial_noise),
+                    lng: AxisState::new(lng, initial_noise),
+                },
+            );
+            return (lat, lng);
+        };
+
+        let dt = time - track.time;
+        if dt > 0.0 {
+            track.time = time;
+            track.lat.update(self.mode, lat, dt);
+            track.lng.update(self.mode, lng, dt);
+        }
+        (track.lat.position, track.lng.position)
+    }
+
+    /// 丢弃某条航迹的滤波状态（目标消失后再出现时重新初始化）
+    pub fn forget(&mut self, icao: &str) {
+        self.tracks.remove(icao);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adsb::SimRng;
+
+    #[test]
+    fn test_smoothing_reduces_variance() {
+        let error_variance = |mode: TrackSmoothing| {
+            let mut smoother = TrackSmoother::new(mode).unwrap();
+            let mut rng = SimRng::new(56);
+            let (mut raw, mut smoothed) = (0.0, 0.0);
+            for step in 0..300 {
+                let time = step as f64;
+                // 匀速直线航迹，叠加约 ±0.002° 的均匀噪声
+                let (lat, lng) = (22.5 + time * 1e-3, 114.0 + time * 5e-4);
+                let noisy_lat = lat + (rng.next_f64() - 0.5) * 0.004;
+                let noisy_lng = lng + (rng.next_f64() - 0.5) * 0.004;
+                let (out_lat, out_lng) = smoother.smooth("ABC123", time, noisy_lat, noisy_lng);
+                if step >= 50 {
+                    raw += (noisy_lat - lat).powi(2) + (noisy_lng - lng).powi(2);
+                    smoothed += (out_lat - lat).powi(2) + (out_lng - lng).powi(2);
+                }
+            }
+            (raw, smoothed)
+        };
+
+        let (raw, none) = error_variance(TrackSmoothing::None);
+        assert_eq!(raw, none);
+        let (raw, alpha_beta) = error_variance(TrackSmoothing::AlphaBeta {
+            alpha: 0.3,
+            beta: 0.05,
+        });
+        assert!(alpha_beta < raw * 0.6, "{} vs {}", alpha_beta, raw);
+        let (raw, kalman) = error_variance(TrackSmoothing::Kalman {
+            process_noise: 1e-10,
+            measurement_noise: 1.3e-6,
+        });
+        assert!(kalman < raw * 0.6, "{} vs {}", kalman, raw);
+
+        assert!(TrackSmoother::new(TrackSmoothing::AlphaBeta {
+            alpha: 1.5,
+            beta: 0.1
+        })
+        .is_err());
+    }
+}
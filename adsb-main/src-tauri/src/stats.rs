@@ -0,0 +1,295 @@
+use crate::adsb::{AdsbEvent, MessageType};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// 延迟直方图桶上界 (µs)，最后一个桶收纳超出上界的样本
+const LATENCY_BUCKETS_US: [u64; 10] = [
+    100, 500, 1_000, 2_000, 5_000, 10_000, 20_000, 50_000, 100_000, 500_000,
+];
+
+/// 从数据产生到事件发送完成的延迟统计
+///
+/// 所有计数均为原子量，后台线程写入、命令读取互不阻塞。
+#[derive(Debug, Default)]
+pub struct LatencyStats {
+    buckets: [AtomicU64; LATENCY_BUCKETS_US.len() + 1],
+    count: AtomicU64,
+    total_us: AtomicU64,
+    max_us: AtomicU64,
+}
+
+/// 延迟直方图桶
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyBucket {
+    pub le_us: Option<u64>, // 桶上界 (µs)，None 表示无上界
+    pub count: u64,
+}
+
+/// 延迟统计快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencySnapshot {
+    pub samples: u64,
+    pub mean_us: f64,
+    pub max_us: u64,
+    pub buckets: Vec<LatencyBucket>,
+}
+
+impl LatencyStats {
+    /// 记录一次延迟
+    pub fn record(&self, latency: Duration) {
+        let us = latency.as_micros().min(u64::MAX as u128) as u64;
+        let index = LATENCY_BUCKETS_US
+            .iter()
+            .position(|&bound| us <= bound)
+            .unwrap_or(LATENCY_BUCKETS_US.len());
+        self.buckets[index].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.total_us.fetch_add(us, Ordering::Relaxed);
+        self.max_us.fetch_max(us, Ordering::Relaxed);
+    }
+
+    /// 以 `produced` 为起点计时执行 `emit`，并记录其完成时的延迟
+    pub fn time<R>(&self, produced: Instant, emit: impl FnOnce() -> R) -> R {
+        let result = emit();
+        self.record(produced.elapsed());
+        result
+    }
+
+    /// 清零所有计数
+    pub fn reset(&self) {
+        for bucket in &self.buckets {
+            bucket.store(0, Ordering::Relaxed);
+        }
+        self.count.store(0, Ordering::Relaxed);
+        self.total_us.store(0, Ordering::Relaxed);
+        self.max_us.store(0, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> LatencySnapshot {
+        let samples = self.count.load(Ordering::Relaxed);
+        let total = self.total_us.load(Ordering::Relaxed);
+        LatencySnapshot {
+            samples,
+            mean_us: if samples > 0 {
+                total as f64 / samples as f64
+            } else {
+                0.0
+            },
+            max_us: self.max_us.load(Ordering::Relaxed),
+            buckets: self
+                .buckets
+                .iter()
+                .enumerate()
+                .map(|(i, bucket)| LatencyBucket {
+                    le_us: LATENCY_BUCKETS_US.get(i).copied(),
+                    count: bucket.load(Ordering::Relaxed),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 参与吞吐统计的消息类型，顺序对应计数数组下标
+const MESSAGE_TYPES: [MessageType; 9] = [
+    MessageType::Identification,
+    MessageType::SurfacePosition,
+    MessageType::Position,
+    MessageType::Velocity,
+    MessageType::Status,
+    MessageType::TargetState,
+    MessageType::OperationalStatus,
+    MessageType::AllCall,
+    MessageType::Other,
+];
+
+/// 模拟吞吐计数：消息总数、各类型消息数、当前飞机数与已推进的 tick 数
+///
+/// 与延迟统计相同，后台线程以原子量写入，读取时不会阻塞模拟。
+#[derive(Debug, Default)]
+pub struct SimulationCounters {
+    total: AtomicU64,
+    by_type: [AtomicU64; MESSAGE_TYPES.len()],
+    aircraft: AtomicUsize,
+    ticks: AtomicU64,
+}
+
+/// 模拟吞吐统计快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationStatistics {
+    pub total_messages: u64,
+    pub by_type: BTreeMap<MessageType, u64>, // 仅包含已发送过的类型
+    pub aircraft_count: usize,
+    pub ticks: u64, // 本次启动以来推进的模拟步数
+}
+
+impl SimulationCounters {
+    /// 记录一个批次：推进了 `ticks` 步，发送 `messages`，当前共有 `aircraft` 架飞机
+    pub fn record(&self, messages: &[AdsbEvent], aircraft: usize, ticks: u64) {
+        for message in messages {
+            if let Some(index) = MESSAGE_TYPES
+                .iter()
+                .position(|&t| t == message.message_type)
+            {
+                self.by_type[index].fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.total
+            .fetch_add(messages.len() as u64, Ordering::Relaxed);
+        self.aircraft.store(aircraft, Ordering::Relaxed);
+        self.ticks.fetch_add(ticks, Ordering::Relaxed);
+    }
+
+    /// 清零所有计数
+    pub fn reset(&self) {
+        for count in &self.by_type {
+            count.store(0, Ordering::Relaxed);
+        }
+        self.total.store(0, Ordering::Relaxed);
+        self.aircraft.store(0, Ordering::Relaxed);
+        self.ticks.store(0, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> SimulationStatistics {
+        SimulationStatistics {
+            total_messages: self.total.load(Ordering::Relaxed),
+            by_type: MESSAGE_TYPES
+                .iter()
+                .zip(&self.by_type)
+                .map(|(&message_type, count)| (message_type, count.load(Ordering::Relaxed)))
+                .filter(|&(_, count)| count > 0)
+                .collect(),
+            aircraft_count: self.aircraft.load(Ordering::Relaxed),
+            ticks: self.ticks.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 按 (DF, 类型码) 统计的消息数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MessageTypeCount {
+    pub df: u8,
+    pub type_code: u8, // 仅 DF17/18 有类型码，其他 DF 为 0
+    pub count: u64,
+}
+
+/// 会话内已发送消息的 DF/类型码分布，用于核对消息组成与发送周期配置
+///
+/// 直接读取十六进制报文头部，不做完整解码，损伤后的消息按实际发出的内容统计。
+#[derive(Debug, Default)]
+pub struct MessageHistogram {
+    counts: BTreeMap<(u8, u8), u64>,
+}
+
+impl MessageHistogram {
+    pub fn record(&mut self, messages: &[AdsbEvent]) {
+        for message in messages {
+            let header = |range: std::ops::Range<usize>| {
+                message
+                    .hex_message
+                    .get(range)
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            };
+            let Some(df) = header(0..2).map(|byte| byte >> 3) else {
+                continue;
+            };
+            let type_code = match df {
+                17 | 18 => header(8..10).map_or(0, |byte| byte >> 3),
+                _ => 0,
+            };
+            *self.counts.entry((df, type_code)).or_insert(0) += 1;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.counts.clear();
+    }
+
+    /// 按 (DF, 类型码) 升序列出各项计数
+    pub fn entries(&self) -> Vec<MessageTypeCount> {
+        self.counts
+            .iter()
+            .map(|(&(df, type_code), &count)| MessageTypeCount {
+                df,
+                type_code,
+                count,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_buckets() {
+        let stats = LatencyStats::default();
+        stats.record(Duration::from_micros(50));
+        stats.record(Duration::from_millis(3));
+        stats.record(Duration::from_secs(2));
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.samples, 3);
+        assert_eq!(snapshot.max_us, 2_000_000);
+        assert_eq!(snapshot.buckets[0].count, 1);
+        assert_eq!(snapshot.buckets[4].count, 1);
+        assert_eq!(snapshot.buckets.last().unwrap().le_us, None);
+        assert_eq!(snapshot.buckets.last().unwrap().count, 1);
+
+        stats.reset();
+        assert_eq!(stats.snapshot().samples, 0);
+    }
+
+    #[test]
+    fn test_message_histogram_totals() {
+        use crate::adsb::AdsbSimulator;
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 48);
+        sim.generate_mock_aircrafts(4).unwrap();
+        let mut histogram = MessageHistogram::default();
+        let mut total = 0;
+        for _ in 0..20 {
+            sim.update_positions();
+            let messages = sim.generate_all_messages();
+            total += messages.len() as u64;
+            histogram.record(&messages);
+        }
+
+        assert!(total > 0);
+        let entries = histogram.entries();
+        assert_eq!(entries.iter().map(|e| e.count).sum::<u64>(), total);
+        assert!(entries.iter().any(|e| e.df == 17 && e.type_code == 19));
+
+        histogram.reset();
+        assert!(histogram.entries().is_empty());
+    }
+
+    #[test]
+    fn test_simulation_counters_per_type() {
+        use crate::adsb::AdsbSimulator;
+
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 271);
+        sim.generate_mock_aircrafts(5).unwrap();
+        let counters = SimulationCounters::default();
+        const TICKS: u64 = 12;
+        for _ in 0..TICKS {
+            sim.update_positions();
+            let messages = sim.generate_all_messages();
+            counters.record(&messages, sim.get_aircrafts().len(), 1);
+        }
+
+        let statistics = counters.snapshot();
+        assert_eq!(statistics.ticks, TICKS);
+        assert_eq!(statistics.aircraft_count, 5);
+        assert_eq!(statistics.by_type[&MessageType::Position], 5 * TICKS);
+        let total: u64 = statistics.by_type.values().sum();
+        assert_eq!(statistics.total_messages, total);
+
+        counters.reset();
+        let statistics = counters.snapshot();
+        assert_eq!((statistics.total_messages, statistics.ticks), (0, 0));
+        assert!(statistics.by_type.is_empty());
+    }
+}
@@ -0,0 +1,214 @@
+//! 978 MHz UAT 通道：为指定飞机生成 UAT 基本 ADS-B 消息 (DO-282B, Payload Type 0)
+//!
+//! UAT 飞机不再发送 1090ES 消息，其 UAT 报文经独立的事件和输出端口发送。
+//! 报文为去除 FEC 后的 18 字节负载，与 dump978 的原始输出一致。
+
+use crate::adsb::{quantize, quantize_angle, AdsbEvent, Aircraft, MessageType};
+use crate::AdsbBatchEvent;
+use std::collections::BTreeSet;
+
+/// 基本消息长度 (字节)
+pub const BASIC_MESSAGE_BYTES: usize = 18;
+/// 经纬度编码的分辨率 (360° / 2^24)
+const LATLNG_SCALE: f64 = (1u32 << 24) as f64 / 360.0;
+/// 高度编码步长 (ft)
+const ALTITUDE_STEP_FT: f64 = 25.0;
+/// 空地状态：亚音速空中
+const AIRBORNE_SUBSONIC: u32 = 0;
+/// 空地状态：地面
+const ON_GROUND: u32 = 2;
+/// 地面航迹角类型：真航迹
+const TRACK_TYPE_TRUE: u32 = 1;
+
+/// 按位写入 (位号从 1 开始，与 DO-282B 一致)
+fn put(bytes: &mut [u8; BASIC_MESSAGE_BYTES], first_bit: usize, len: usize, value: u32) {
+    for i in 0..len {
+        if (value >> (len - 1 - i)) & 1 == 1 {
+            let bit = first_bit - 1 + i;
+            bytes[bit / 8] |= 0x80 >> (bit % 8);
+        }
+    }
+}
+
+/// 带符号速度分量：1 位符号 + 10 位 (速度 + 1)，0 表示无效
+fn signed_velocity(kts: f64) -> u32 {
+    let magnitude = quantize(kts.abs(), 1.0, 0x3FE) as u32 + 1;
+    ((kts < 0.0) as u32) << 10 | magnitude
+}
+
+/// 编码一架飞机的 UAT 基本消息，返回 36 个十六进制字符
+pub fn encode_basic(aircraft: &Aircraft) -> Result<String, String> {
+    let address = u32::from_str_radix(&aircraft.id, 16)
+        .ok()
+        .filter(|&address| address <= 0xFF_FFFF)
+        .ok_or_else(|| format!("Invalid ICAO address: {}", aircraft.id))?;
+    let mut bytes = [0u8; BASIC_MESSAGE_BYTES];
+
+    // 头部：负载类型 0、地址限定符 0 (ICAO 地址)
+    put(&mut bytes, 9, 24, address);
+
+    // 状态向量
+    // 经纬度为二进制补码角度，负值按 24 位回绕
+    let lat = (aircraft.lat * LATLNG_SCALE).round_ties_even() as i64 as u32 & 0x7F_FFFF;
+    let lng = (aircraft.lng * LATLNG_SCALE).round_ties_even() as i64 as u32 & 0xFF_FFFF;
+    put(&mut bytes, 33, 23, lat);
+    put(&mut bytes, 56, 24, lng);
+    // 第 80 位为 0：气压高度
+    let altitude = quantize(aircraft.altitude + 1000.0, ALTITUDE_STEP_FT, 4094) as u32 + 1;
+    put(&mut bytes, 81, 12, altitude);
+    put(&mut bytes, 93, 4, aircraft.nic.min(15) as u32);
+
+    if aircraft.on_ground {
+        put(&mut bytes, 97, 2, ON_GROUND);
+        put(
+            &mut bytes,
+            101,
+            10,
+            quantize(aircraft.ground_speed, 1.0, 0x3FE) as u32 + 1,
+        );
+        let track = quantize_angle(aircraft.track, 9) as u32;
+        put(&mut bytes, 111, 2, TRACK_TYPE_TRUE);
+        put(&mut bytes, 113, 9, track);
+    } else {
+        put(&mut bytes, 97, 2, AIRBORNE_SUBSONIC);
+        // 速度分量为地速矢量 (含风)；北向为正、南向符号位置 1；东向为正、西向符号位置 1
+        let track = aircraft.track.to_radians();
+        put(
+            &mut bytes,
+            100,
+            11,
+            signed_velocity(aircraft.ground_speed * track.cos()),
+        );
+        put(
+            &mut bytes,
+            111,
+            11,
+            signed_velocity(aircraft.ground_speed * track.sin()),
+        );
+        // 垂直速率：气压来源，符号位 1 表示下降，64 ft/min 分辨率
+        let vertical_rate = quantize(aircraft.vertical_rate.abs(), 64.0, 0x1FE) as u32 + 1;
+        put(&mut bytes, 122, 1, 1);
+        put(&mut bytes, 123, 1, (aircraft.vertical_rate < 0.0) as u32);
+        put(&mut bytes, 124, 9, vertical_rate);
+    }
+
+    Ok(bytes.iter().map(|b| format!("{:02X}", b)).collect())
+}
+
+/// UAT 通道：记录哪些飞机使用 UAT 并生成其消息
+#[derive(Debug, Default)]
+pub struct UatChannel {
+    aircraft: BTreeSet<String>, // 使用 UAT 的 ICAO 地址（大写）
+}
+
+impl UatChannel {
+    pub fn set_aircraft(&mut self, icaos: &[String]) {
+        self.aircraft = icaos
+            .iter()
+            .map(|icao| icao.trim().to_uppercase())
+            .collect();
+    }
+
+    pub fn contains(&self, icao: &str) -> bool {
+        self.aircraft.contains(&icao.to_uppercase())
+    }
+
+    /// 从批次中移除 UAT 飞机的 1090ES 消息，返回这些飞机本批次的 UAT 消息
+    pub fn split(&self, batch: &mut AdsbBatchEvent) -> Vec<AdsbEvent> {
+        if self.aircraft.is_empty() {
+            return Vec::new();
+        }
+        batch
+            .messages
+            .retain(|message| !self.contains(&message.aircraft_id));
+        batch
+            .aircrafts
+            .iter()
+            .filter(|aircraft| self.contains(&aircraft.id))
+            .filter_map(|aircraft| {
+                let hex_message = encode_basic(aircraft).ok()?;
+                Some(AdsbEvent {
+                    hex_message,
+                    aircraft_id: aircraft.id.clone(),
+                    message_type: MessageType::Position,
+                    rssi: None,
+                    spoofed: false,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adsb::AdsbSimulator;
+    use crate::build_batch;
+
+    #[test]
+    fn test_uat_aircraft_use_uat_format() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 49);
+        sim.generate_mock_aircrafts(4).unwrap();
+        let uat_id = sim.get_aircrafts()[1].id.clone();
+        let mut channel = UatChannel::default();
+        channel.set_aircraft(&[uat_id.to_lowercase()]);
+
+        let mut batch = build_batch(&mut sim, 1, 0, 0);
+        let uat = channel.split(&mut batch);
+        assert_eq!(uat.len(), 1);
+        assert_eq!(uat[0].aircraft_id, uat_id);
+        let hex = &uat[0].hex_message;
+        assert_eq!(hex.len(), BASIC_MESSAGE_BYTES * 2);
+        assert_eq!(&hex[..2], "00"); // 负载类型 0、ICAO 地址
+        assert_eq!(&hex[2..8], uat_id);
+
+        // 1090ES 消息 (28 个十六进制字符，DF17/18) 中不再有 UAT 飞机
+        assert!(!batch.messages.is_empty());
+        assert!(batch.messages.iter().all(|m| m.aircraft_id != uat_id));
+        assert!(batch
+            .messages
+            .iter()
+            .all(|m| m.hex_message.len() != hex.len()));
+
+        // 纬度按 360/2^24 解码回原值
+        let aircraft = batch.aircrafts.iter().find(|a| a.id == uat_id).unwrap();
+        let raw = u32::from_str_radix(&hex[8..14], 16).unwrap() >> 1;
+        assert!((raw as f64 / LATLNG_SCALE - aircraft.lat).abs() < 1e-4);
+    }
+
+    /// 按位读取 (位号从 1 开始)
+    fn get(hex: &str, first_bit: usize, len: usize) -> u32 {
+        let bytes: Vec<u8> = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect();
+        (0..len).fold(0, |value, i| {
+            let bit = first_bit - 1 + i;
+            value << 1 | ((bytes[bit / 8] >> (7 - bit % 8)) & 1) as u32
+        })
+    }
+
+    #[test]
+    fn test_uat_velocity_uses_ground_track() {
+        let mut sim = AdsbSimulator::with_seed(22.5431, 114.0579, 49);
+        sim.generate_mock_aircrafts(1).unwrap();
+        // 侧风下航迹角、地速与航向、空速不同
+        let mut aircraft = sim.get_aircrafts()[0].clone();
+        aircraft.on_ground = false;
+        aircraft.heading = 90.0;
+        aircraft.speed = 300.0;
+        aircraft.track = 180.0;
+        aircraft.ground_speed = 250.0;
+
+        let hex = encode_basic(&aircraft).unwrap();
+        // 南向 250 kts：北/南分量符号位 1；东/西分量为 0
+        assert_eq!(get(&hex, 100, 11), 1 << 10 | 251);
+        assert_eq!(get(&hex, 111, 11) & 0x3FF, 1);
+
+        aircraft.on_ground = true;
+        aircraft.ground_speed = 12.0;
+        let hex = encode_basic(&aircraft).unwrap();
+        assert_eq!(get(&hex, 101, 10), 13);
+        assert_eq!(get(&hex, 113, 9), quantize_angle(180.0, 9) as u32);
+    }
+}
@@ -0,0 +1,256 @@
+//! 已知正确的 ADS-B 测试向量：取自公开资料中的真实报文，附带文档给出的解码结果
+//!
+//! 用于对照外部参考校验解码器，而不仅是与本模拟器的编码器自洽。
+//! 报文与期望值出自 Junzi Sun《The 1090 Megahertz Riddle》中的示例。
+
+use crate::decoder::{self, MessageBody};
+use serde::Serialize;
+
+/// 浮点字段允许的误差（与文档给出的小数位数一致）
+const FLOAT_TOLERANCE: f64 = 0.01;
+
+/// 单个测试向量
+struct TestVector {
+    name: &'static str,
+    hex: &'static str,
+    icao: &'static str,
+    type_code: u8,
+    body: MessageBody,
+}
+
+/// 单个测试向量的校验结果
+#[derive(Debug, Clone, Serialize)]
+pub struct VectorResult {
+    pub name: String,
+    pub hex: String,
+    pub passed: bool,
+    pub mismatches: Vec<String>, // 与期望值不符的字段说明
+}
+
+fn vectors() -> Vec<TestVector> {
+    vec![
+        TestVector {
+            name: "identification KLM1023",
+            hex: "8D4840D6202CC371C32CE0576098",
+            icao: "4840D6",
+            type_code: 4,
+            body: MessageBody::Identification {
+                category: 0,
+                callsign: "KLM1023".to_string(),
+            },
+        },
+        TestVector {
+            name: "identification EZY85MH",
+            hex: "8D406B902015A678D4D220AA4BDA",
+            icao: "406B90",
+            type_code: 4,
+            body: MessageBody::Identification {
+                category: 0,
+                callsign: "EZY85MH".to_string(),
+            },
+        },
+        TestVector {
+            name: "airborne position, even frame",
+            hex: "8D40621D58C382D690C8AC2863A7",
+            icao: "40621D",
+            type_code: 11,
+            body: MessageBody::Position {
+                altitude: Some(38000.0),
+                odd: false,
+                lat_cpr: 93000,
+                lng_cpr: 51372,
+            },
+        },
+        TestVector {
+            name: "airborne position, odd frame",
+            hex: "8D40621D58C386435CC412692AD6",
+            icao: "40621D",
+            type_code: 11,
+            body: MessageBody::Position {
+                altitude: Some(38000.0),
+                odd: true,
+                lat_cpr: 74158,
+                lng_cpr: 50194,
+            },
+        },
+        TestVector {
+            name: "airborne velocity, ground speed",
+            hex: "8D485020994409940838175B284F",
+            icao: "485020",
+            type_code: 19,
+            body: MessageBody::Velocity {
+                speed: Some(159.20),
+                heading: Some(182.88),
+                nacv: 0,
+                vertical_rate: Some(-832.0),
+            },
+        },
+        TestVector {
+            name: "airborne velocity, airspeed",
+            hex: "8DA05F219B06B6AF189400CBC33F",
+            icao: "A05F21",
+            type_code: 19,
+            body: MessageBody::Velocity {
+                speed: Some(375.0),
+                heading: Some(243.98),
+                nacv: 0,
+                vertical_rate: Some(-2304.0),
+            },
+        },
+    ]
+}
+
+/// 比较可选浮点值，允许 `FLOAT_TOLERANCE` 的误差
+fn close(actual: Option<f64>, expected: Option<f64>) -> bool {
+    match (actual, expected) {
+        (Some(a), Some(e)) => (a - e).abs() <= FLOAT_TOLERANCE,
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// 比较解码内容，返回不符的字段说明
+fn compare_body(actual: &MessageBody, expected: &MessageBody) -> Vec<String> {
+    let mut mismatches = Vec::new();
+    let mut check = |field: &str, ok: bool, actual: String, expected: String| {
+        if !ok {
+            mismatches.push(format!("{}: expected {}, got {}", field, expected, actual));
+        }
+    };
+    match (actual, expected) {
+        (
+            MessageBody::Position {
+                altitude,
+                odd,
+                lat_cpr,
+                lng_cpr,
+            },
+            MessageBody::Position {
+                altitude: e_altitude,
+                odd: e_odd,
+                lat_cpr: e_lat_cpr,
+                lng_cpr: e_lng_cpr,
+            },
+        ) => {
+            let show = |v: &Option<f64>| format!("{:?}", v);
+            check(
+                "altitude",
+                close(*altitude, *e_altitude),
+                show(altitude),
+                show(e_altitude),
+            );
+            check("odd", odd == e_odd, odd.to_string(), e_odd.to_string());
+            check(
+                "lat_cpr",
+                lat_cpr == e_lat_cpr,
+                lat_cpr.to_string(),
+                e_lat_cpr.to_string(),
+            );
+            check(
+                "lng_cpr",
+                lng_cpr == e_lng_cpr,
+                lng_cpr.to_string(),
+                e_lng_cpr.to_string(),
+            );
+        }
+        (
+            MessageBody::Velocity {
+                speed,
+                heading,
+                nacv,
+                vertical_rate,
+            },
+            MessageBody::Velocity {
+                speed: e_speed,
+                heading: e_heading,
+                nacv: e_nacv,
+                vertical_rate: e_vertical_rate,
+            },
+        ) => {
+            check("nacv", nacv == e_nacv, nacv.to_string(), e_nacv.to_string());
+            for (field, a, e) in [
+                ("speed", speed, e_speed),
+                ("heading", heading, e_heading),
+                ("vertical_rate", vertical_rate, e_vertical_rate),
+            ] {
+                check(field, close(*a, *e), format!("{:?}", a), format!("{:?}", e));
+            }
+        }
+        _ => check(
+            "body",
+            actual == expected,
+            format!("{:?}", actual),
+            format!("{:?}", expected),
+        ),
+    }
+    mismatches
+}
+
+/// 解码全部内置测试向量并与期望值比较
+pub fn run_test_vectors() -> Vec<VectorResult> {
+    vectors()
+        .into_iter()
+        .map(|vector| {
+            let mut mismatches = Vec::new();
+            match decoder::crc_syndrome(vector.hex) {
+                Ok(0) => {}
+                Ok(syndrome) => mismatches.push(format!("CRC syndrome {:06X}", syndrome)),
+                Err(e) => mismatches.push(e.to_string()),
+            }
+            match decoder::decode_message(vector.hex) {
+                Ok(decoded) => {
+                    if decoded.icao != vector.icao {
+                        mismatches.push(format!(
+                            "icao: expected {}, got {}",
+                            vector.icao, decoded.icao
+                        ));
+                    }
+                    if decoded.type_code != vector.type_code {
+                        mismatches.push(format!(
+                            "type_code: expected {}, got {}",
+                            vector.type_code, decoded.type_code
+                        ));
+                    }
+                    mismatches.extend(compare_body(&decoded.body, &vector.body));
+                }
+                Err(e) => mismatches.push(e.to_string()),
+            }
+            VectorResult {
+                name: vector.name.to_string(),
+                hex: vector.hex.to_string(),
+                passed: mismatches.is_empty(),
+                mismatches,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_vectors_pass() {
+        let results = run_test_vectors();
+        assert_eq!(results.len(), vectors().len());
+        for result in &results {
+            assert!(
+                result.passed,
+                "{} ({}): {:?}",
+                result.name, result.hex, result.mismatches
+            );
+        }
+
+        // 期望值不符时报告具体字段
+        let wrong = MessageBody::Velocity {
+            speed: Some(100.0),
+            heading: Some(182.88),
+            nacv: 0,
+            vertical_rate: Some(-832.0),
+        };
+        let decoded = decoder::decode_message("8D485020994409940838175B284F").unwrap();
+        let mismatches = compare_body(&decoded.body, &wrong);
+        assert_eq!(mismatches.len(), 1);
+        assert!(mismatches[0].starts_with("speed"));
+    }
+}
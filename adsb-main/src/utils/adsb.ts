@@ -28,61 +28,160 @@ export interface DecodedIdentification {
 
 export type DecodedData = DecodedPosition | DecodedVelocity | DecodedIdentification | null;
 
+// CPR (Compact Position Reporting) parameters, matching the Rust encoder
+const CPR_NZ = 15;
+const CPR_SCALE = 131072;
+// Even/odd frames further apart than this are not paired for a global decode
+const CPR_PAIR_TIMEOUT_MS = 10000;
+// Highest altitude the 25 ft Q-bit encoding can represent; above it Gillham (100 ft) is used
+const Q_BIT_MAX_ALTITUDE_FT = 50175;
+const GILLHAM_MAX_ALTITUDE_FT = 126700;
+
+// Airborne position type code (TC 9-18) for each NIC, as sent by the Rust simulator; NIC 0 uses TC 18
+const NIC_TYPE_CODES: Array<[number, number]> = [
+  [1, 17], [2, 16], [3, 16], [4, 15], [5, 14], [6, 13], [7, 12], [8, 11], [9, 11], [10, 10], [11, 9],
+];
+
+const modulo = (x: number, y: number) => x - y * Math.floor(x / y);
+
+// Number of longitude zones (NL) at a latitude
+function cprNL(lat: number): number {
+  const absLat = Math.abs(lat);
+  if (absLat < 1e-9) return 59;
+  if (absLat >= 87) return absLat > 87 ? 1 : 2;
+  const a = 1 - Math.cos(Math.PI / (2 * CPR_NZ));
+  const b = Math.cos((Math.PI / 180) * absLat) ** 2;
+  return Math.floor((2 * Math.PI) / Math.acos(1 - a / b));
+}
+
+// Airborne CPR encoding, returns the 17-bit [YZ, XZ]
+export function cprEncode(lat: number, lng: number, odd: boolean): [number, number] {
+  const i = odd ? 1 : 0;
+  const dLat = 360 / (4 * CPR_NZ - i);
+  const yz = Math.floor((CPR_SCALE * modulo(lat, dLat)) / dLat + 0.5);
+  const rLat = dLat * (yz / CPR_SCALE + Math.floor(lat / dLat));
+  const dLng = 360 / Math.max(cprNL(rLat) - i, 1);
+  const xz = Math.floor((CPR_SCALE * modulo(lng, dLng)) / dLng + 0.5);
+  return [yz & 0x1FFFF, xz & 0x1FFFF];
+}
+
+// Global decode of an even/odd airborne CPR pair; the result is the position of the newer frame.
+// Returns null when the two frames straddle a longitude zone boundary.
+export function cprDecodeGlobal(
+  even: [number, number],
+  odd: [number, number],
+  latestOdd: boolean
+): { lat: number; lng: number } | null {
+  const [latEven, lngEven] = [even[0] / CPR_SCALE, even[1] / CPR_SCALE];
+  const [latOdd, lngOdd] = [odd[0] / CPR_SCALE, odd[1] / CPR_SCALE];
+
+  const j = Math.floor(59 * latEven - 60 * latOdd + 0.5);
+  const wrap = (lat: number) => (lat >= 270 ? lat - 360 : lat);
+  const rLatEven = wrap((360 / 60) * (modulo(j, 60) + latEven));
+  const rLatOdd = wrap((360 / 59) * (modulo(j, 59) + latOdd));
+  if (cprNL(rLatEven) !== cprNL(rLatOdd)) return null;
+
+  const lat = latestOdd ? rLatOdd : rLatEven;
+  const nl = cprNL(lat);
+  const m = Math.floor(lngEven * (nl - 1) - lngOdd * nl + 0.5);
+  const n = Math.max(nl - (latestOdd ? 1 : 0), 1);
+  const lng = (360 / n) * (modulo(m, n) + (latestOdd ? lngOdd : lngEven));
+  return { lat, lng: lng >= 180 ? lng - 360 : lng };
+}
+
+// Encode the 12-bit airborne altitude field (ft): 25 ft steps with the Q bit set,
+// Gillham gray code in 100 ft steps above 50175 ft. Non-finite values encode as 0 (unavailable).
+export function encodeAltitude(alt: number): number {
+  if (!Number.isFinite(alt)) return 0;
+  if (alt <= Q_BIT_MAX_ALTITUDE_FT) {
+    const n = Math.min(Math.max(Math.round((alt + 1000) / 25), 0), 0x7FF);
+    return ((n & 0x7F0) << 1) | 0x010 | (n & 0x00F);
+  }
+
+  const hundreds = Math.round(Math.min(alt, GILLHAM_MAX_ALTITUDE_FT) / 100) + 12;
+  const fiveHundreds = Math.floor(hundreds / 5);
+  let oneHundreds = (hundreds % 5) + 1;
+  if (fiveHundreds & 1) oneHundreds = 6 - oneHundreds;
+  if (oneHundreds === 5) oneHundreds = 7; // 5 and 7 are swapped in the gray code
+  const gray500 = fiveHundreds ^ (fiveHundreds >> 1);
+  const gray100 = oneHundreds ^ (oneHundreds >> 1);
+
+  // AC bit order: C1 A1 C2 A2 C4 A4 M B1 Q B2 D2 B4 D4; the 500 ft bits are D2 D4 A1 A2 A4 B1 B2 B4
+  const bit = (value: number, index: number, mask: number) => ((value >> index) & 1 ? mask : 0);
+  const ac13 = bit(gray100, 2, 0x1000) | bit(gray100, 1, 0x0400) | bit(gray100, 0, 0x0100)
+    | bit(gray500, 7, 0x0004) | bit(gray500, 6, 0x0001) | bit(gray500, 5, 0x0800)
+    | bit(gray500, 4, 0x0200) | bit(gray500, 3, 0x0080) | bit(gray500, 2, 0x0020)
+    | bit(gray500, 1, 0x0008) | bit(gray500, 0, 0x0002);
+  // Drop the M bit to get the 12-bit field
+  return ((ac13 & 0x1F80) >> 1) | (ac13 & 0x003F);
+}
+
+// Decode the 12-bit airborne altitude field (ft), null when unavailable or invalid
+export function decodeAltitude(ac12: number): number | null {
+  ac12 &= 0xFFF;
+  if (ac12 === 0) return null;
+  if (ac12 & 0x010) {
+    const n = ((ac12 & 0xFE0) >> 1) | (ac12 & 0x00F);
+    return n * 25 - 1000;
+  }
+
+  // Re-insert the M bit and decode the Gillham code
+  const ac13 = ((ac12 & 0xFC0) << 1) | (ac12 & 0x03F);
+  const bit = (mask: number) => (ac13 & mask) !== 0;
+  // D1 is unused for altitude, and all-zero C bits are illegal
+  if (bit(0x0010) || !(bit(0x1000) || bit(0x0400) || bit(0x0100))) return null;
+
+  let oneHundreds = 0;
+  if (bit(0x1000)) oneHundreds ^= 0x7;
+  if (bit(0x0400)) oneHundreds ^= 0x3;
+  if (bit(0x0100)) oneHundreds ^= 0x1;
+  if ((oneHundreds & 5) === 5) oneHundreds ^= 2; // swap 7 and 5
+  if (oneHundreds > 5) return null;
+
+  let fiveHundreds = 0;
+  // D2 D4 A1 A2 A4 B1 B2 B4
+  const grayBits: Array<[number, number]> = [
+    [0x0004, 0x0FF], [0x0001, 0x07F], [0x0800, 0x03F], [0x0200, 0x01F],
+    [0x0080, 0x00F], [0x0020, 0x007], [0x0008, 0x003], [0x0002, 0x001],
+  ];
+  for (const [mask, xor] of grayBits) {
+    if (bit(mask)) fiveHundreds ^= xor;
+  }
+  if (fiveHundreds & 1) oneHundreds = 6 - oneHundreds;
+
+  return (fiveHundreds * 5 + oneHundreds - 13) * 100;
+}
+
 export class AdsbSimulator {
-  // Simulate generating a DF17 Position Message (Airborne Position)
-  // Note: This uses a simplified CPR-like encoding for demonstration. 
-  // Real CPR is much more complex (Odd/Even frames, zones).
+  // Next CPR frame parity per ICAO: position messages alternate between even and odd frames
+  private static nextOdd = new Map<string, boolean>();
+
+  // Generate a DF17 airborne position message (TC 9-18) in the same encoding as the Rust simulator:
+  // the type code carries the NIC, altitude uses the Q-bit/Gillham field, position is CPR encoded.
   static generatePositionMessage(icao: string, lat: number, lng: number, alt: number, nic: number): string {
     const df = 17;
     const ca = 5;
     const icaoInt = parseInt(icao, 16);
-    
-    // Type Code 11 (Airborne Position w/ Baro Alt)
-    const typeCode = 11;
-    const _surveillanceStatus = 0;
-    const _singleAntenna = 1;
-    
-    // Encode Altitude (Simplified: 12 bits)
-    // Real ADS-B uses Gillham code or binary. We'll use simple binary for this sim.
-    const altEncoded = Math.floor((alt + 1000) / 25) & 0xFFF;
-    
-    // Encode Time (1 bit) - CPR Odd/Even
-    const time = 0; // Even frame
-    
-    // Encode CPR Format (1 bit)
-    const cprFormat = 0; 
-    
-    // Encode Lat/Lon (17 bits each)
-    // Simplified: Map -90..90 to 0..131071
-    const latEncoded = Math.floor(((lat + 90) / 180) * 131071) & 0x1FFFF;
-    const lngEncoded = Math.floor(((lng + 180) / 360) * 131071) & 0x1FFFF;
-
-    // Construct 56-bit Payload
-    // [Type:5][Surv:2][NIC:1][Alt:12][Time:1][CPR:1][Lat:17][Lon:17]
-    // Wait, NIC is not directly in this position in standard. 
-    // In Type 9-18, NIC supplement B is used. 
-    // For simulation purposes, we will pack NIC into the "Surveillance Status" or similar unused bits 
-    // or just assume the Type Code implies a certain NIC range.
-    // Let's use the standard structure roughly:
-    // Bits: 1-5 Type, 6-7 Surv, 8 NIC-B, 9-20 Alt, 21 Time, 22 CPR, 23-39 Lat, 40-56 Lon
-    
-    // We will embed the passed NIC value (0-11) into the message to be decoded later.
-    // We'll use the Type Code to represent NIC roughly, or just pack it in unused bits for this sim.
-    // Let's pack NIC into bits 6-9 (4 bits) replacing Surv/NIC-B for this custom sim
-    const nicEncoded = nic & 0xF;
 
+    const odd = this.nextOdd.get(icao) ?? false;
+    this.nextOdd.set(icao, !odd);
+
+    const typeCode = NIC_TYPE_CODES.find(([level]) => level === nic)?.[1] ?? 18;
+    const [latCpr, lngCpr] = cprEncode(lat, lng, odd);
+
+    // [Type:5][Surv:2][NIC-B:1][Alt:12][Time:1][CPR:1][Lat:17][Lon:17]
     let payload = BigInt(0);
     payload |= BigInt(typeCode) << 51n;
-    payload |= BigInt(nicEncoded) << 47n; // Custom packing for simulation
-    payload |= BigInt(altEncoded) << 35n;
-    payload |= BigInt(time) << 34n;
-    payload |= BigInt(cprFormat) << 33n;
-    payload |= BigInt(latEncoded) << 16n;
-    payload |= BigInt(lngEncoded);
+    payload |= BigInt(encodeAltitude(alt)) << 36n;
+    payload |= BigInt(odd ? 1 : 0) << 34n;
+    payload |= BigInt(latCpr) << 17n;
+    payload |= BigInt(lngCpr);
 
     return this.assembleMessage(df, ca, icaoInt, payload);
   }
 
+  // Generate a DF17 airborne velocity message (TC 19 subtype 1) with east/west and north/south
+  // ground speed components, matching the Rust encoder
   static generateVelocityMessage(icao: string, speed: number, heading: number): string {
     const df = 17;
     const ca = 5;
@@ -91,17 +190,19 @@ export class AdsbSimulator {
     // Type Code 19 (Airborne Velocity)
     const typeCode = 19;
     const subType = 1; // Ground speed
-    
-    // Encode Speed/Heading
-    const speedEncoded = Math.floor(speed) & 0x3FF; // 10 bits
-    const headingEncoded = Math.floor((heading / 360) * 127) & 0x7F; // 7 bits (simplified)
+
+    // Direction bit 1 means west/south; the value is |v| + 1 (0 means unavailable)
+    const track = (heading * Math.PI) / 180;
+    const component = (v: number) => {
+      const magnitude = Math.min(Math.max(Math.round(Math.abs(v)), 0), 0x3FE) + 1;
+      return (BigInt(v < 0 ? 1 : 0) << 10n) | BigInt(magnitude);
+    };
     
     let payload = BigInt(0);
     payload |= BigInt(typeCode) << 51n;
     payload |= BigInt(subType) << 48n;
-    // ... simplified packing ...
-    payload |= BigInt(speedEncoded) << 30n;
-    payload |= BigInt(headingEncoded) << 20n;
+    payload |= component(speed * Math.sin(track)) << 32n;
+    payload |= component(speed * Math.cos(track)) << 21n;
 
     return this.assembleMessage(df, ca, icaoInt, payload);
   }
@@ -126,6 +227,12 @@ export class AdsbSimulator {
 }
 
 export class AdsbDecoder {
+  // Latest even/odd CPR frame per ICAO, used for the global position decode
+  private static cprFrames = new Map<string, {
+    even?: { cpr: [number, number]; time: number };
+    odd?: { cpr: [number, number]; time: number };
+  }>();
+
   // Mock Demodulation: Convert "Signal Strength" array to bits (Concept only)
   static demodulate(_signal: number[]): string {
     // In a real system, this would detect preamble and decode PPM pulses.
@@ -133,7 +240,13 @@ export class AdsbDecoder {
     return "RAW_BITS_STREAM";
   }
 
-  static decodeMessage(hexMsg: string): { icao: string, data: DecodedData } | null {
+  // Forget all buffered CPR frames (e.g. when the displayed state is rebuilt)
+  static reset() {
+    this.cprFrames.clear();
+  }
+
+  // `time` (ms) is used to decide whether an even and an odd frame are recent enough to pair
+  static decodeMessage(hexMsg: string, time: number = Date.now()): { icao: string, data: DecodedData } | null {
     if (hexMsg.length !== 28) return null;
 
     const msgInt = BigInt('0x' + hexMsg);
@@ -149,7 +262,7 @@ export class AdsbDecoder {
 
     // Decode based on Type Code
     if (typeCode >= 9 && typeCode <= 18) {
-      return { icao, data: this.decodePosition(payload) };
+      return { icao, data: this.decodePosition(icao, typeCode, payload, time) };
     } else if (typeCode === 19) {
       return { icao, data: this.decodeVelocity(payload) };
     }
@@ -157,35 +270,64 @@ export class AdsbDecoder {
     return { icao, data: null };
   }
 
-  private static decodePosition(payload: bigint): DecodedPosition {
-    // Reverse the packing in Simulator
-    const nicEncoded = Number((payload >> 47n) & 0xFn);
-    const altEncoded = Number((payload >> 35n) & 0xFFFn);
-    const latEncoded = Number((payload >> 16n) & 0x1FFFFn);
-    const lngEncoded = Number(payload & 0x1FFFFn);
+  // Airborne position: needs a recent even/odd pair from the same ICAO, otherwise null
+  private static decodePosition(icao: string, typeCode: number, payload: bigint, time: number): DecodedPosition | null {
+    const odd = ((payload >> 34n) & 1n) === 1n;
+    const cpr: [number, number] = [Number((payload >> 17n) & 0x1FFFFn), Number(payload & 0x1FFFFn)];
 
-    // Decode
-    const alt = (altEncoded * 25) - 1000;
-    const lat = (latEncoded / 131071) * 180 - 90;
-    const lng = (lngEncoded / 131071) * 360 - 180;
+    const frames = this.cprFrames.get(icao) ?? {};
+    frames[odd ? 'odd' : 'even'] = { cpr, time };
+    this.cprFrames.set(icao, frames);
+    if (!frames.even || !frames.odd || Math.abs(frames.even.time - frames.odd.time) > CPR_PAIR_TIMEOUT_MS) {
+      return null;
+    }
+
+    const position = cprDecodeGlobal(frames.even.cpr, frames.odd.cpr, odd);
+    if (!position) return null;
 
     return {
       type: 'position',
-      lat,
-      lng,
-      altitude: alt,
-      nic: nicEncoded
+      lat: position.lat,
+      lng: position.lng,
+      altitude: decodeAltitude(Number((payload >> 36n) & 0xFFFn)) ?? 0,
+      // TC 11 and TC 16 each cover two NIC values; report the lower one
+      nic: NIC_TYPE_CODES.find(([, code]) => code === typeCode)?.[0] ?? 0
     };
   }
 
-  private static decodeVelocity(payload: bigint): DecodedVelocity {
-    const speedEncoded = Number((payload >> 30n) & 0x3FFn);
-    const headingEncoded = Number((payload >> 20n) & 0x7Fn);
+  // Airborne velocity subtypes 1/2: ground speed from the east/west and north/south components
+  // (subtype 2 is supersonic, 4 kt units); subtypes 3/4 carry heading and airspeed
+  private static decodeVelocity(payload: bigint): DecodedVelocity | null {
+    const subType = Number((payload >> 48n) & 0x7n);
+    const scale = subType === 2 || subType === 4 ? 4 : 1;
+    const magnitude = (raw: number) => (raw === 0 ? null : (raw - 1) * scale);
 
-    return {
-      type: 'velocity',
-      speed: speedEncoded,
-      heading: (headingEncoded / 127) * 360
-    };
+    if (subType === 1 || subType === 2) {
+      const component = (shift: bigint) => {
+        const value = magnitude(Number((payload >> shift) & 0x3FFn));
+        if (value === null) return null;
+        return ((payload >> (shift + 10n)) & 1n) === 1n ? -value : value;
+      };
+      const east = component(32n);
+      const north = component(21n);
+      if (east === null || north === null) return null;
+      return {
+        type: 'velocity',
+        speed: Math.hypot(east, north),
+        heading: modulo((Math.atan2(east, north) * 180) / Math.PI, 360)
+      };
+    }
+
+    if (subType === 3 || subType === 4) {
+      const speed = magnitude(Number((payload >> 21n) & 0x3FFn));
+      if (speed === null || ((payload >> 42n) & 1n) === 0n) return null;
+      return {
+        type: 'velocity',
+        speed,
+        heading: (Number((payload >> 32n) & 0x3FFn) * 360) / 1024
+      };
+    }
+
+    return null;
   }
 }